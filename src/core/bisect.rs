@@ -0,0 +1,298 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+
+use crate::core::{
+    commit::Commit,
+    errors::NyxError,
+    index::index::Index,
+    refs::refs,
+    rev_walk::ancestors,
+    revision::resolve_commit,
+    sequencer::short_hash,
+    tree::tree::Tree,
+    worktree::{reset_hard, staged_changes, unstaged_changes},
+};
+use crate::FILE_SYSTEM;
+
+const START_FILE: &str = "BISECT_START";
+const LOG_FILE: &str = "BISECT_LOG";
+const BAD_REF: &str = "bisect/bad";
+const GOOD_PREFIX: &str = "bisect/good-";
+const SKIP_PREFIX: &str = "bisect/skip-";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mark {
+    Good,
+    Bad,
+    Skip,
+}
+
+impl Mark {
+    fn name(&self) -> &str {
+        match self {
+            Mark::Good => "good",
+            Mark::Bad => "bad",
+            Mark::Skip => "skip",
+        }
+    }
+}
+
+/// What the search looks like after marking a commit.
+#[derive(Debug)]
+pub enum BisectStep {
+    /// At least one good and one bad commit are needed
+    Waiting,
+    /// The commit that was checked out for testing
+    Testing(Commit),
+    /// The first bad commit
+    Found(Commit),
+    /// Only skipped commits are left, any of them could be the culprit
+    OnlySkipped(Vec<String>),
+}
+
+/// A binary search for the commit that introduced a regression.
+/// The marks are kept as refs below `refs/bisect/`.
+pub struct Bisect {
+    bad: Option<String>,
+    good: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl Bisect {
+    pub fn in_progress() -> bool {
+        FILE_SYSTEM.get_state_file_path(START_FILE).exists()
+    }
+
+    pub fn load() -> Result<Self, NyxError> {
+        if !Bisect::in_progress() {
+            return Err(NyxError::Usage(String::from(
+                "You need to start by \"nyx bisect start\"",
+            )));
+        }
+        let marked = |prefix: &str| -> Vec<String> {
+            refs::list_refs("bisect")
+                .into_iter()
+                .filter(|(name, _)| name.starts_with(prefix))
+                .map(|(_, hash)| hash)
+                .collect()
+        };
+        Ok(Self {
+            bad: refs::read_ref(BAD_REF),
+            good: marked(GOOD_PREFIX),
+            skipped: marked(SKIP_PREFIX),
+        })
+    }
+
+    /// Starts a search, remembering where HEAD was to return there on reset.
+    pub fn start() -> Result<(), NyxError> {
+        if Bisect::in_progress() {
+            Bisect::reset()?;
+        }
+        let head = Commit::read(&refs::head_hash())?;
+        let index = Index::new();
+        let head_paths = Tree::read_paths(Some(head.tree_hash()));
+        if !staged_changes(&index, &head_paths).is_empty() || !unstaged_changes(&index).is_empty() {
+            return Err(NyxError::Usage(String::from(
+                "cannot bisect: You have unstaged or uncommitted changes.",
+            )));
+        }
+
+        let start = refs::current_branch().unwrap_or_else(|| head.get_hash().to_string());
+        fs::write(
+            FILE_SYSTEM.get_state_file_path(START_FILE),
+            format!("{}\n", start),
+        )?;
+        fs::write(
+            FILE_SYSTEM.get_state_file_path(LOG_FILE),
+            "nyx bisect start\n",
+        )?;
+        Ok(())
+    }
+
+    /// Marks the given commits and records it in the bisect log.
+    pub fn mark(&mut self, mark: Mark, revs: &[String]) -> Result<(), NyxError> {
+        let head = [String::from(refs::HEAD)];
+        let revs = if revs.is_empty() { &head[..] } else { revs };
+        if mark == Mark::Bad && revs.len() > 1 {
+            return Err(NyxError::Usage(String::from(
+                "'nyx bisect bad' can take only one argument.",
+            )));
+        }
+
+        let mut log = fs::read_to_string(FILE_SYSTEM.get_state_file_path(LOG_FILE))?;
+        for rev in revs {
+            let commit = resolve_commit(rev)?;
+            let hash = commit.get_hash().to_string();
+            match mark {
+                Mark::Bad => {
+                    refs::write_ref(BAD_REF, &hash)?;
+                    self.bad = Some(hash.clone());
+                }
+                Mark::Good => {
+                    refs::write_ref(&format!("{}{}", GOOD_PREFIX, hash), &hash)?;
+                    self.good.push(hash.clone());
+                }
+                Mark::Skip => {
+                    refs::write_ref(&format!("{}{}", SKIP_PREFIX, hash), &hash)?;
+                    self.skipped.push(hash.clone());
+                }
+            }
+            log.push_str(&format!(
+                "# {}: [{}] {}\nnyx bisect {} {}\n",
+                mark.name(),
+                hash,
+                commit.summary(),
+                mark.name(),
+                hash
+            ));
+        }
+        fs::write(FILE_SYSTEM.get_state_file_path(LOG_FILE), log)?;
+        Ok(())
+    }
+
+    /// Checks out the next commit to test or reports the result.
+    pub fn next(&self) -> Result<BisectStep, NyxError> {
+        let bad = match &self.bad {
+            Some(bad) if !self.good.is_empty() => bad.clone(),
+            _ => return Ok(BisectStep::Waiting),
+        };
+
+        // Suspects are reachable from the bad commit but from no good one
+        let cleared = ancestors(&self.good);
+        let mut suspects: HashMap<String, Vec<String>> = HashMap::new();
+        let mut queue = VecDeque::from([bad.clone()]);
+        while let Some(hash) = queue.pop_front() {
+            if cleared.contains(&hash) || suspects.contains_key(&hash) {
+                continue;
+            }
+            let commit = Commit::read(&hash)?;
+            queue.extend(commit.parent_hashes().iter().cloned());
+            suspects.insert(hash, commit.parent_hashes().clone());
+        }
+        if suspects.is_empty() {
+            return Err(NyxError::Usage(String::from(
+                "the bad commit is an ancestor of a good commit",
+            )));
+        }
+        if suspects.len() == 1 {
+            return Ok(BisectStep::Found(Commit::read(&bad)?));
+        }
+
+        let testable: Vec<&String> = suspects
+            .keys()
+            .filter(|hash| **hash != bad && !self.skipped.contains(hash))
+            .collect();
+        if testable.is_empty() {
+            let mut left: Vec<String> = suspects.into_keys().collect();
+            left.sort();
+            return Ok(BisectStep::OnlySkipped(left));
+        }
+
+        // The best commit splits the suspects in two halves of equal size
+        let total = suspects.len();
+        let mut best: Option<(usize, &String)> = None;
+        for hash in testable {
+            let reached = reachable_within(hash, &suspects);
+            let score = reached.min(total - reached);
+            if best.is_none_or(|(best_score, best_hash)| {
+                score > best_score || (score == best_score && hash < best_hash)
+            }) {
+                best = Some((score, hash));
+            }
+        }
+        let commit = Commit::read(best.unwrap().1)?;
+        checkout(&commit)?;
+
+        let left = total / 2;
+        let steps = usize::BITS - left.leading_zeros();
+        println!(
+            "Bisecting: {} revision{} left to test after this (roughly {} step{})",
+            left,
+            if left == 1 { "" } else { "s" },
+            steps,
+            if steps == 1 { "" } else { "s" }
+        );
+        println!("[{}] {}", commit.get_hash(), commit.summary());
+        Ok(BisectStep::Testing(commit))
+    }
+
+    /// Returns to the commit or branch checked out before the search.
+    pub fn reset() -> Result<(), NyxError> {
+        if !Bisect::in_progress() {
+            println!("We are not bisecting.");
+            return Ok(());
+        }
+        let start = fs::read_to_string(FILE_SYSTEM.get_state_file_path(START_FILE))?;
+        let start = start.trim();
+        let hash = refs::read_ref(start).unwrap_or_else(|| start.to_string());
+        let commit = Commit::read(&hash)?;
+        reset_hard(
+            &mut Index::new(),
+            &Tree::read_paths(Some(commit.tree_hash())),
+        )?;
+
+        let current = refs::head_hash();
+        let message = format!("checkout: moving from {} to {}", current, start);
+        if refs::ref_exists(start) {
+            refs::set_head_to_branch(start)?;
+            refs::append_head_log(&current, &hash, &message)?;
+        } else {
+            refs::detach_head(&hash, &message)?;
+        }
+
+        for (name, _) in refs::list_refs("bisect") {
+            refs::delete_ref(&name)?;
+        }
+        fs::remove_file(FILE_SYSTEM.get_state_file_path(START_FILE))?;
+        fs::remove_file(FILE_SYSTEM.get_state_file_path(LOG_FILE))?;
+        if let Some(previous) = Commit::from_hash(&current) {
+            println!(
+                "Previous HEAD position was {} {}",
+                short_hash(&current),
+                previous.summary()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn log() -> Result<String, NyxError> {
+        Bisect::load()?;
+        Ok(fs::read_to_string(
+            FILE_SYSTEM.get_state_file_path(LOG_FILE),
+        )?)
+    }
+
+    /// Appends a line like `# first bad commit: [...]` to the log.
+    pub fn log_result(&self, line: &str) -> Result<(), NyxError> {
+        let mut log = fs::read_to_string(FILE_SYSTEM.get_state_file_path(LOG_FILE))?;
+        log.push_str(&format!("# {}\n", line));
+        fs::write(FILE_SYSTEM.get_state_file_path(LOG_FILE), log)?;
+        Ok(())
+    }
+}
+
+/// Number of suspects reachable from `start`, including itself.
+fn reachable_within(start: &str, suspects: &HashMap<String, Vec<String>>) -> usize {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(hash) = queue.pop_front() {
+        if let Some(parents) = suspects.get(hash) {
+            if seen.insert(hash) {
+                queue.extend(parents.iter().map(String::as_str));
+            }
+        }
+    }
+    seen.len()
+}
+
+fn checkout(commit: &Commit) -> Result<(), NyxError> {
+    let current = refs::head_hash();
+    reset_hard(
+        &mut Index::new(),
+        &Tree::read_paths(Some(commit.tree_hash())),
+    )?;
+    refs::detach_head(
+        commit.get_hash(),
+        &format!("checkout: moving from {} to {}", current, commit.get_hash()),
+    )
+}
@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+use super::commit::Commit;
+use super::diff::myers::{diff, DiffOp};
+use super::errors::NyxError;
+use super::object_type::NyxObjectType;
+use super::shared::read_object_data;
+use super::tree::tree::Tree;
+
+/// Minimal share of equal lines for a deleted file to be
+/// considered the origin of an added one.
+const RENAME_SIMILARITY: f64 = 0.5;
+
+/// The commit that introduced a line of the blamed file.
+#[derive(Debug, Clone)]
+pub struct BlameLine {
+    pub commit: String,
+    /// Path of the file in `commit`, differs from the blamed path after renames
+    pub path: String,
+    /// Line number in the version of the file in `commit`
+    pub original_line: usize,
+    /// Line number in the blamed version of the file
+    pub final_line: usize,
+    pub content: String,
+    /// Whether `commit` has no parents
+    pub boundary: bool,
+}
+
+/// Lines of one version of the file still looking for their origin,
+/// as `(final index, index in this version)` pairs.
+type Pending = Vec<(usize, usize)>;
+
+fn blob_lines(hash: &str) -> Vec<String> {
+    read_object_data(hash)
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+fn blob_at(commit: &Commit, path: &str) -> Option<String> {
+    match Tree::lookup(commit.tree_hash(), path)? {
+        (NyxObjectType::Blob, hash) => Some(hash),
+        _ => None,
+    }
+}
+
+fn similarity(a: &[String], b: &[String]) -> f64 {
+    let equal = diff(a, b)
+        .iter()
+        .filter(|op| matches!(op, DiffOp::Equal(..)))
+        .count();
+    equal as f64 / a.len().max(b.len()).max(1) as f64
+}
+
+/// Looks for the path `path` had in `parent` when it does not exist
+/// there: a file deleted by `commit` with the same or similar content.
+fn find_rename(commit: &Commit, parent: &Commit, blob: &str) -> Option<String> {
+    let paths = Tree::read_paths(Some(commit.tree_hash()));
+    let candidates: Vec<(String, String)> = Tree::read_paths(Some(parent.tree_hash()))
+        .into_iter()
+        .filter(|(path, _)| !paths.contains_key(path))
+        .collect();
+
+    if let Some((path, _)) = candidates.iter().find(|(_, hash)| hash == blob) {
+        return Some(path.clone());
+    }
+    let lines = blob_lines(blob);
+    candidates
+        .into_iter()
+        .map(|(path, hash)| (similarity(&blob_lines(&hash), &lines), path))
+        .filter(|(score, _)| *score >= RENAME_SIMILARITY)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, path)| path)
+}
+
+fn commit_time(commit: &Commit) -> i64 {
+    commit
+        .committer()
+        .or_else(|| commit.author())
+        .map(|s| s.time)
+        .unwrap_or(0)
+}
+
+/// Attributes each line of `path` as of `start` to the commit that
+/// introduced it. Only lines within `range` (0 based, exclusive end)
+/// are blamed. Lines are passed on to the parents as long as a diff
+/// shows them unchanged, following the file across renames.
+pub fn blame(
+    start: &Commit,
+    path: &str,
+    range: Option<(usize, usize)>,
+) -> Result<Vec<BlameLine>, NyxError> {
+    let blob = blob_at(start, path)
+        .ok_or_else(|| NyxError::InvalidRevision(format!("no such path '{}'", path)))?;
+    let final_lines = blob_lines(&blob);
+    let (from, to) = range.unwrap_or((0, final_lines.len()));
+    if from > 0 && from >= final_lines.len() {
+        let count = final_lines.len();
+        return Err(NyxError::Usage(format!(
+            "file {} has only {} line{}",
+            path,
+            count,
+            if count == 1 { "" } else { "s" }
+        )));
+    }
+    let to = to.min(final_lines.len());
+
+    let mut commits: HashMap<String, Commit> = HashMap::new();
+    let mut pending: HashMap<(String, String), Pending> = HashMap::new();
+    let mut queue: BinaryHeap<(i64, String, String)> = BinaryHeap::new();
+    let mut result: BTreeMap<usize, BlameLine> = BTreeMap::new();
+
+    let key = (start.get_hash().to_string(), path.to_string());
+    pending.insert(key.clone(), (from..to).map(|i| (i, i)).collect());
+    queue.push((commit_time(start), key.0, key.1));
+    commits.insert(start.get_hash().to_string(), start.clone());
+
+    // Newest commits first so every version is handled once after all
+    // of its descendants passed their lines on
+    while let Some((_, hash, path)) = queue.pop() {
+        let mut lines = match pending.remove(&(hash.clone(), path.clone())) {
+            Some(lines) => lines,
+            None => continue,
+        };
+        let commit = commits[&hash].clone();
+        let blob = blob_at(&commit, &path).unwrap_or_default();
+        let content = blob_lines(&blob);
+
+        for parent_hash in commit.parent_hashes() {
+            if lines.is_empty() {
+                break;
+            }
+            let parent = match commits
+                .get(parent_hash)
+                .cloned()
+                .or_else(|| Commit::from_hash(parent_hash))
+            {
+                Some(parent) => parent,
+                None => continue,
+            };
+            let parent_path = match blob_at(&parent, &path) {
+                Some(_) => path.clone(),
+                None => match find_rename(&commit, &parent, &blob) {
+                    Some(parent_path) => parent_path,
+                    None => continue,
+                },
+            };
+            let parent_blob = blob_at(&parent, &parent_path).unwrap_or_default();
+
+            let mapping: HashMap<usize, usize> = if parent_blob == blob {
+                lines.iter().map(|(_, here)| (*here, *here)).collect()
+            } else {
+                diff(&blob_lines(&parent_blob), &content)
+                    .into_iter()
+                    .filter_map(|op| match op {
+                        DiffOp::Equal(old, new) => Some((new, old)),
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            let (passed, kept): (Pending, Pending) = lines
+                .into_iter()
+                .partition(|(_, here)| mapping.contains_key(here));
+            lines = kept;
+            if passed.is_empty() {
+                continue;
+            }
+
+            let key = (parent_hash.clone(), parent_path);
+            if !pending.contains_key(&key) {
+                queue.push((commit_time(&parent), key.0.clone(), key.1.clone()));
+            }
+            pending.entry(key).or_default().extend(
+                passed
+                    .into_iter()
+                    .map(|(line, here)| (line, mapping[&here])),
+            );
+            commits.insert(parent_hash.clone(), parent);
+        }
+
+        for (line, here) in lines {
+            result.insert(
+                line,
+                BlameLine {
+                    commit: hash.clone(),
+                    path: path.clone(),
+                    original_line: here + 1,
+                    final_line: line + 1,
+                    content: final_lines[line].clone(),
+                    boundary: commit.parent_hashes().is_empty(),
+                },
+            );
+        }
+    }
+
+    Ok(result.into_values().collect())
+}
@@ -10,34 +10,356 @@ pub struct NyxCli {
 #[derive(Subcommand)]
 pub enum NyxCommand {
     /// Creates an empty nyx repository
-    Init,
+    Init {
+        /// Hash function naming the objects, `sha1` (default) or `sha256`
+        #[clap(long, value_parser)]
+        object_format: Option<String>,
+    },
+    /// Clone a repository into a new directory
+    Clone {
+        /// Path or `http://` URL of the repository to clone
+        #[clap(value_parser)]
+        source: String,
+        #[clap(value_parser)]
+        directory: Option<String>,
+    },
     /// Adds one or many files to staging area
     Add {
         #[clap(value_parser)]
         paths: Vec<String>,
+        /// Stage all changes, including new and deleted files
+        #[clap(short = 'A', long, conflicts_with = "update")]
+        all: bool,
+        /// Only stage changes of tracked files
+        #[clap(short, long)]
+        update: bool,
+        /// Only show what would be added or removed
+        #[clap(short = 'n', long)]
+        dry_run: bool,
+        /// Also add ignored files
+        #[clap(short, long)]
+        force: bool,
+        /// Interactively pick the hunks to stage
+        #[clap(short, long)]
+        patch: bool,
     },
     /// Record changes to the repository
     Commit {
         #[clap(short, value_parser)]
         message: String,
+        /// Bypass the pre-commit and commit-msg hooks
+        #[clap(short, long)]
+        no_verify: bool,
+        /// Sign the commit with the key of `user.signingKey`
+        #[clap(short = 'S', long = "gpg-sign")]
+        sign: bool,
     },
     /// Display untracked/modified files
-    Status,
+    Status {
+        /// Only show the status of these paths
+        #[clap(value_parser)]
+        paths: Vec<String>,
+        /// Show one `XY path` line per changed path
+        #[clap(short, long)]
+        short: bool,
+        /// Show the branch and its upstream in the short and porcelain formats
+        #[clap(short, long)]
+        branch: bool,
+        /// Stable machine readable output, `v1` (the default) or `v2`
+        #[clap(
+            long,
+            value_name = "VERSION",
+            min_values = 0,
+            require_equals = true,
+            default_missing_value = "v1"
+        )]
+        porcelain: Option<String>,
+        /// Terminate entries with NUL instead of newlines
+        #[clap(short = 'z')]
+        null: bool,
+    },
     /// Log commit history
-    Log,
+    Log {
+        /// Commits to start from, `A..B` or `^A` exclude the history of `A`
+        #[clap(value_parser)]
+        revisions: Vec<String>,
+        /// Only show commits touching these paths
+        #[clap(last = true, value_parser)]
+        paths: Vec<String>,
+        /// Limit the number of commits to output
+        #[clap(short = 'n', long, value_parser)]
+        max_count: Option<usize>,
+        /// Show commits more recent than a date
+        #[clap(long, alias = "after", value_parser)]
+        since: Option<String>,
+        /// Show commits older than a date
+        #[clap(long, alias = "before", value_parser)]
+        until: Option<String>,
+        /// Only show commits whose author matches
+        #[clap(long, value_parser)]
+        author: Option<String>,
+        /// Only show commits whose message matches
+        #[clap(long, value_parser)]
+        grep: Option<String>,
+        /// Format like oneline, short, medium, full or a template such as "%h %s"
+        #[clap(long, alias = "pretty", value_parser)]
+        format: Option<String>,
+        /// Show each commit on a single line
+        #[clap(long)]
+        oneline: bool,
+        /// Draw the history graph next to the commits
+        #[clap(long)]
+        graph: bool,
+        /// Never show a parent before all of its children
+        #[clap(long)]
+        topo_order: bool,
+        /// Like --topo-order, but ordered by commit date otherwise
+        #[clap(long)]
+        date_order: bool,
+        /// Check the signatures of signed commits and show the result
+        #[clap(long)]
+        show_signature: bool,
+    },
+    /// Show what revision and author last modified each line of a file
+    Blame {
+        #[clap(value_parser)]
+        path: String,
+        /// Revision to start from, defaults to HEAD
+        #[clap(value_parser)]
+        rev: Option<String>,
+        /// Only blame the lines in the range `start,end` or `start,+count`
+        #[clap(short = 'L', value_parser)]
+        range: Option<String>,
+    },
+    /// Show commits, trees, blobs and tags
+    Show {
+        /// Objects to show, e.g. `HEAD~1`, `v1.0` or `HEAD:src/main.rs`
+        #[clap(value_parser)]
+        revs: Vec<String>,
+    },
+    /// Create, list or delete tags
+    Tag {
+        #[clap(value_parser)]
+        name: Option<String>,
+        /// Object the new tag points to, defaults to HEAD
+        #[clap(value_parser)]
+        rev: Option<String>,
+        /// Create an annotated tag object
+        #[clap(short, long)]
+        annotate: bool,
+        /// Message of an annotated tag
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+        /// Delete the tag
+        #[clap(short, long)]
+        delete: bool,
+        /// Replace an existing tag
+        #[clap(short, long)]
+        force: bool,
+        /// Create an annotated tag signed with the key of `user.signingKey`
+        #[clap(short, long)]
+        sign: bool,
+    },
     /// Switch between commits
     Checkout {
         #[clap(value_parser)]
         hash: String,
     },
+    /// Reset index entries to HEAD, keeping the working tree as it is
+    Reset {
+        #[clap(value_parser)]
+        paths: Vec<String>,
+        /// Interactively pick the hunks to unstage
+        #[clap(short, long)]
+        patch: bool,
+    },
+    /// Restore working tree files from the index
+    Restore {
+        #[clap(value_parser)]
+        paths: Vec<String>,
+        /// Interactively pick the hunks to discard
+        #[clap(short, long)]
+        patch: bool,
+    },
+    /// Apply the changes introduced by existing commits
+    CherryPick {
+        #[clap(value_parser)]
+        revs: Vec<String>,
+        /// Continue after resolving a conflict
+        #[clap(long = "continue")]
+        proceed: bool,
+        /// Cancel and return to the pre-sequence state
+        #[clap(long)]
+        abort: bool,
+        /// Skip the current commit and continue with the rest
+        #[clap(long)]
+        skip: bool,
+        /// Append a line that says which commit was picked
+        #[clap(short = 'x')]
+        record_origin: bool,
+        /// Parent number of a merge commit to diff against
+        #[clap(short, long, value_parser)]
+        mainline: Option<usize>,
+        /// Apply the changes without committing them
+        #[clap(short, long)]
+        no_commit: bool,
+    },
+    /// Revert the changes introduced by existing commits
+    Revert {
+        #[clap(value_parser)]
+        revs: Vec<String>,
+        /// Continue after resolving a conflict
+        #[clap(long = "continue")]
+        proceed: bool,
+        /// Cancel and return to the pre-sequence state
+        #[clap(long)]
+        abort: bool,
+        /// Skip the current commit and continue with the rest
+        #[clap(long)]
+        skip: bool,
+        /// Parent number of a merge commit to diff against
+        #[clap(short, long, value_parser)]
+        mainline: Option<usize>,
+        /// Apply the changes without committing them
+        #[clap(short, long)]
+        no_commit: bool,
+    },
+    /// Reapply commits on top of another base commit
+    Rebase {
+        #[clap(value_parser)]
+        upstream: Option<String>,
+        /// Starting point at which to create the new commits
+        #[clap(long, value_parser)]
+        onto: Option<String>,
+        /// Edit the list of commits to rebase before starting
+        #[clap(short, long)]
+        interactive: bool,
+        /// Continue after resolving a conflict or editing a commit
+        #[clap(long = "continue")]
+        proceed: bool,
+        /// Cancel and return to the original branch
+        #[clap(long)]
+        abort: bool,
+        /// Skip the current commit and continue with the rest
+        #[clap(long)]
+        skip: bool,
+    },
+    /// Stash the changes in a dirty working directory away
+    Stash {
+        #[clap(subcommand)]
+        command: Option<StashCommand>,
+    },
+    /// Manage the set of tracked repositories
+    Remote {
+        /// Show the URLs of the remotes
+        #[clap(short, long)]
+        verbose: bool,
+        #[clap(subcommand)]
+        command: Option<RemoteCommand>,
+    },
+    /// Download objects and refs from another repository
+    Fetch {
+        #[clap(value_parser)]
+        remote: Option<String>,
+    },
+    /// Update a remote branch along with the objects it needs
+    Push {
+        #[clap(value_parser)]
+        remote: Option<String>,
+        /// Branch to push or `<source>:<destination>`
+        #[clap(value_parser)]
+        refspec: Option<String>,
+        /// Update the remote branch even if it is not an ancestor
+        #[clap(short, long)]
+        force: bool,
+        /// Let the branch track the pushed remote branch
+        #[clap(short = 'u', long)]
+        set_upstream: bool,
+    },
+    /// Serve the repository over HTTP for clone, fetch and push
+    Serve {
+        /// Address to listen on
+        #[clap(long, default_value = "127.0.0.1")]
+        bind: String,
+        #[clap(short, long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Cleanup unnecessary files and optimize the local repository
+    Gc {
+        /// Prune unreachable objects older than this date, `now` or `never`
+        #[clap(long, value_parser)]
+        prune: Option<String>,
+        /// Only report what would be removed
+        #[clap(short = 'n', long)]
+        dry_run: bool,
+    },
+    /// Convert the history, branches and tags of a git repository
+    ImportGit {
+        /// Path of the `.git` directory or its working tree
+        #[clap(value_parser)]
+        path: String,
+        /// Where to write the mapping from git to nyx hashes, defaults
+        /// to `.nyx/git-map`
+        #[clap(long, value_parser)]
+        map_file: Option<String>,
+        /// Move branches and tags that are not fast-forwarded
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Rewrite all objects for another object format, e.g. `sha256`
+    ConvertObjects {
+        /// The object format to convert to
+        #[clap(long, value_parser)]
+        object_format: String,
+        /// Where to write the mapping from old to new hashes, defaults
+        /// to `.nyx/object-map`
+        #[clap(long, value_parser)]
+        map_file: Option<String>,
+    },
+    /// Write branches and tags as a git fast-import stream to stdout
+    FastExport {
+        /// Branches and tags to export, `A..B` and `^A` leave out history,
+        /// defaults to all branches and tags
+        #[clap(value_parser)]
+        revisions: Vec<String>,
+    },
+    /// Create commits, tags and refs from a git fast-import stream on stdin
+    FastImport,
+    /// Move refs and objects through a single file
+    Bundle {
+        #[clap(subcommand)]
+        command: BundleCommand,
+    },
+    /// Store large files outside of the object database
+    Lfs {
+        #[clap(subcommand)]
+        command: LfsCommand,
+    },
+    /// Use binary search to find the commit that introduced a bug
+    Bisect {
+        #[clap(subcommand)]
+        command: BisectCommand,
+    },
 
     // ##################################
     // ####### LOW-LEVEL COMMANDS #######
     // ##################################
-    /// Compute object ID and creates a blob object from a file
+    /// Compute object ID and optionally create an object from a file
     HashObject {
         #[clap(value_parser)]
-        path: String,
+        paths: Vec<String>,
+        /// Type of the object
+        #[clap(short = 't', value_parser, default_value = "blob")]
+        object_type: String,
+        /// Write the object into the object database
+        #[clap(short = 'w')]
+        write: bool,
+        /// Read the content from stdin
+        #[clap(long, value_parser)]
+        stdin: bool,
+        /// Read file names from stdin, one per line
+        #[clap(long, value_parser)]
+        stdin_paths: bool,
     },
     /// Provide content for repository object
     CatFile {
@@ -46,4 +368,190 @@ pub enum NyxCommand {
     },
     /// Provide content of index
     LsFile,
+    /// Prune all unreachable objects from the object database
+    Prune {
+        /// Only prune objects older than this date
+        #[clap(long, value_parser)]
+        expire: Option<String>,
+        /// Only report what would be removed
+        #[clap(short = 'n', long)]
+        dry_run: bool,
+        /// Report all removed objects
+        #[clap(short, long)]
+        verbose: bool,
+    },
+    /// Verify the connectivity and validity of the objects in the database
+    Fsck {
+        /// Print all objects that are not reachable, not only dangling ones
+        #[clap(long)]
+        unreachable: bool,
+        /// Do not print dangling objects
+        #[clap(long)]
+        no_dangling: bool,
+        /// Do not consider objects only referenced by reflogs reachable
+        #[clap(long)]
+        no_reflogs: bool,
+    },
+    /// Check the signatures of commits against the allowed signers
+    VerifyCommit {
+        #[clap(value_parser, required = true)]
+        revs: Vec<String>,
+    },
+    /// Check the signatures of tags against the allowed signers
+    VerifyTag {
+        #[clap(value_parser, required = true)]
+        names: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StashCommand {
+    /// Save local modifications to a new stash entry and revert to HEAD
+    Push {
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+        /// Leave the staged changes in the working tree and index
+        #[clap(short, long)]
+        keep_index: bool,
+    },
+    /// List the stash entries
+    List,
+    /// Show the changes recorded in a stash entry
+    Show {
+        #[clap(value_parser)]
+        stash: Option<String>,
+        /// Show the changes as patch
+        #[clap(short, long)]
+        patch: bool,
+    },
+    /// Apply a stash entry on top of the current working tree
+    Apply {
+        #[clap(value_parser)]
+        stash: Option<String>,
+        /// Also restore the staged changes
+        #[clap(long)]
+        index: bool,
+    },
+    /// Apply a stash entry and remove it from the stash list
+    Pop {
+        #[clap(value_parser)]
+        stash: Option<String>,
+        /// Also restore the staged changes
+        #[clap(long)]
+        index: bool,
+    },
+    /// Remove a stash entry from the stash list
+    Drop {
+        #[clap(value_parser)]
+        stash: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RemoteCommand {
+    /// Add a remote named <name> for the repository at <url>
+    Add {
+        #[clap(value_parser)]
+        name: String,
+        #[clap(value_parser)]
+        url: String,
+    },
+    /// Remove a remote and its remote-tracking branches
+    Remove {
+        #[clap(value_parser)]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Write the refs and the objects they need into a file, `A..B`
+    /// and `^A` leave out what the receiver already has
+    Create {
+        #[clap(value_parser)]
+        file: String,
+        #[clap(value_parser)]
+        revisions: Vec<String>,
+        /// Bundle all refs
+        #[clap(long)]
+        all: bool,
+    },
+    /// Check that a bundle is valid and applies to this repository
+    Verify {
+        #[clap(value_parser)]
+        file: String,
+    },
+    /// List the refs of a bundle
+    ListHeads {
+        #[clap(value_parser)]
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LfsCommand {
+    /// Store paths matching the patterns as large files, lists the
+    /// tracked patterns without patterns
+    Track {
+        #[clap(value_parser)]
+        patterns: Vec<String>,
+    },
+    /// List the large files in the index
+    LsFiles,
+    /// Download the large files of the revisions from a remote
+    Fetch {
+        /// Remote to download from, defaults to the upstream remote
+        #[clap(value_parser)]
+        remote: Option<String>,
+        /// Revisions whose large files are needed, defaults to HEAD
+        #[clap(value_parser)]
+        revisions: Vec<String>,
+        /// Download the large files of all refs and their history
+        #[clap(long)]
+        all: bool,
+    },
+    /// Delete large files no longer referenced from the history
+    Prune {
+        /// Only report what would be removed
+        #[clap(short = 'n', long)]
+        dry_run: bool,
+        /// Report all removed files
+        #[clap(short, long)]
+        verbose: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BisectCommand {
+    /// Start a bisect session, optionally marking a bad and good commits
+    Start {
+        #[clap(value_parser)]
+        bad: Option<String>,
+        #[clap(value_parser)]
+        good: Vec<String>,
+    },
+    /// Mark a commit as bad, defaults to HEAD
+    Bad {
+        #[clap(value_parser)]
+        rev: Option<String>,
+    },
+    /// Mark commits as good, defaults to HEAD
+    Good {
+        #[clap(value_parser)]
+        revs: Vec<String>,
+    },
+    /// Mark commits as untestable, defaults to HEAD
+    Skip {
+        #[clap(value_parser)]
+        revs: Vec<String>,
+    },
+    /// Finish the session and return to the original HEAD
+    Reset,
+    /// Show what has been marked so far
+    Log,
+    /// Mark commits automatically by the exit code of a command
+    Run {
+        #[clap(value_parser, required = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
 }
@@ -1,32 +1,223 @@
-use std::{fs, path::PathBuf};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
 
 use crate::{
-    core::{errors::NyxError, index::index::Index, shared::get_object_hash},
+    core::{
+        commit::Commit,
+        diff::select::{HunkSelector, PatchMode, Selection},
+        errors::NyxError,
+        index::{entry::IndexEntry, file_state::NyxFileState, index::Index},
+        lfs::track::is_tracked,
+        object_type::NyxObjectType,
+        pathspec::Pathspec,
+        shared::{generate_object, read_object_data},
+        tree::tree::Tree,
+        worktree::{hash_working_file, working_path, write_working_file_object},
+    },
     FILE_SYSTEM,
 };
 
-pub fn add(paths: Vec<String>) -> Result<(), NyxError> {
+#[derive(Debug, Default)]
+pub struct AddOptions {
+    pub all: bool,
+    pub update: bool,
+    pub dry_run: bool,
+    pub force: bool,
+    pub patch: bool,
+}
+
+/// Collects the working tree files the pathspec selects, relative to
+/// the root. Untracked ignored files are left out, those the pathspec
+/// names explicitly are kept in `ignored` to complain about them.
+struct FileCollector<'a> {
+    pathspec: &'a Pathspec,
+    indexed: &'a BTreeMap<String, IndexEntry>,
+    force: bool,
+    files: Vec<String>,
+    ignored: Vec<String>,
+}
+
+impl FileCollector<'_> {
+    fn collect(&mut self, dir: &Path, prefix: &str, in_ignored: bool) -> Result<(), NyxError> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        for path in paths {
+            if path == FILE_SYSTEM.get_repo_dir() {
+                continue;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let relative = format!("{}{}", prefix, name);
+            let is_ignored = !self.force && (in_ignored || FILE_SYSTEM.is_ignored(&path));
+
+            if path.is_dir() {
+                // Tracked files stay tracked inside ignored directories
+                let dir_prefix = format!("{}/", relative);
+                let has_tracked = self
+                    .indexed
+                    .keys()
+                    .any(|path| path.starts_with(&dir_prefix));
+                if is_ignored && !has_tracked {
+                    if self.pathspec.names(&relative) {
+                        self.ignored.push(relative);
+                    }
+                    continue;
+                }
+                self.collect(&path, &dir_prefix, is_ignored)?;
+            } else if self.pathspec.matches(&relative) {
+                if is_ignored && !self.indexed.contains_key(&relative) {
+                    if self.pathspec.names(&relative) {
+                        self.ignored.push(relative);
+                    }
+                    continue;
+                }
+                self.files.push(relative);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stages the working tree state of the paths the pathspec selects:
+/// new and changed files as well as deleted ones. With `update` only
+/// tracked files are staged, `all` stages everything without a pathspec.
+pub fn add(paths: &[String], options: AddOptions) -> Result<(), NyxError> {
+    let pathspec = Pathspec::parse(paths)?;
+    if options.patch {
+        return add_patch(&pathspec);
+    }
+    if pathspec.is_empty() && !options.all && !options.update {
+        println!("Nothing specified, nothing added.");
+        return Ok(());
+    }
+
     let mut index = Index::new();
+    // Entries keep the form of their path, with or without `./`
+    let indexed: BTreeMap<String, IndexEntry> = index
+        .entries()
+        .iter()
+        .map(|entry| {
+            let path = entry.path.strip_prefix("./").unwrap_or(&entry.path);
+            (path.to_string(), entry.clone())
+        })
+        .collect();
+    let mut collector = FileCollector {
+        pathspec: &pathspec,
+        indexed: &indexed,
+        force: options.force,
+        files: Vec::new(),
+        ignored: Vec::new(),
+    };
+    collector.collect(FILE_SYSTEM.get_root_dir(), "", false)?;
+    let FileCollector { files, ignored, .. } = collector;
 
-    for path in paths {
-        add_recursive(&path, &mut index);
+    let known = files.iter().chain(indexed.keys()).chain(ignored.iter());
+    if let Some(spec) = pathspec.unmatched(known.map(String::as_str)).first() {
+        return Err(NyxError::Usage(format!(
+            "pathspec '{}' did not match any files",
+            spec
+        )));
+    }
+
+    for path in &files {
+        let entry = indexed.get(path);
+        if options.update && entry.is_none() {
+            continue;
+        }
+        let hash = hash_working_file(path);
+        let unchanged = entry.is_some_and(|entry| {
+            entry.state != NyxFileState::Unmerged && hash.as_ref() == Some(&entry.hash)
+        });
+        if unchanged {
+            continue;
+        }
+        if options.dry_run {
+            println!("add '{}'", path);
+            continue;
+        }
+        let hash = write_working_file_object(path)
+            .ok_or_else(|| NyxError::Usage(format!("unable to add '{}'", path)))?;
+        let index_path = match entry {
+            Some(entry) => entry.path.clone(),
+            None => format!("./{}", path),
+        };
+        index.set_entry(&index_path, &hash, NyxFileState::Staged);
+    }
+    for (path, entry) in &indexed {
+        if !pathspec.matches(path) || working_path(path).exists() {
+            continue;
+        }
+        match options.dry_run {
+            true => println!("remove '{}'", path),
+            false => index.remove_entry(&entry.path),
+        }
+    }
+    if !options.dry_run {
+        index.write();
+    }
+
+    if !ignored.is_empty() {
+        return Err(NyxError::Usage(format!(
+            "The following paths are ignored by one of your .nyxignore files:\n{}\nUse -f if you really want to add them.",
+            ignored.join("\n")
+        )));
     }
     Ok(())
 }
 
-fn add_recursive(path: &str, index: &mut Index) {
-    let path = PathBuf::from(path);
-    if path.is_dir() {
-        if FILE_SYSTEM.is_ignored(&path) {
-            return;
+/// Stages the hunks of the unstaged changes the user picks. The working
+/// tree is left as it is.
+fn add_patch(pathspec: &Pathspec) -> Result<(), NyxError> {
+    let head_paths = Tree::read_paths(Commit::from_head().as_ref().map(|c| c.tree_hash()));
+    let mut index = Index::new();
+    let mut selector = HunkSelector::new(PatchMode::Stage);
+    let mut changed = false;
+
+    for entry in index.entries().clone() {
+        // Large files and conflicts can only be staged as a whole
+        if !pathspec.matches(&entry.path)
+            || entry.state == NyxFileState::Unmerged
+            || is_tracked(&entry.path)
+        {
+            continue;
+        }
+        let working = hash_working_file(&entry.path);
+        if working.as_ref() == Some(&entry.hash) {
+            continue;
+        }
+        changed = true;
+        let old = read_object_data(&entry.hash);
+        let new = working
+            .map(|_| fs::read_to_string(working_path(&entry.path)))
+            .transpose();
+        let (Ok(old), Ok(new)) = (old, new) else {
+            println!("Skipping binary file {}", entry.path);
+            continue;
+        };
+
+        match selector.select(&entry.path, Some(&old), new.as_deref())? {
+            Selection::Content(content) => {
+                let hash = generate_object(content.as_bytes(), NyxObjectType::Blob);
+                let state = match head_paths.get(&entry.path) == Some(&hash) {
+                    true => NyxFileState::Committed,
+                    false => NyxFileState::Staged,
+                };
+                index.set_entry(&entry.path, &hash, state);
+            }
+            Selection::Removed => index.remove_entry(&entry.path),
+            Selection::Unchanged => (),
         }
-        for p in fs::read_dir(&path).unwrap() {
-            let relative_path = path.join(p.unwrap().file_name());
-            add_recursive(relative_path.to_str().unwrap(), index);
+        if selector.has_quit() {
+            break;
         }
-    } else {
-        let path = path.to_str().unwrap();
-        let sha1 = get_object_hash(path);
-        index.add(&sha1, path).unwrap();
     }
+
+    if !changed {
+        println!("No changes.");
+        return Ok(());
+    }
+    index.write();
+    Ok(())
 }
@@ -0,0 +1,122 @@
+use std::process::Command;
+
+use crate::core::{
+    bisect::{Bisect, BisectStep, Mark},
+    cl_args::BisectCommand,
+    errors::NyxError,
+};
+use crate::FILE_SYSTEM;
+
+/// Exit code of `bisect run` scripts for commits that cannot be tested.
+const SKIP_EXIT_CODE: i32 = 125;
+
+pub fn bisect(command: &BisectCommand) -> Result<(), NyxError> {
+    match command {
+        BisectCommand::Start { bad, good } => {
+            Bisect::start()?;
+            let mut bisect = Bisect::load()?;
+            if let Some(bad) = bad {
+                bisect.mark(Mark::Bad, std::slice::from_ref(bad))?;
+            }
+            if !good.is_empty() {
+                bisect.mark(Mark::Good, good)?;
+            }
+            report(&bisect, bisect.next()?)?;
+        }
+        BisectCommand::Bad { rev } => _ = mark(Mark::Bad, rev.as_slice())?,
+        BisectCommand::Good { revs } => _ = mark(Mark::Good, revs)?,
+        BisectCommand::Skip { revs } => _ = mark(Mark::Skip, revs)?,
+        BisectCommand::Reset => Bisect::reset()?,
+        BisectCommand::Log => print!("{}", Bisect::log()?),
+        BisectCommand::Run { command } => run(command)?,
+    }
+    Ok(())
+}
+
+fn mark(mark: Mark, revs: &[String]) -> Result<BisectStep, NyxError> {
+    let mut bisect = Bisect::load()?;
+    bisect.mark(mark, revs)?;
+    let step = bisect.next()?;
+    report(&bisect, step)
+}
+
+/// Prints the outcome of a step and passes it on.
+fn report(bisect: &Bisect, step: BisectStep) -> Result<BisectStep, NyxError> {
+    match &step {
+        BisectStep::Waiting => println!("status: waiting for both good and bad commits"),
+        BisectStep::Testing(_) => (),
+        BisectStep::Found(commit) => {
+            println!("{} is the first bad commit", commit.get_hash());
+            println!("{}", commit);
+            bisect.log_result(&format!(
+                "first bad commit: [{}] {}",
+                commit.get_hash(),
+                commit.summary()
+            ))?;
+        }
+        BisectStep::OnlySkipped(hashes) => {
+            println!("There are only 'skip'ped commits left to test.");
+            println!("The first bad commit could be any of:");
+            for hash in hashes {
+                println!("{}", hash);
+            }
+            bisect.log_result("only skipped commits left to test")?;
+        }
+    }
+    Ok(step)
+}
+
+/// Runs the command from the root of the repository on every checked
+/// out commit: exit code 0 marks it good, 125 skips it and other codes
+/// below 128 mark it bad.
+fn run(command: &[String]) -> Result<(), NyxError> {
+    if command.is_empty() {
+        return Err(NyxError::Usage(String::from(
+            "bisect run requires a command",
+        )));
+    }
+    let display = command.join(" ");
+    loop {
+        println!("running '{}'", display);
+        // A single argument may be a shell snippet, otherwise run the program directly
+        let (program, args) = match command {
+            [script] => ("sh", vec!["-c", script.as_str()]),
+            [program, args @ ..] => (program.as_str(), args.iter().map(String::as_str).collect()),
+            [] => unreachable!(),
+        };
+        let status = Command::new(program)
+            .args(args)
+            .current_dir(FILE_SYSTEM.get_root_dir())
+            .status()?;
+        let code = status.code().unwrap_or(-1);
+
+        let mark = match code {
+            0 => Mark::Good,
+            SKIP_EXIT_CODE => Mark::Skip,
+            1..=127 => Mark::Bad,
+            _ => {
+                return Err(NyxError::Usage(format!(
+                    "bisect run failed: exit code {} from '{}' is < 0 or >= 128",
+                    code, display
+                )))
+            }
+        };
+        match self::mark(mark, &[])? {
+            BisectStep::Testing(_) => continue,
+            BisectStep::Waiting => {
+                return Err(NyxError::Usage(String::from(
+                    "bisect run failed: a good and a bad commit are needed",
+                )))
+            }
+            BisectStep::Found(_) => {
+                println!("bisect found first bad commit");
+                return Ok(());
+            }
+            BisectStep::OnlySkipped(_) => {
+                return Err(NyxError::Usage(String::from(
+                    "bisect run cannot continue any more",
+                )))
+            }
+        }
+    }
+}
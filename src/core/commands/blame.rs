@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::core::{
+    blame::blame as blame_lines, commit::Commit, date::format_iso_date, errors::NyxError,
+    refs::refs, revision::resolve_commit,
+};
+
+/// Parses `-L` ranges like `10,20`, `10,+5`, `10,` or `,20` into
+/// 0 based line indices with an exclusive end.
+fn parse_range(range: &str) -> Result<(usize, usize), NyxError> {
+    let invalid = || NyxError::Usage(format!("invalid line range '{}'", range));
+    let (start, end) = range.split_once(',').unwrap_or((range, ""));
+    let start: usize = match start {
+        "" => 1,
+        start => start.parse().map_err(|_| invalid())?,
+    };
+    if start == 0 {
+        return Err(invalid());
+    }
+    let end: usize = match end {
+        "" => usize::MAX,
+        end => match end.strip_prefix('+') {
+            Some(count) => match count.parse::<usize>().map_err(|_| invalid())? {
+                0 => return Err(invalid()),
+                count => start.saturating_add(count - 1),
+            },
+            None => end.parse().map_err(|_| invalid())?,
+        },
+    };
+    if end < start {
+        return Err(invalid());
+    }
+    Ok((start - 1, end))
+}
+
+pub fn blame(path: &str, rev: &Option<String>, range: &Option<String>) -> Result<(), NyxError> {
+    let commit = resolve_commit(rev.as_deref().unwrap_or(refs::HEAD))?;
+    let range = range.as_deref().map(parse_range).transpose()?;
+    let lines = blame_lines(&commit, path, range)?;
+
+    let mut commits: HashMap<&str, Commit> = HashMap::new();
+    for line in &lines {
+        if !commits.contains_key(line.commit.as_str()) {
+            commits.insert(&line.commit, Commit::read(&line.commit)?);
+        }
+    }
+    let author = |hash: &str| {
+        commits[hash]
+            .author()
+            .map(|a| (a.name.clone(), format_iso_date(a.time, &a.offset)))
+            .unwrap_or_default()
+    };
+
+    let show_path = lines.iter().any(|line| line.path != path);
+    let path_width = lines.iter().map(|l| l.path.len()).max().unwrap_or(0);
+    let name_width = lines
+        .iter()
+        .map(|l| author(&l.commit).0.len())
+        .max()
+        .unwrap_or(0);
+    let number_width = lines
+        .last()
+        .map(|l| l.final_line.to_string().len())
+        .unwrap_or(1);
+
+    for line in &lines {
+        let (name, date) = author(&line.commit);
+        let hash = match line.boundary {
+            true => format!("^{}", &line.commit[..7]),
+            false => line.commit[..8].to_string(),
+        };
+        let path = match show_path {
+            true => format!(" {:width$}", line.path, width = path_width),
+            false => String::new(),
+        };
+        println!(
+            "{}{} ({:name_width$} {} {:>number_width$}) {}",
+            hash,
+            path,
+            name,
+            date,
+            line.final_line,
+            line.content,
+            name_width = name_width,
+            number_width = number_width
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use crate::core::{
+    cl_args::BundleCommand,
+    errors::NyxError,
+    refs::refs,
+    remote::{bundle::Bundle, pack},
+    revision::resolve_commit,
+    shared::object_exists,
+};
+
+const TAGS_PREFIX: &str = "tags/";
+
+fn ref_count(n: usize) -> String {
+    match n {
+        1 => String::from("this ref"),
+        _ => format!("these {} refs", n),
+    }
+}
+
+/// The branch or tag a positive revision names with its hash.
+fn bundle_ref(name: &str) -> Result<(String, String), NyxError> {
+    refs::expand_ref_name(name)
+        .and_then(|name| {
+            let hash = refs::read_ref(&name)?;
+            Some((name, hash))
+        })
+        .ok_or_else(|| {
+            NyxError::Usage(format!(
+                "'{}' is neither a branch nor a tag, refusing to bundle it",
+                name
+            ))
+        })
+}
+
+fn create(file: &str, revisions: &[String], all: bool) -> Result<(), NyxError> {
+    let mut bundled: Vec<(String, String)> = Vec::new();
+    let mut exclude: Vec<String> = Vec::new();
+    if all {
+        bundled.extend(
+            refs::list_refs("")
+                .into_iter()
+                .filter(|(name, _)| refs::is_branch_name(name) || name.starts_with(TAGS_PREFIX)),
+        );
+    }
+    for revision in revisions {
+        let or_head = |rev: &str| match rev.is_empty() {
+            true => refs::HEAD.to_string(),
+            false => rev.to_string(),
+        };
+        if let Some((from, to)) = revision.split_once("..") {
+            exclude.push(resolve_commit(&or_head(from))?.get_hash().to_string());
+            bundled.push(bundle_ref(&or_head(to))?);
+        } else if let Some(rev) = revision.strip_prefix('^') {
+            exclude.push(resolve_commit(rev)?.get_hash().to_string());
+        } else {
+            bundled.push(bundle_ref(revision)?);
+        }
+    }
+    bundled.sort();
+    bundled.dedup();
+    if bundled.is_empty() {
+        return Err(NyxError::Usage(String::from(
+            "Refusing to create empty bundle.",
+        )));
+    }
+
+    // Clones of the bundle check out the current branch if it is included
+    let is_bundled = |branch: &String| bundled.iter().any(|(name, _)| name == branch);
+    let head = refs::current_branch().filter(is_bundled).or_else(|| {
+        bundled
+            .iter()
+            .map(|(name, _)| name.clone())
+            .find(|name| refs::is_branch_name(name))
+    });
+
+    Bundle::create(bundled, head, &exclude)?.write(Path::new(file))
+}
+
+fn verify(file: &str) -> Result<(), NyxError> {
+    let bundle = Bundle::read(Path::new(file))?;
+    let objects = pack::decode(&bundle.pack)?;
+    for (_, hash) in &bundle.refs {
+        if !objects.iter().any(|(object, _)| object == hash) && !object_exists(hash) {
+            return Err(NyxError::InvalidObject(format!(
+                "{} is missing in the bundle",
+                hash
+            )));
+        }
+    }
+
+    println!("The bundle contains {}:", ref_count(bundle.refs.len()));
+    for (name, hash) in &bundle.refs {
+        println!("{} {}", hash, name);
+    }
+    match bundle.prerequisites.is_empty() {
+        true => println!("The bundle records a complete history."),
+        false => {
+            println!(
+                "The bundle requires {}:",
+                ref_count(bundle.prerequisites.len())
+            );
+            for hash in &bundle.prerequisites {
+                println!("{}", hash);
+            }
+        }
+    }
+    bundle.check_prerequisites()?;
+    println!("{} is okay", file);
+    Ok(())
+}
+
+fn list_heads(file: &str) -> Result<(), NyxError> {
+    let bundle = Bundle::read(Path::new(file))?;
+    for (name, hash) in &bundle.refs {
+        println!("{} {}", hash, name);
+    }
+    let head = bundle
+        .refs
+        .iter()
+        .find(|(name, _)| Some(name) == bundle.head.as_ref());
+    if let Some((_, hash)) = head {
+        println!("{} {}", hash, refs::HEAD);
+    }
+    Ok(())
+}
+
+pub fn bundle(command: &BundleCommand) -> Result<(), NyxError> {
+    match command {
+        BundleCommand::Create {
+            file,
+            revisions,
+            all,
+        } => create(file, revisions, *all),
+        BundleCommand::Verify { file } => verify(file),
+        BundleCommand::ListHeads { file } => list_heads(file),
+    }
+}
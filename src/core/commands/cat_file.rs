@@ -1,7 +1,13 @@
-use crate::core::{errors::NyxError, shared::read_object_data};
+use std::io::{self, Write};
 
+use crate::core::{errors::NyxError, odb::stream, shared::open_object};
+
+/// Prints the content of the object, streamed so large blobs do not
+/// have to fit into memory.
 pub fn cat_file(hash: &str) -> Result<(), NyxError> {
-    let content = read_object_data(hash)?;
-    println!("{}", content);
+    let mut object = open_object(hash)?;
+    let mut stdout = io::stdout().lock();
+    stream::copy(&mut object, &mut stdout)?;
+    writeln!(stdout)?;
     Ok(())
 }
@@ -2,18 +2,30 @@ use std::{fs, path::PathBuf, process};
 
 use crate::{
     core::{
-        commit::Commit, object_type::NyxObjectType, shared::read_object_data, tree::tree::Tree,
+        commit::Commit,
+        hooks::{self, POST_CHECKOUT},
+        index::{file_state::NyxFileState, index::Index},
+        lfs::filter,
+        object_type::NyxObjectType,
+        refs::refs,
+        revision,
+        shared::read_object_data,
+        tree::tree::Tree,
     },
     FILE_SYSTEM,
 };
 
 pub fn checkout(hash: &str) {
-    let mut hash = hash.to_string();
-    let mut is_master = false;
-    if hash.eq("master") {
-        hash = fs::read_to_string(FILE_SYSTEM.get_refs_dir_path().join("master")).unwrap();
-        is_master = true;
-    }
+    let branch = hash.to_string();
+    let is_branch = refs::is_branch_name(&branch) && refs::ref_exists(&branch);
+
+    let hash = match revision::resolve(&branch) {
+        Ok(hash) => revision::peel(&hash),
+        Err(err) => {
+            eprint!("{}", err);
+            process::exit(1);
+        }
+    };
 
     // TODO: Move error handling to Commit::from_hash ctor
     if let Err(err) = read_object_data(&hash) {
@@ -21,17 +33,34 @@ pub fn checkout(hash: &str) {
         process::exit(1);
     }
 
+    let previous = refs::head_hash();
     let commit = Commit::from_hash(&hash).unwrap();
     let tree = Tree::from_hash(commit.tree_hash());
 
-    // Write hash in HEAD
-    fs::write(FILE_SYSTEM.get_head_path(), hash).unwrap();
+    // Let HEAD point to the branch or write the hash directly
+    let reflog_message = format!("checkout: moving from {} to {}", previous, branch);
+    if is_branch {
+        refs::set_head_to_branch(&branch).unwrap();
+        refs::append_head_log(&previous, &hash, &reflog_message).unwrap();
+    } else {
+        refs::detach_head(&hash, &reflog_message).unwrap();
+    }
 
     remove_not_ignored_files();
 
     restore_working_tree_recursively(&tree, FILE_SYSTEM.get_root_dir().to_str().unwrap());
 
-    print_info_text(is_master, &commit);
+    Index::new().reset_to(&tree.flatten(), NyxFileState::Committed);
+
+    print_info_text(is_branch, &branch, &commit);
+
+    let previous = match previous.is_empty() {
+        true => refs::zero_hash(),
+        false => previous,
+    };
+    if let Err(err) = hooks::run_hook(POST_CHECKOUT, &[&previous, &hash, "1"]) {
+        eprintln!("{}", err);
+    }
 }
 
 fn restore_working_tree_recursively(tree: &Tree, path: &str) {
@@ -42,8 +71,7 @@ fn restore_working_tree_recursively(tree: &Tree, path: &str) {
                     fs::create_dir_all(path).unwrap();
                 }
                 let path = PathBuf::from(path).join(&entry.path);
-                let content = read_object_data(&entry.hash).unwrap();
-                fs::write(path, content).unwrap();
+                filter::restore(&entry.hash, &path).unwrap();
             }
             NyxObjectType::Tree => {
                 let path = PathBuf::from(path).join(&entry.path);
@@ -70,8 +98,8 @@ fn remove_not_ignored_files() {
     }
 }
 
-fn print_info_text(is_master: bool, commit: &Commit) {
-    if !is_master {
+fn print_info_text(is_branch: bool, branch: &str, commit: &Commit) {
+    if !is_branch {
         println!(
             "\
 You are in 'detached HEAD' state.
@@ -82,9 +110,9 @@ You are in 'detached HEAD' state.
         
 HEAD is now at {} {} commit",
             &commit.get_hash()[0..8],
-            commit.message()
+            commit.summary()
         );
         return;
     }
-    println!("HEAD is now at master");
+    println!("HEAD is now at {}", branch);
 }
@@ -0,0 +1,28 @@
+use crate::core::{
+    errors::NyxError,
+    sequencer::{Action, Sequencer, SequencerOptions},
+};
+
+pub fn cherry_pick(
+    revs: &[String],
+    proceed: bool,
+    abort: bool,
+    skip: bool,
+    options: SequencerOptions,
+) -> Result<(), NyxError> {
+    if proceed {
+        return Sequencer::resume();
+    }
+    if abort {
+        return Sequencer::abort();
+    }
+    if skip {
+        return Sequencer::skip();
+    }
+    if revs.is_empty() {
+        return Err(NyxError::Usage(String::from(
+            "no commits to cherry-pick given",
+        )));
+    }
+    Sequencer::start(Action::Pick, revs, options)
+}
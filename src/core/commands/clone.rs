@@ -0,0 +1,107 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::core::{
+    errors::NyxError,
+    index::index::Index,
+    object_format::ObjectFormat,
+    refs::refs,
+    remote::{
+        http::is_http_url,
+        remote::{Remote, DEFAULT_REMOTE},
+    },
+    revision::resolve_commit,
+    tree::tree::Tree,
+    worktree::reset_hard,
+};
+
+use super::fetch::fetch_remote;
+use super::init::init;
+
+/// Absolute path of a local source and the name of its working tree
+/// or bundle file.
+fn local_source(source: &str) -> Result<(String, String), NyxError> {
+    let source = fs::canonicalize(source)
+        .map_err(|_| NyxError::Usage(format!("repository '{}' does not exist", source)))?;
+    let source_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match source_name == ".nyx" {
+        true => source
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        false => source_name.trim_end_matches(".bundle").to_string(),
+    };
+    Ok((source.to_string_lossy().to_string(), name))
+}
+
+/// Last segment of the path of a URL, the host if there is no path.
+fn url_name(url: &str) -> String {
+    let url = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let segment = url.trim_end_matches('/').rsplit('/').next().unwrap_or(url);
+    let segment = segment.trim_end_matches(".nyx");
+    segment.split(':').next().unwrap_or(segment).to_string()
+}
+
+/// Creates a new repository in `directory` (defaults to the name of the
+/// source), fetches everything from `source` and checks out the branch
+/// HEAD of the source points to.
+///
+/// Must run before anything else touches `FILE_SYSTEM`, which then picks
+/// up the new repository as the current one.
+pub fn clone(source: &str, directory: &Option<String>) -> Result<(), NyxError> {
+    let (url, source_name) = match is_http_url(source) {
+        true => (source.to_string(), url_name(source)),
+        false => local_source(source)?,
+    };
+    let directory = directory.clone().unwrap_or(source_name);
+    let target = Path::new(&directory);
+    if target.exists() && fs::read_dir(target)?.next().is_some() {
+        return Err(NyxError::Usage(format!(
+            "destination path '{}' already exists and is not an empty directory.",
+            directory
+        )));
+    }
+
+    println!("Cloning into '{}'...", directory);
+    fs::create_dir_all(target.join(".nyx"))?;
+    env::set_current_dir(target)?;
+    // The new repository hashes objects like its source
+    let source = Remote {
+        name: DEFAULT_REMOTE.to_string(),
+        url: url.clone(),
+    };
+    let transport = source.transport()?;
+    init(transport.object_format()?.unwrap_or(ObjectFormat::Sha1))?;
+
+    let remote = Remote::add(DEFAULT_REMOTE, &url)?;
+    fetch_remote(&remote)?;
+
+    let branch = match transport.head_branch()? {
+        Some(branch) if refs::ref_exists(&remote.tracking_ref(&branch).unwrap_or_default()) => {
+            branch
+        }
+        _ => match refs::list_refs(&format!("remotes/{}", DEFAULT_REMOTE)).first() {
+            Some((name, _)) => name.rsplit('/').next().unwrap_or_default().to_string(),
+            None => {
+                println!("warning: You appear to have cloned an empty repository.");
+                return Ok(());
+            }
+        },
+    };
+
+    let commit = resolve_commit(&remote.tracking_name(&branch))?;
+    let message = format!("clone: from {}", remote.url);
+    refs::update_ref(&branch, commit.get_hash(), &message)?;
+    refs::set_head_to_branch(&branch)?;
+    refs::append_head_log("", commit.get_hash(), &message)?;
+    reset_hard(
+        &mut Index::new(),
+        &Tree::read_paths(Some(commit.tree_hash())),
+    )?;
+    remote.set_upstream(&branch, &branch)
+}
@@ -1,10 +1,44 @@
-use crate::core::{commit::Commit, index::index::Index};
+use std::fs;
+
+use crate::core::{
+    commit::Commit,
+    errors::NyxError,
+    hooks::{run_hook, run_pre_hook, COMMIT_MSG, POST_COMMIT, PRE_COMMIT},
+    index::index::Index,
+};
+use crate::FILE_SYSTEM;
+
+/// Records the index as a new commit. Unless `no_verify` is set the
+/// `pre-commit` and `commit-msg` hooks may reject it, the latter can
+/// also rewrite the message file it is given. With `sign` the commit
+/// is signed with the key of `user.signingKey`.
+pub fn commit(message: &str, no_verify: bool, sign: bool) -> Result<(), NyxError> {
+    if !no_verify {
+        run_pre_hook(PRE_COMMIT, &[])?;
+    }
+
+    let message_path = FILE_SYSTEM.get_state_file_path("COMMIT_EDITMSG");
+    fs::write(&message_path, format!("{}\n", message))?;
+    if !no_verify {
+        run_pre_hook(COMMIT_MSG, &[message_path.to_str().unwrap()])?;
+    }
+    let message = fs::read_to_string(&message_path)?.trim().to_string();
+    if message.is_empty() {
+        return Err(NyxError::Usage(String::from(
+            "Aborting commit due to empty commit message.",
+        )));
+    }
 
-pub fn commit(message: &str) {
     // TODO: Check for ustaged changes
     let mut index = Index::new();
     let tree = index.write_tree();
-    let mut commit = Commit::new(&tree.hash, message);
+    let mut commit = Commit::new(&tree.hash, &message);
+    if sign {
+        commit.sign()?;
+    }
     commit.write();
     println!("{}", commit.get_hash());
+
+    run_hook(POST_COMMIT, &[])?;
+    Ok(())
 }
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::core::{
+    errors::NyxError,
+    index::index::Index,
+    object_format::{object_format, write_object_format},
+    odb::{convert::Converter, database::chunk_threshold, loose::LooseObjectDatabase},
+    reachability::STATE_FILES,
+    refs::{
+        reflog::Reflog,
+        refs::{self, Head},
+    },
+    remote::remote::FETCH_HEAD,
+    shared::list_objects,
+};
+use crate::FILE_SYSTEM;
+
+use super::init::parse_object_format;
+
+const MAP_FILE: &str = "object-map";
+
+/// The replaced objects directory until the converted one took its place.
+const OLD_OBJECTS_DIR: &str = "objects-old";
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    match count {
+        1 => format!("1 {}", one),
+        _ => format!("{} {}", count, many),
+    }
+}
+
+/// New value of a hash stored in a ref, reflog or the index. Zero
+/// hashes get the length of the new format, hashes of objects that no
+/// longer exist have no new value.
+fn new_hash(mapping: &HashMap<String, String>, hash: &str) -> Option<String> {
+    match refs::is_zero_hash(hash) {
+        true => Some(refs::zero_hash()),
+        false => mapping.get(hash).cloned(),
+    }
+}
+
+/// Points refs, HEAD, reflogs, the index and FETCH_HEAD at the
+/// converted objects.
+fn rewrite_hashes(mapping: &HashMap<String, String>) -> Result<(), NyxError> {
+    for (name, hash) in refs::list_refs("") {
+        match mapping.get(&hash) {
+            Some(converted) => refs::write_ref(&name, converted)?,
+            None => eprintln!("warning: {} points to missing object {}", name, hash),
+        }
+    }
+    if let Some(Head::Detached(hash)) = refs::read_head() {
+        if let Some(converted) = mapping.get(&hash) {
+            fs::write(FILE_SYSTEM.get_head_path(), converted)?;
+        }
+    }
+
+    for name in Reflog::names() {
+        let mut reflog = Reflog::new(&name);
+        reflog.map_hashes(|hash| new_hash(mapping, hash));
+        reflog.write()?;
+    }
+
+    let mut index = Index::new();
+    for entry in index.entries().clone() {
+        if let Some(converted) = mapping.get(&entry.hash) {
+            index.set_entry(&entry.path, converted, entry.state);
+        }
+    }
+    index.write();
+
+    let fetch_head = FILE_SYSTEM.get_repo_dir().join(FETCH_HEAD);
+    if let Ok(content) = fs::read_to_string(&fetch_head) {
+        let lines: Vec<String> = content
+            .lines()
+            .filter_map(|line| {
+                let (hash, rest) = line.split_once('\t')?;
+                Some(format!("{}\t{}", mapping.get(hash)?, rest))
+            })
+            .collect();
+        fs::write(&fetch_head, lines.join("\n") + "\n")?;
+    }
+    Ok(())
+}
+
+/// Rewrites all objects of the repository for another object format and
+/// points everything at the new hashes. The mapping from old to new
+/// hashes is written to `map_file`, `.nyx/object-map` by default.
+pub fn convert_objects(
+    object_format_name: &str,
+    map_file: &Option<String>,
+) -> Result<(), NyxError> {
+    let format = parse_object_format(&Some(object_format_name.to_string()))?;
+    let current = object_format();
+    if format == current {
+        return Err(NyxError::Usage(format!(
+            "the repository already uses the {} object format",
+            format
+        )));
+    }
+    let repo_dir = FILE_SYSTEM.get_repo_dir();
+    let in_progress = STATE_FILES
+        .iter()
+        .filter(|name| **name != FETCH_HEAD)
+        .any(|name| repo_dir.join(name).exists());
+    if in_progress {
+        return Err(NyxError::Usage(String::from(
+            "finish or abort the operation in progress before converting",
+        )));
+    }
+
+    // Objects are converted into a directory of their own, the objects
+    // directory is only replaced once everything was converted
+    let objects_dir = FILE_SYSTEM.get_objects_dir_path();
+    let converted_dir = repo_dir.join(format!("objects-{}", format));
+    let old_dir = repo_dir.join(OLD_OBJECTS_DIR);
+    if old_dir.exists() {
+        return Err(NyxError::Usage(format!(
+            "{} is left from an interrupted conversion, restore or remove it first",
+            old_dir.display()
+        )));
+    }
+    if converted_dir.exists() {
+        fs::remove_dir_all(&converted_dir)?;
+    }
+    let target = LooseObjectDatabase::with_format(converted_dir.clone(), format, chunk_threshold());
+    let mut converter = Converter::new(&target, format);
+    let converted = list_objects()
+        .iter()
+        .try_for_each(|hash| converter.convert(hash).map(|_| ()));
+    if let Err(err) = converted {
+        _ = fs::remove_dir_all(&converted_dir);
+        return Err(err);
+    }
+
+    let map_path = match map_file {
+        Some(map_file) => PathBuf::from(map_file),
+        None => repo_dir.join(MAP_FILE),
+    };
+    let mut mapping: Vec<String> = converter
+        .mapping()
+        .iter()
+        .map(|(old, new)| format!("{} {}", old, new))
+        .collect();
+    mapping.sort();
+    fs::write(&map_path, mapping.join("\n") + "\n")?;
+
+    // Hashes written from here on have the new format
+    write_object_format(format)?;
+    rewrite_hashes(converter.mapping())?;
+    // The old objects are only deleted once the new ones are in place
+    fs::rename(&objects_dir, &old_dir)?;
+    fs::rename(&converted_dir, &objects_dir)?;
+    fs::remove_dir_all(&old_dir)?;
+
+    let counts: Vec<String> = converter
+        .counts()
+        .iter()
+        .map(|(object_type, count)| plural(*count, object_type, &format!("{}s", object_type)))
+        .collect();
+    println!(
+        "Converted {} from {} to {}",
+        match counts.is_empty() {
+            true => String::from("no objects"),
+            false => counts.join(", "),
+        },
+        current,
+        format
+    );
+    println!("Wrote the hash mapping to {}", map_path.display());
+    Ok(())
+}
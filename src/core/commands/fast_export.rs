@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+use crate::core::{
+    commit::Commit,
+    errors::NyxError,
+    object_type::NyxObjectType,
+    refs::refs,
+    rev_walk::{walk, WalkOrder},
+    revision::{peel, resolve_commit},
+    shared::read_raw_object,
+    shared::split_raw_object,
+    signature::Signature,
+    tag::Tag,
+    tree::tree::Tree,
+};
+
+const TAGS_PREFIX: &str = "tags/";
+
+/// Refs to export with their hashes, and the excluded commits.
+type Revisions = (Vec<(String, String)>, Vec<String>);
+/// Nyx does not record file modes, every file is exported as regular file.
+const FILE_MODE: &str = "100644";
+
+/// Name of the git ref for a nyx ref, e.g. `refs/heads/master`.
+fn git_ref_name(name: &str) -> String {
+    match name.strip_prefix(TAGS_PREFIX) {
+        Some(tag) => format!("refs/tags/{}", tag),
+        None => format!("refs/heads/{}", name),
+    }
+}
+
+/// Quotes paths git would otherwise misread, without the leading `./`
+/// of flattened nyx trees.
+fn quote_path(path: &str) -> String {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    if !path.starts_with('"') && !path.contains('\n') {
+        return path.to_string();
+    }
+    let escaped = path
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn signature_or_unknown(signature: Option<&Signature>) -> String {
+    match signature {
+        Some(signature) => signature.to_string(),
+        None => String::from("Unknown <unknown> 0 +0000"),
+    }
+}
+
+struct Exporter<W: Write> {
+    out: W,
+    marks: HashMap<String, usize>,
+}
+
+impl<W: Write> Exporter<W> {
+    fn mark(&mut self, hash: &str) -> usize {
+        let next = self.marks.len() + 1;
+        *self.marks.entry(hash.to_string()).or_insert(next)
+    }
+
+    /// A mark for exported objects, the hash for everything else.
+    fn reference(&self, hash: &str) -> String {
+        match self.marks.get(hash) {
+            Some(mark) => format!(":{}", mark),
+            None => hash.to_string(),
+        }
+    }
+
+    fn data(&mut self, content: &[u8]) -> io::Result<()> {
+        writeln!(self.out, "data {}", content.len())?;
+        self.out.write_all(content)?;
+        writeln!(self.out)
+    }
+
+    fn blob(&mut self, hash: &str) -> Result<(), NyxError> {
+        if self.marks.contains_key(hash) {
+            return Ok(());
+        }
+        let raw = read_raw_object(hash)?;
+        let (_, content) =
+            split_raw_object(&raw).ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+        let mark = self.mark(hash);
+        writeln!(self.out, "blob\nmark :{}", mark)?;
+        self.data(content)?;
+        Ok(())
+    }
+
+    fn commit(&mut self, commit: &Commit, git_ref: &str) -> Result<(), NyxError> {
+        let parent_paths = Tree::read_paths(
+            Commit::from_hash(commit.get_parent_hash())
+                .as_ref()
+                .map(|parent| parent.tree_hash()),
+        );
+        let paths = Tree::read_paths(Some(commit.tree_hash()));
+        for (path, hash) in &paths {
+            if parent_paths.get(path) != Some(hash) {
+                self.blob(hash)?;
+            }
+        }
+
+        // Without parents the ref is reset so the commit does not
+        // continue what was imported into it before
+        if commit.parent_hashes().is_empty() {
+            writeln!(self.out, "reset {}", git_ref)?;
+        }
+        let mark = self.mark(commit.get_hash());
+        writeln!(self.out, "commit {}\nmark :{}", git_ref, mark)?;
+        let author = commit.author().or(commit.committer());
+        writeln!(self.out, "author {}", signature_or_unknown(author))?;
+        let committer = commit.committer().or(commit.author());
+        writeln!(self.out, "committer {}", signature_or_unknown(committer))?;
+        self.data(format!("{}\n", commit.message()).as_bytes())?;
+        for (idx, parent) in commit.parent_hashes().iter().enumerate() {
+            let keyword = if idx == 0 { "from" } else { "merge" };
+            writeln!(self.out, "{} {}", keyword, self.reference(parent))?;
+        }
+        for path in parent_paths
+            .keys()
+            .filter(|path| !paths.contains_key(*path))
+        {
+            writeln!(self.out, "D {}", quote_path(path))?;
+        }
+        for (path, hash) in &paths {
+            if parent_paths.get(path) != Some(hash) {
+                let blob = self.reference(hash);
+                writeln!(self.out, "M {} {} {}", FILE_MODE, blob, quote_path(path))?;
+            }
+        }
+        writeln!(self.out)?;
+        Ok(())
+    }
+
+    fn tag(&mut self, tag: &Tag) -> Result<(), NyxError> {
+        writeln!(self.out, "tag {}", tag.name())?;
+        writeln!(self.out, "from {}", self.reference(tag.object()))?;
+        if let Some(tagger) = tag.tagger() {
+            writeln!(self.out, "tagger {}", tagger)?;
+        }
+        self.data(format!("{}\n", tag.message()).as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Splits the revisions into the refs to export and the excluded commits,
+/// without revisions all branches and tags are exported.
+fn parse_revisions(revisions: &[String]) -> Result<Revisions, NyxError> {
+    let mut tips = Vec::new();
+    let mut exclude = Vec::new();
+    let expand = |name: &str| {
+        let name = refs::expand_ref_name(name)
+            .ok_or_else(|| NyxError::Usage(format!("'{}' is neither a branch nor a tag", name)))?;
+        let hash = refs::read_ref(&name).unwrap_or_default();
+        Ok::<(String, String), NyxError>((name, hash))
+    };
+    for revision in revisions {
+        if let Some((from, to)) = revision.split_once("..") {
+            let or_head = |rev: &str| match rev.is_empty() {
+                true => refs::HEAD.to_string(),
+                false => rev.to_string(),
+            };
+            exclude.push(resolve_commit(&or_head(from))?.get_hash().to_string());
+            tips.push(expand(&or_head(to))?);
+        } else if let Some(rev) = revision.strip_prefix('^') {
+            exclude.push(resolve_commit(rev)?.get_hash().to_string());
+        } else {
+            tips.push(expand(revision)?);
+        }
+    }
+    if revisions.is_empty() {
+        tips = refs::list_refs("")
+            .into_iter()
+            .filter(|(name, _)| refs::is_branch_name(name) || name.starts_with(TAGS_PREFIX))
+            .collect();
+    }
+    tips.sort();
+    tips.dedup();
+    Ok((tips, exclude))
+}
+
+/// Writes the history of the refs as a git fast-import stream to stdout.
+/// Commits are emitted parents first under the first ref reaching them,
+/// a final reset puts every branch and lightweight tag in place.
+pub fn fast_export(revisions: &[String]) -> Result<(), NyxError> {
+    let (tips, exclude) = parse_revisions(revisions)?;
+
+    let mut labels: HashMap<String, String> = HashMap::new();
+    for (name, hash) in &tips {
+        for commit in walk(&[peel(hash)], &exclude, WalkOrder::Date) {
+            labels
+                .entry(commit.get_hash().to_string())
+                .or_insert_with(|| git_ref_name(name));
+        }
+    }
+    let commit_tips: Vec<String> = tips.iter().map(|(_, hash)| peel(hash)).collect();
+    let mut commits = walk(&commit_tips, &exclude, WalkOrder::Topo);
+    commits.reverse();
+
+    let mut exporter = Exporter {
+        out: io::BufWriter::new(io::stdout().lock()),
+        marks: HashMap::new(),
+    };
+    for commit in &commits {
+        exporter.commit(commit, &labels[commit.get_hash()])?;
+    }
+
+    let mut resets: BTreeMap<String, String> = BTreeMap::new();
+    for (name, hash) in &tips {
+        match Tag::from_hash(hash) {
+            Some(tag) if tag.object_type() == NyxObjectType::Commit => exporter.tag(&tag)?,
+            Some(tag) => eprintln!(
+                "warning: skipping tag {} pointing to a {}",
+                tag.name(),
+                tag.object_type()
+            ),
+            None => _ = resets.insert(git_ref_name(name), hash.clone()),
+        }
+    }
+    for (git_ref, hash) in resets {
+        let from = exporter.reference(&hash);
+        writeln!(exporter.out, "reset {}\nfrom {}\n", git_ref, from)?;
+    }
+    exporter.out.flush()?;
+    Ok(())
+}
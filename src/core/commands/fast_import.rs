@@ -0,0 +1,459 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+
+use crate::core::{
+    commit::Commit, errors::NyxError, index::file_state::NyxFileState, index::index::Index,
+    object_type::NyxObjectType, reachability::is_object_hash, refs::refs, revision::resolve_commit,
+    shared::generate_object, signature::Signature, tag::Tag, tree::tree::Tree,
+};
+
+const GITLINK_MODE: &str = "160000";
+const TREE_MODES: [&str; 2] = ["040000", "40000"];
+/// Flattened nyx trees start every path with the root directory.
+const ROOT_PREFIX: &str = "./";
+
+/// Paths of the tree relative to its root, as the stream names them.
+fn tree_paths(hash: Option<&str>) -> BTreeMap<String, String> {
+    Tree::read_paths(hash)
+        .into_iter()
+        .map(|(path, hash)| match path.strip_prefix(ROOT_PREFIX) {
+            Some(path) => (path.to_string(), hash),
+            None => (path, hash),
+        })
+        .collect()
+}
+
+/// Name of the nyx ref for a git ref, e.g. `refs/heads/main` becomes
+/// `main` and `refs/tags/v1` becomes `tags/v1`. `None` for refs nyx has
+/// no place for, like `refs/notes/commits`.
+fn nyx_ref_name(git_ref: &str) -> Option<String> {
+    if let Some(branch) = git_ref.strip_prefix("refs/heads/") {
+        return refs::is_branch_name(branch).then(|| branch.to_string());
+    }
+    git_ref
+        .strip_prefix("refs/")
+        .filter(|name| name.starts_with("tags/"))
+        .map(str::to_string)
+}
+
+/// Refs named by the stream must stay below the refs directory.
+fn check_ref(git_ref: &str) -> Result<(), NyxError> {
+    match git_ref
+        .strip_prefix("refs/")
+        .is_some_and(refs::is_valid_ref_name)
+    {
+        true => Ok(()),
+        false => Err(NyxError::Usage(format!(
+            "fast-import: invalid ref '{}'",
+            git_ref
+        ))),
+    }
+}
+
+fn invalid(line: &str) -> NyxError {
+    NyxError::Usage(format!("fast-import: unsupported command '{}'", line))
+}
+
+/// Undoes the C-style quoting git applies to unusual paths.
+fn unquote_path(path: &str) -> String {
+    let inner = match path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => return path.to_string(),
+    };
+    let mut bytes = Vec::new();
+    let mut chars = inner.bytes().peekable();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = (digit - b'0') as u32;
+                for _ in 0..2 {
+                    if let Some(next @ b'0'..=b'7') = chars.peek().copied() {
+                        value = value * 8 + (next - b'0') as u32;
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => (),
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Splits `<path>` or `"<path>" <rest>` of file commands.
+fn split_path(value: &str) -> (String, &str) {
+    if value.starts_with('"') {
+        let mut escaped = false;
+        for (idx, c) in value.char_indices().skip(1) {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => {
+                    return (unquote_path(&value[..=idx]), value[idx + 1..].trim_start());
+                }
+                _ => escaped = false,
+            }
+        }
+    }
+    match value.split_once(' ') {
+        Some((path, rest)) => (path.to_string(), rest),
+        None => (value.to_string(), ""),
+    }
+}
+
+/// Reads the stream line by line, `data` commands as raw bytes.
+struct Stream {
+    input: Vec<u8>,
+    pos: usize,
+    peeked: Option<String>,
+}
+
+impl Stream {
+    fn next_line(&mut self) -> Option<String> {
+        if let Some(line) = self.peeked.take() {
+            return Some(line);
+        }
+        loop {
+            if self.pos >= self.input.len() {
+                return None;
+            }
+            let rest = &self.input[self.pos..];
+            let end = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            let line = String::from_utf8_lossy(&rest[..end]).to_string();
+            self.pos += end + 1;
+            if !line.starts_with('#') {
+                return Some(line);
+            }
+        }
+    }
+
+    fn peek_line(&mut self) -> Option<&str> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_line();
+        }
+        self.peeked.as_deref()
+    }
+
+    /// Reads the content of `data <count>` or `data <<<delimiter>`.
+    fn data(&mut self) -> Result<Vec<u8>, NyxError> {
+        let line = self.next_line().unwrap_or_default();
+        let spec = line.strip_prefix("data ").ok_or_else(|| invalid(&line))?;
+        if let Some(delimiter) = spec.strip_prefix("<<") {
+            let mut content = Vec::new();
+            loop {
+                let line = self.next_line().ok_or_else(|| invalid(&line))?;
+                if line == delimiter {
+                    return Ok(content);
+                }
+                content.extend(line.into_bytes());
+                content.push(b'\n');
+            }
+        }
+        let count: usize = spec.parse().map_err(|_| invalid(&line))?;
+        let content = self
+            .input
+            .get(self.pos..self.pos + count)
+            .ok_or_else(|| invalid(&line))?
+            .to_vec();
+        self.pos += count;
+        if self.input.get(self.pos) == Some(&b'\n') {
+            self.pos += 1;
+        }
+        Ok(content)
+    }
+
+    /// Consumes the line if it starts with `prefix` and returns the rest.
+    fn optional(&mut self, prefix: &str) -> Option<String> {
+        let rest = self.peek_line()?.strip_prefix(prefix)?.to_string();
+        self.peeked = None;
+        Some(rest)
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    blobs: usize,
+    commits: usize,
+    tags: usize,
+}
+
+struct Importer {
+    stream: Stream,
+    marks: HashMap<String, String>,
+    /// Tips of the refs touched by the stream, `None` after a plain reset.
+    refs: BTreeMap<String, Option<String>>,
+    counts: Counts,
+}
+
+impl Importer {
+    /// Resolves a mark, a ref of the stream, a hash or a nyx revision.
+    fn resolve(&self, value: &str) -> Result<String, NyxError> {
+        if value.starts_with(':') {
+            return self
+                .marks
+                .get(value)
+                .cloned()
+                .ok_or_else(|| NyxError::Usage(format!("fast-import: unknown mark {}", value)));
+        }
+        if let Some(Some(hash)) = self.refs.get(value) {
+            return Ok(hash.clone());
+        }
+        if is_object_hash(value) {
+            return Ok(value.to_string());
+        }
+        let name = nyx_ref_name(value).unwrap_or_else(|| value.to_string());
+        Ok(resolve_commit(&name)?.get_hash().to_string())
+    }
+
+    fn set_mark(&mut self, mark: Option<String>, hash: &str) {
+        if let Some(mark) = mark {
+            self.marks.insert(mark, hash.to_string());
+        }
+    }
+
+    fn signature(&mut self, prefix: &str) -> Result<Option<Signature>, NyxError> {
+        match self.stream.optional(prefix) {
+            Some(value) => Signature::parse(&value).map(Some).ok_or_else(|| {
+                NyxError::Usage(format!("fast-import: invalid {}{}", prefix, value))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn blob(&mut self) -> Result<(), NyxError> {
+        let mark = self.stream.optional("mark ");
+        self.stream.optional("original-oid ");
+        let content = self.stream.data()?;
+        let hash = generate_object(&content, NyxObjectType::Blob);
+        self.set_mark(mark, &hash);
+        self.counts.blobs += 1;
+        Ok(())
+    }
+
+    /// Applies a file command of a commit to its paths.
+    fn file_command(
+        &mut self,
+        line: &str,
+        paths: &mut BTreeMap<String, String>,
+    ) -> Result<(), NyxError> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "M" => {
+                let mut parts = rest.splitn(3, ' ');
+                let (mode, dataref, path) = match (parts.next(), parts.next(), parts.next()) {
+                    (Some(mode), Some(dataref), Some(path)) => (mode, dataref, unquote_path(path)),
+                    _ => return Err(invalid(line)),
+                };
+                let hash = match dataref {
+                    "inline" => generate_object(&self.stream.data()?, NyxObjectType::Blob),
+                    dataref => self.resolve(dataref)?,
+                };
+                if mode == GITLINK_MODE {
+                    eprintln!("warning: skipping submodule '{}'", path);
+                } else if path.contains(char::is_whitespace) {
+                    eprintln!("warning: skipping '{}', names with whitespace", path);
+                } else if TREE_MODES.contains(&mode) {
+                    let prefix = format!("{}/", path);
+                    paths.retain(|p, _| !p.starts_with(&prefix));
+                    for (sub_path, blob) in tree_paths(Some(&hash)) {
+                        paths.insert(format!("{}{}", prefix, sub_path), blob);
+                    }
+                } else {
+                    paths.insert(path, hash);
+                }
+            }
+            "D" => {
+                let path = unquote_path(rest);
+                let prefix = format!("{}/", path);
+                paths.retain(|p, _| *p != path && !p.starts_with(&prefix));
+            }
+            "C" | "R" => {
+                let (source, destination) = split_path(rest);
+                let destination = unquote_path(destination);
+                let prefix = format!("{}/", source);
+                let moved: Vec<(String, String)> = paths
+                    .iter()
+                    .filter(|(p, _)| **p == source || p.starts_with(&prefix))
+                    .map(|(p, hash)| {
+                        (
+                            format!("{}{}", destination, &p[source.len()..]),
+                            hash.clone(),
+                        )
+                    })
+                    .collect();
+                if command == "R" {
+                    paths.retain(|p, _| *p != source && !p.starts_with(&prefix));
+                }
+                paths.extend(moved);
+            }
+            "deleteall" => paths.clear(),
+            _ => return Err(invalid(line)),
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self, git_ref: &str) -> Result<(), NyxError> {
+        check_ref(git_ref)?;
+        let mark = self.stream.optional("mark ");
+        self.stream.optional("original-oid ");
+        let author = self.signature("author ")?;
+        let committer = self
+            .signature("committer ")?
+            .ok_or_else(|| NyxError::Usage(String::from("fast-import: missing committer")))?;
+        self.stream.optional("encoding ");
+        let message = String::from_utf8_lossy(&self.stream.data()?).to_string();
+
+        // Without `from` the commit continues the current tip of its ref
+        let mut parents: Vec<String> = Vec::new();
+        match self.stream.optional("from ") {
+            Some(from) => parents.push(self.resolve(&from)?),
+            None => match self.refs.get(git_ref) {
+                Some(tip) => parents.extend(tip.clone()),
+                None => {
+                    parents.extend(nyx_ref_name(git_ref).and_then(|name| refs::read_ref(&name)))
+                }
+            },
+        }
+        while let Some(merge) = self.stream.optional("merge ") {
+            parents.push(self.resolve(&merge)?);
+        }
+
+        let mut paths = tree_paths(
+            parents
+                .first()
+                .and_then(|parent| Commit::from_hash(parent))
+                .as_ref()
+                .map(|parent| parent.tree_hash()),
+        );
+        while let Some(line) = self.stream.peek_line() {
+            let command = line.split(' ').next().unwrap_or_default().to_string();
+            if !["M", "D", "C", "R", "N", "deleteall"].contains(&command.as_str()) {
+                break;
+            }
+            let line = self.stream.next_line().unwrap_or_default();
+            if command == "N" {
+                eprintln!("warning: skipping note '{}'", line);
+                continue;
+            }
+            self.file_command(&line, &mut paths)?;
+        }
+
+        let paths = paths
+            .into_iter()
+            .map(|(path, hash)| (format!("{}{}", ROOT_PREFIX, path), hash))
+            .collect();
+        let tree = Index::write_tree_from_map(&paths);
+        let mut commit = Commit::with_parents(&tree.hash, parents, message.trim_end());
+        commit.set_author(Some(author.unwrap_or_else(|| committer.clone())));
+        commit.set_committer(Some(committer));
+        let hash = commit.write_object().to_string();
+        self.set_mark(mark, &hash);
+        self.refs.insert(git_ref.to_string(), Some(hash));
+        self.counts.commits += 1;
+        Ok(())
+    }
+
+    fn tag(&mut self, name: &str) -> Result<(), NyxError> {
+        check_ref(&format!("refs/tags/{}", name))?;
+        self.stream.optional("mark ");
+        let from = self
+            .stream
+            .optional("from ")
+            .ok_or_else(|| NyxError::Usage(format!("fast-import: tag {} without from", name)))?;
+        let object = self.resolve(&from)?;
+        self.stream.optional("original-oid ");
+        let tagger = self.signature("tagger ")?;
+        let message = String::from_utf8_lossy(&self.stream.data()?).to_string();
+
+        let mut tag = Tag::new(&object, NyxObjectType::Commit, name, message.trim_end());
+        tag.set_tagger(tagger);
+        let hash = tag.write_object().to_string();
+        self.refs.insert(format!("refs/tags/{}", name), Some(hash));
+        self.counts.tags += 1;
+        Ok(())
+    }
+
+    fn reset(&mut self, git_ref: &str) -> Result<(), NyxError> {
+        check_ref(git_ref)?;
+        let tip = match self.stream.optional("from ") {
+            Some(from) => Some(self.resolve(&from)?),
+            None => None,
+        };
+        self.refs.insert(git_ref.to_string(), tip);
+        Ok(())
+    }
+
+    fn run(&mut self) -> Result<(), NyxError> {
+        while let Some(line) = self.stream.next_line() {
+            let (command, argument) = line.split_once(' ').unwrap_or((&line, ""));
+            match command {
+                "" | "checkpoint" | "feature" | "option" | "progress" => (),
+                "done" => break,
+                "blob" => self.blob()?,
+                "commit" => self.commit(argument)?,
+                "tag" => self.tag(argument)?,
+                "reset" => self.reset(argument)?,
+                _ => return Err(invalid(&line)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a git fast-import stream from stdin and creates the blobs,
+/// commits and tags it describes. Refs are only updated at the end.
+pub fn fast_import() -> Result<(), NyxError> {
+    let mut input = Vec::new();
+    io::stdin().lock().read_to_end(&mut input)?;
+    let mut importer = Importer {
+        stream: Stream {
+            input,
+            pos: 0,
+            peeked: None,
+        },
+        marks: HashMap::new(),
+        refs: BTreeMap::new(),
+        counts: Counts::default(),
+    };
+    importer.run()?;
+
+    let unborn = refs::head_hash().is_empty();
+    let mut updated = 0;
+    for (git_ref, tip) in &importer.refs {
+        let Some(name) = nyx_ref_name(git_ref) else {
+            eprintln!("warning: skipping ref '{}'", git_ref);
+            continue;
+        };
+        match tip {
+            Some(hash) if refs::is_branch_name(&name) => {
+                refs::update_ref(&name, hash, "fast-import")?
+            }
+            Some(hash) => refs::write_ref(&name, hash)?,
+            None => continue,
+        }
+        updated += 1;
+    }
+
+    // Like a first commit, the import fills an unborn HEAD and its index
+    let head_branch = refs::current_branch().unwrap_or_else(|| refs::DEFAULT_BRANCH.to_string());
+    if unborn && refs::ref_exists(&head_branch) {
+        refs::set_head_to_branch(&head_branch)?;
+        let commit = resolve_commit(&head_branch)?;
+        refs::append_head_log("", commit.get_hash(), "fast-import")?;
+        let paths = Tree::read_paths(Some(commit.tree_hash()));
+        Index::new().reset_to(&paths, NyxFileState::Committed);
+    }
+
+    let counts = &importer.counts;
+    println!(
+        "fast-import: blobs {}, commits {}, tags {}, refs updated {}",
+        counts.blobs, counts.commits, counts.tags, updated
+    );
+    Ok(())
+}
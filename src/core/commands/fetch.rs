@@ -0,0 +1,124 @@
+use std::fs;
+
+use crate::core::{
+    errors::NyxError,
+    refs::refs,
+    remote::{
+        remote::{Remote, FETCH_HEAD},
+        transfer::fetch_objects,
+    },
+    rev_walk::is_ancestor,
+    sequencer::short_hash,
+};
+use crate::FILE_SYSTEM;
+
+const TAGS_PREFIX: &str = "tags/";
+
+/// Downloads the branches and new tags of a remote and updates the
+/// remote-tracking refs. Returns the lines describing the updates.
+pub fn fetch_remote(remote: &Remote) -> Result<Vec<String>, NyxError> {
+    let transport = remote.transport()?;
+    let remote_refs = transport.list_refs()?;
+    // The names are chosen by the remote and become paths below refs
+    if let Some((name, _)) = remote_refs
+        .iter()
+        .find(|(name, _)| !refs::is_valid_ref_name(name))
+    {
+        return Err(NyxError::Usage(format!(
+            "invalid ref '{}' advertised by '{}'",
+            name,
+            transport.url()
+        )));
+    }
+    let branches: Vec<&(String, String)> = remote_refs
+        .iter()
+        .filter(|(name, _)| refs::is_branch_name(name))
+        .collect();
+    let tags: Vec<&(String, String)> = remote_refs
+        .iter()
+        .filter(|(name, _)| name.starts_with(TAGS_PREFIX) && !refs::ref_exists(name))
+        .collect();
+
+    let tips: Vec<String> = branches
+        .iter()
+        .chain(tags.iter())
+        .map(|(_, hash)| hash.clone())
+        .collect();
+    fetch_objects(transport.as_ref(), &tips)?;
+
+    // The branch HEAD of the remote points to comes first in FETCH_HEAD
+    let head_branch = transport.head_branch()?;
+    let mut fetch_head: Vec<String> = Vec::new();
+    let mut lines = Vec::new();
+    for (branch, hash) in &branches {
+        let line = format!("{}\tbranch '{}' of {}", hash, branch, transport.url());
+        match Some(branch) == head_branch.as_ref() {
+            true => fetch_head.insert(0, line),
+            false => fetch_head.push(line),
+        }
+
+        let tracking = match remote.tracking_ref(branch) {
+            Some(tracking) => tracking,
+            None => {
+                lines.push(format!(
+                    " * branch            {:<10} -> {}",
+                    branch, FETCH_HEAD
+                ));
+                continue;
+            }
+        };
+        let name = remote.tracking_name(branch);
+        match refs::read_ref(&tracking) {
+            Some(old) if old == *hash => (),
+            Some(old) if is_ancestor(&old, hash) => {
+                refs::update_ref(&tracking, hash, "fetch: fast-forward")?;
+                lines.push(format!(
+                    "   {}..{}  {} -> {}",
+                    short_hash(&old),
+                    short_hash(hash),
+                    branch,
+                    name
+                ));
+            }
+            Some(old) => {
+                refs::update_ref(&tracking, hash, "fetch: forced-update")?;
+                lines.push(format!(
+                    " + {}...{} {} -> {}  (forced update)",
+                    short_hash(&old),
+                    short_hash(hash),
+                    branch,
+                    name
+                ));
+            }
+            None => {
+                refs::update_ref(&tracking, hash, "fetch: storing head")?;
+                lines.push(format!(" * [new branch]      {:<10} -> {}", branch, name));
+            }
+        }
+    }
+
+    for (tag, hash) in tags {
+        refs::write_ref(tag, hash)?;
+        let name = tag.trim_start_matches(TAGS_PREFIX);
+        lines.push(format!(" * [new tag]         {:<10} -> {}", name, name));
+    }
+
+    fs::write(
+        FILE_SYSTEM.get_state_file_path(FETCH_HEAD),
+        fetch_head.join("\n") + "\n",
+    )?;
+    Ok(lines)
+}
+
+pub fn fetch(remote: &Option<String>) -> Result<(), NyxError> {
+    let name = remote.clone().unwrap_or_else(Remote::default_name);
+    let remote = Remote::load(&name)?;
+    let lines = fetch_remote(&remote)?;
+    if !lines.is_empty() {
+        println!("From {}", remote.url);
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Read, Write};
+
+use crate::core::{
+    commit::Commit,
+    errors::NyxError,
+    index::index::Index,
+    object_type::NyxObjectType,
+    odb::{stream, writer::ObjectWriter},
+    reachability::{is_object_hash, object_references, reachable_objects, root_hashes},
+    refs::{reflog::Reflog, refs},
+    shared::{list_objects, open_object, read_object_data},
+    tag::Tag,
+};
+
+/// Collects the problems found while checking the object database.
+struct Checker {
+    /// Type of every object whose header could be read
+    types: BTreeMap<String, NyxObjectType>,
+    errors: Vec<String>,
+}
+
+impl Checker {
+    fn error(&mut self, message: String) {
+        self.errors.push(message);
+    }
+
+    /// Verifies that the hash matches the content and the header declares
+    /// a known type and the actual length. Returns the type and content,
+    /// blobs are hashed as they are streamed and come without content.
+    fn check_file(&mut self, hash: &str) -> Option<(NyxObjectType, Vec<u8>)> {
+        let mut reader = match open_object(hash) {
+            Ok(reader) => reader,
+            Err(err) => {
+                self.error(format!("{}: unable to read object: {}", hash, err));
+                return None;
+            }
+        };
+        let (object_type, size) = (reader.object_type, reader.size);
+
+        let mut content = Vec::new();
+        let mut hasher = ObjectWriter::new(io::sink(), object_type, size).ok()?;
+        let copied = match object_type {
+            NyxObjectType::Blob => stream::copy(&mut reader, &mut hasher),
+            _ => reader
+                .read_to_end(&mut content)
+                .and_then(|count| hasher.write_all(&content).map(|_| count as u64)),
+        };
+        match copied {
+            Ok(copied) if copied == size => (),
+            Ok(copied) => {
+                self.error(format!(
+                    "{}: declared length {} but content has {} bytes",
+                    hash, size, copied
+                ));
+                return None;
+            }
+            Err(err) => {
+                self.error(format!("{}: unable to read object: {}", hash, err));
+                return None;
+            }
+        }
+        if reader.has_trailing_data().unwrap_or(false) {
+            self.error(format!(
+                "{}: content is longer than the declared length {}",
+                hash, size
+            ));
+        }
+
+        let actual = hasher.finish().ok()?.1;
+        if actual != hash {
+            self.error(format!(
+                "{}: hash mismatch, content hashes to {}",
+                hash, actual
+            ));
+        }
+        Some((object_type, content))
+    }
+
+    /// Parses commits, trees and tags and checks their syntax.
+    fn check_content(&mut self, hash: &str, object_type: NyxObjectType, content: &[u8]) {
+        let text = match std::str::from_utf8(content) {
+            Ok(text) => text,
+            Err(_) if object_type == NyxObjectType::Blob => return,
+            Err(_) => {
+                self.error(format!("{}: {} is not valid UTF-8", hash, object_type));
+                return;
+            }
+        };
+        match object_type {
+            NyxObjectType::Commit => {
+                if Commit::from_hash(hash).is_none() {
+                    self.error(format!("{}: broken commit, no tree header", hash));
+                }
+            }
+            NyxObjectType::Tree => {
+                for line in text.lines().filter(|line| !line.is_empty()) {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    let valid = match fields.as_slice() {
+                        [entry_type, entry_hash, _] => {
+                            matches!(*entry_type, "blob" | "tree") && is_object_hash(entry_hash)
+                        }
+                        _ => false,
+                    };
+                    if !valid {
+                        self.error(format!("{}: malformed tree entry '{}'", hash, line));
+                    }
+                }
+            }
+            NyxObjectType::Tag => {
+                if Tag::from_hash(hash).is_none() {
+                    self.error(format!("{}: broken tag, missing object or type", hash));
+                }
+            }
+            NyxObjectType::Blob => (),
+        }
+    }
+
+    /// Reports a reference from `from` (an object or a ref) to a missing
+    /// object or an object of the wrong type.
+    fn check_link(&mut self, from: &str, to: &str, expected: Option<NyxObjectType>) {
+        match (self.types.get(to), expected) {
+            (None, Some(expected)) => self.error(format!(
+                "missing {} {} (referenced by {})",
+                expected, to, from
+            )),
+            (None, None) => self.error(format!("missing object {} (referenced by {})", to, from)),
+            (Some(actual), Some(expected)) if *actual != expected => self.error(format!(
+                "broken link from {} to {} {}, which is a {}",
+                from, expected, to, actual
+            )),
+            _ => (),
+        }
+    }
+
+    fn check_links(&mut self, hash: &str, object_type: NyxObjectType) {
+        match object_type {
+            NyxObjectType::Commit => {
+                if let Some(commit) = Commit::from_hash(hash) {
+                    self.check_link(hash, commit.tree_hash(), Some(NyxObjectType::Tree));
+                    for parent in commit.parent_hashes() {
+                        self.check_link(hash, parent, Some(NyxObjectType::Commit));
+                    }
+                }
+            }
+            NyxObjectType::Tree => {
+                let content = read_object_data(hash).unwrap_or_default();
+                for line in content.lines() {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    if let [entry_type, entry_hash, _] = fields.as_slice() {
+                        if is_object_hash(entry_hash) {
+                            self.check_link(hash, entry_hash, NyxObjectType::parse(entry_type));
+                        }
+                    }
+                }
+            }
+            NyxObjectType::Tag => {
+                if let Some(tag) = Tag::from_hash(hash) {
+                    self.check_link(hash, tag.object(), Some(tag.object_type()));
+                }
+            }
+            NyxObjectType::Blob => (),
+        }
+    }
+}
+
+/// Verifies the integrity of all objects and the refs, reflogs and index
+/// pointing into them, then reports objects that are not reachable.
+pub fn fsck(unreachable: bool, no_dangling: bool, no_reflogs: bool) -> Result<(), NyxError> {
+    let mut checker = Checker {
+        types: BTreeMap::new(),
+        errors: Vec::new(),
+    };
+
+    let objects = list_objects();
+    let mut parsed = Vec::new();
+    for hash in &objects {
+        if let Some((object_type, content)) = checker.check_file(hash) {
+            checker.check_content(hash, object_type, &content);
+            checker.types.insert(hash.clone(), object_type);
+            parsed.push((hash.clone(), object_type));
+        }
+    }
+    for (hash, object_type) in &parsed {
+        checker.check_links(hash, *object_type);
+    }
+
+    let head = refs::head_hash();
+    if !head.is_empty() {
+        checker.check_link(refs::HEAD, &head, Some(NyxObjectType::Commit));
+    }
+    for (name, hash) in refs::list_refs("") {
+        checker.check_link(&format!("refs/{}", name), &hash, None);
+    }
+    for entry in Index::new().entries() {
+        checker.check_link(
+            &format!("index entry {}", entry.path),
+            &entry.hash,
+            Some(NyxObjectType::Blob),
+        );
+    }
+    if !no_reflogs {
+        for name in Reflog::names() {
+            for entry in Reflog::new(&name).entries() {
+                if !refs::is_zero_hash(&entry.new_hash) {
+                    checker.check_link(&format!("reflog of {}", name), &entry.new_hash, None);
+                }
+            }
+        }
+    }
+
+    // Unreachable objects nothing else unreachable points to are dangling
+    let reachable = reachable_objects(&root_hashes(!no_reflogs));
+    let unreachable_objects: Vec<&(String, NyxObjectType)> = parsed
+        .iter()
+        .filter(|(hash, _)| !reachable.contains(hash))
+        .collect();
+    let referenced: HashSet<String> = unreachable_objects
+        .iter()
+        .flat_map(|(hash, _)| object_references(hash).unwrap_or_default())
+        .collect();
+    for (hash, object_type) in unreachable_objects {
+        if unreachable {
+            println!("unreachable {} {}", object_type, hash);
+        } else if !no_dangling && !referenced.contains(hash) {
+            println!("dangling {} {}", object_type, hash);
+        }
+    }
+
+    for error in &checker.errors {
+        eprintln!("error: {}", error);
+    }
+    if !checker.errors.is_empty() {
+        return Err(NyxError::Usage(format!(
+            "{} problem{} found in {} objects",
+            checker.errors.len(),
+            if checker.errors.len() == 1 { "" } else { "s" },
+            objects.len()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::core::errors::NyxError;
+
+use super::prune::{parse_expire, prune_objects};
+
+/// Unreachable objects younger than this are kept, they may belong to
+/// an operation that has not updated any ref yet.
+const DEFAULT_PRUNE_EXPIRE: &str = "2 weeks ago";
+
+pub fn gc(prune: &Option<String>, dry_run: bool) -> Result<(), NyxError> {
+    let expire = parse_expire(prune.as_deref().unwrap_or(DEFAULT_PRUNE_EXPIRE))?;
+    let expire = match expire {
+        Some(expire) => expire,
+        None => {
+            println!("Nothing to prune");
+            return Ok(());
+        }
+    };
+
+    let result = prune_objects(expire, dry_run, false)?;
+    println!("{}", result.summary(dry_run));
+    Ok(())
+}
@@ -1,7 +1,58 @@
-use crate::core::{errors::NyxError, shared::get_object_hash};
+use std::io::{self, BufRead, Read};
+use std::path::Path;
 
-pub fn hash_object(path: &str) -> Result<String, NyxError> {
-    let object_hash = get_object_hash(path);
-    println!("{object_hash}");
-    Ok(object_hash)
+use crate::core::{
+    errors::NyxError,
+    object_type::NyxObjectType,
+    shared::{generate_object, hash_file, hash_object as hash_content, write_file_object},
+};
+
+fn hash_path(path: &str, object_type: NyxObjectType, write: bool) -> Result<String, NyxError> {
+    match write {
+        true => write_file_object(Path::new(path), object_type),
+        false => hash_file(Path::new(path), object_type),
+    }
+}
+
+/// Prints the object hash of the files, of stdin with `stdin` or of the
+/// files named on stdin with `stdin_paths`. Objects are only stored
+/// with `write`.
+pub fn hash_object(
+    paths: &[String],
+    object_type: &str,
+    write: bool,
+    stdin: bool,
+    stdin_paths: bool,
+) -> Result<(), NyxError> {
+    let object_type = NyxObjectType::parse(object_type)
+        .ok_or_else(|| NyxError::Usage(format!("invalid object type '{}'", object_type)))?;
+    if stdin && stdin_paths {
+        return Err(NyxError::Usage(String::from(
+            "--stdin and --stdin-paths cannot be combined",
+        )));
+    }
+    if stdin_paths && !paths.is_empty() {
+        return Err(NyxError::Usage(String::from(
+            "paths cannot be given with --stdin-paths",
+        )));
+    }
+
+    if stdin {
+        let mut content = Vec::new();
+        io::stdin().lock().read_to_end(&mut content)?;
+        let hash = match write {
+            true => generate_object(&content, object_type),
+            false => hash_content(&content, object_type),
+        };
+        println!("{}", hash);
+    }
+    for path in paths {
+        println!("{}", hash_path(path, object_type, write)?);
+    }
+    if stdin_paths {
+        for path in io::stdin().lock().lines() {
+            println!("{}", hash_path(&path?, object_type, write)?);
+        }
+    }
+    Ok(())
 }
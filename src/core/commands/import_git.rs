@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::{
+    errors::NyxError,
+    git::{import::Importer, repository::GitRepository},
+    index::{file_state::NyxFileState, index::Index},
+    refs::refs,
+    rev_walk::is_ancestor,
+    revision::resolve_commit,
+    sequencer::short_hash,
+    tree::tree::Tree,
+};
+use crate::FILE_SYSTEM;
+
+const GIT_BRANCH_PREFIX: &str = "refs/heads/";
+const GIT_TAG_PREFIX: &str = "refs/tags/";
+const MAP_FILE: &str = "git-map";
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    match count {
+        1 => format!("1 {}", one),
+        _ => format!("{} {}", count, many),
+    }
+}
+
+/// Name of the nyx ref for a git branch or tag, other refs are not imported.
+fn nyx_ref_name(git_ref: &str) -> Option<String> {
+    if let Some(branch) = git_ref.strip_prefix(GIT_BRANCH_PREFIX) {
+        return refs::is_branch_name(branch).then(|| branch.to_string());
+    }
+    git_ref
+        .strip_prefix(GIT_TAG_PREFIX)
+        .map(|tag| format!("tags/{}", tag))
+}
+
+/// Refuses updates that would lose commits or leave the working tree
+/// behind: moving the checked out branch, and moving other branches
+/// or tags anywhere but forward unless `force` is given.
+fn check_update(name: &str, new: &str, force: bool) -> Result<(), NyxError> {
+    let old = match refs::read_ref(name) {
+        Some(old) if old != new => old,
+        _ => return Ok(()),
+    };
+    if refs::current_branch().as_deref() == Some(name) {
+        return Err(NyxError::Usage(format!(
+            "refusing to update checked out branch '{}'",
+            name
+        )));
+    }
+    let fast_forward = refs::is_branch_name(name) && is_ancestor(&old, new);
+    if !force && !fast_forward {
+        return Err(NyxError::Usage(format!(
+            "refusing to move '{}' from {} to {}, use --force",
+            name,
+            short_hash(&old),
+            short_hash(new)
+        )));
+    }
+    Ok(())
+}
+
+/// Converts the history of a git repository into this repository and
+/// points the branches and tags at the converted commits. If HEAD has no
+/// commit yet it takes over the branch of the git HEAD, the working
+/// tree is left alone.
+pub fn import_git(path: &str, map_file: &Option<String>, force: bool) -> Result<(), NyxError> {
+    let git = GitRepository::open(Path::new(path))?;
+    let mut importer = Importer::new(&git);
+    let message = format!("import-git: from {}", git.git_dir().display());
+
+    let mut updates = Vec::new();
+    for (git_ref, hash) in git.refs() {
+        let name = match nyx_ref_name(&git_ref) {
+            Some(name) => name,
+            None => continue,
+        };
+        if !refs::is_valid_ref_name(&name) {
+            return Err(NyxError::Usage(format!(
+                "invalid ref '{}' in {}",
+                git_ref, path
+            )));
+        }
+        let nyx_hash = importer.import(&hash)?;
+        check_update(&name, &nyx_hash, force)?;
+        updates.push((name, nyx_hash));
+    }
+
+    let mut branches = 0;
+    let mut tags = 0;
+    for (name, nyx_hash) in updates {
+        match refs::is_branch_name(&name) {
+            true => {
+                refs::update_ref(&name, &nyx_hash, &message)?;
+                branches += 1;
+            }
+            false => {
+                refs::write_ref(&name, &nyx_hash)?;
+                tags += 1;
+            }
+        }
+    }
+
+    let head_branch = git
+        .head_ref()
+        .and_then(|head| nyx_ref_name(&head))
+        .filter(|name| refs::is_branch_name(name) && refs::ref_exists(name));
+    if let (true, Some(branch)) = (refs::head_hash().is_empty(), head_branch) {
+        refs::set_head_to_branch(&branch)?;
+        let commit = resolve_commit(&branch)?;
+        refs::append_head_log("", commit.get_hash(), &message)?;
+        let paths = Tree::read_paths(Some(commit.tree_hash()));
+        Index::new().reset_to(&paths, NyxFileState::Committed);
+    }
+
+    let map_path = match map_file {
+        Some(map_file) => PathBuf::from(map_file),
+        None => FILE_SYSTEM.get_repo_dir().join(MAP_FILE),
+    };
+    let mut mapping: Vec<String> = importer
+        .mapping()
+        .iter()
+        .map(|(git_hash, nyx_hash)| format!("{} {}", git_hash, nyx_hash))
+        .collect();
+    mapping.sort();
+    fs::write(&map_path, mapping.join("\n") + "\n")?;
+
+    let counts: Vec<String> = importer
+        .counts()
+        .iter()
+        .map(|(object_type, count)| plural(*count, object_type, &format!("{}s", object_type)))
+        .collect();
+    for skipped in importer.skipped() {
+        eprintln!("warning: skipped {}", skipped);
+    }
+    println!(
+        "Imported {} from {}",
+        match counts.is_empty() {
+            true => String::from("no objects"),
+            false => counts.join(", "),
+        },
+        git.git_dir().display()
+    );
+    println!(
+        "Updated {} and {}",
+        plural(branches, "branch", "branches"),
+        plural(tags, "tag", "tags")
+    );
+    println!("Wrote the hash mapping to {}", map_path.display());
+    Ok(())
+}
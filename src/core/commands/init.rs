@@ -1,9 +1,24 @@
 use std::fs;
 
-use crate::{core::errors::NyxError, FILE_SYSTEM};
+use crate::core::errors::NyxError;
+use crate::core::object_format::{write_object_format, ObjectFormat};
+use crate::FILE_SYSTEM;
 
-pub fn init() -> Result<(), NyxError> {
+/// Parses the name of an object format, `None` stands for SHA-1.
+pub fn parse_object_format(name: &Option<String>) -> Result<ObjectFormat, NyxError> {
+    match name {
+        Some(name) => ObjectFormat::parse(name)
+            .ok_or_else(|| NyxError::Usage(format!("unknown hash algorithm '{}'", name))),
+        None => Ok(ObjectFormat::Sha1),
+    }
+}
+
+pub fn init(object_format: ObjectFormat) -> Result<(), NyxError> {
     fs::create_dir_all(FILE_SYSTEM.get_objects_dir_path())?;
     fs::create_dir_all(FILE_SYSTEM.get_refs_dir_path())?;
+    fs::create_dir_all(FILE_SYSTEM.get_hooks_dir_path())?;
+    if object_format != ObjectFormat::Sha1 {
+        write_object_format(object_format)?;
+    }
     Ok(())
 }
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+
+use crate::core::{
+    cl_args::LfsCommand,
+    errors::NyxError,
+    index::index::Index,
+    lfs::{
+        pointer::Pointer,
+        store,
+        track::{self, ATTRIBUTES_FILE},
+    },
+    reachability::{reachable_objects, root_hashes},
+    refs::refs,
+    remote::remote::Remote,
+    rev_walk::{walk, WalkOrder},
+    revision::resolve_commit,
+    tree::tree::Tree,
+    worktree::{working_path, write_working_blob},
+};
+
+fn display_path(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+fn large_files(count: usize) -> String {
+    match count {
+        1 => String::from("1 large file"),
+        _ => format!("{} large files", count),
+    }
+}
+
+fn track(patterns: &[String]) -> Result<(), NyxError> {
+    if patterns.is_empty() {
+        println!("Listing tracked patterns");
+        for pattern in track::tracked_patterns() {
+            println!("    {} ({})", pattern, ATTRIBUTES_FILE);
+        }
+        return Ok(());
+    }
+    for pattern in patterns {
+        match track::track(pattern)? {
+            true => println!("Tracking \"{}\"", pattern),
+            false => println!("\"{}\" already supported", pattern),
+        }
+    }
+    Ok(())
+}
+
+/// Lists the indexed large files, `*` marks those in the store and `-`
+/// those only present as pointer.
+fn ls_files() -> Result<(), NyxError> {
+    for entry in Index::new().entries() {
+        if let Some(pointer) = Pointer::read(&entry.hash) {
+            let marker = if store::contains(&pointer.oid) {
+                '*'
+            } else {
+                '-'
+            };
+            println!(
+                "{} {} {}",
+                &pointer.oid[..10],
+                marker,
+                display_path(&entry.path)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Pointers in the trees of the given commits by object id.
+fn commit_pointers(commits: &[String]) -> Result<BTreeMap<String, Pointer>, NyxError> {
+    let mut pointers = BTreeMap::new();
+    for commit in commits {
+        let commit = resolve_commit(commit)?;
+        for hash in Tree::read_paths(Some(commit.tree_hash())).values() {
+            if let Some(pointer) = Pointer::read(hash) {
+                pointers.insert(pointer.oid.clone(), pointer);
+            }
+        }
+    }
+    Ok(pointers)
+}
+
+/// Downloads the large files of the revisions, defaulting to HEAD, or of
+/// the whole history with `all`. Working tree files that are still
+/// pointers get their content afterwards.
+fn fetch(remote: &Option<String>, revisions: &[String], all: bool) -> Result<(), NyxError> {
+    let remote = Remote::load(&remote.clone().unwrap_or_else(Remote::default_name))?;
+    let transport = remote.transport()?;
+
+    let commits: Vec<String> = match (all, revisions.is_empty()) {
+        (true, _) => {
+            let tips: Vec<String> = refs::list_refs("")
+                .into_iter()
+                .map(|(_, hash)| hash)
+                .collect();
+            walk(&tips, &[], WalkOrder::Date)
+                .iter()
+                .map(|commit| commit.get_hash().to_string())
+                .collect()
+        }
+        (false, true) => vec![refs::HEAD.to_string()],
+        (false, false) => revisions.to_vec(),
+    };
+
+    let mut fetched = 0;
+    for (oid, pointer) in commit_pointers(&commits)? {
+        if store::contains(&oid) {
+            continue;
+        }
+        let mut object = transport.lfs_object(&oid)?;
+        store::store(&mut object, Some(&pointer))?;
+        fetched += 1;
+    }
+
+    let mut updated = 0;
+    for entry in Index::new().entries() {
+        let fetched_pointer = Pointer::read(&entry.hash).is_some_and(|p| store::contains(&p.oid));
+        if fetched_pointer && Pointer::from_file(&working_path(&entry.path)).is_some() {
+            write_working_blob(&entry.path, &entry.hash)?;
+            updated += 1;
+        }
+    }
+    println!("Fetched {} from {}", large_files(fetched), transport.url());
+    if updated > 0 {
+        println!("Updated {} in the working tree", large_files(updated));
+    }
+    Ok(())
+}
+
+/// Deletes large files that no reachable commit, the index or a reflog
+/// refers to.
+fn prune(dry_run: bool, verbose: bool) -> Result<(), NyxError> {
+    let referenced: HashSet<String> = reachable_objects(&root_hashes(true))
+        .iter()
+        .filter_map(|hash| Pointer::read(hash))
+        .map(|pointer| pointer.oid)
+        .collect();
+
+    let mut count = 0;
+    let mut bytes = 0;
+    for oid in store::list() {
+        if referenced.contains(&oid) {
+            continue;
+        }
+        let path = store::object_path(&oid);
+        bytes += fs::metadata(&path)?.len();
+        count += 1;
+        if verbose || dry_run {
+            println!("{}", oid);
+        }
+        if !dry_run {
+            fs::remove_file(&path)?;
+        }
+    }
+    let verb = if dry_run { "Would prune" } else { "Pruned" };
+    println!("{} {}, {} bytes", verb, large_files(count), bytes);
+    Ok(())
+}
+
+pub fn lfs(command: &LfsCommand) -> Result<(), NyxError> {
+    match command {
+        LfsCommand::Track { patterns } => track(patterns),
+        LfsCommand::LsFiles => ls_files(),
+        LfsCommand::Fetch {
+            remote,
+            revisions,
+            all,
+        } => fetch(remote, revisions, *all),
+        LfsCommand::Prune { dry_run, verbose } => prune(*dry_run, *verbose),
+    }
+}
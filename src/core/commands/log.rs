@@ -1,10 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::core::commit::Commit;
+use crate::core::date::parse_date;
+use crate::core::errors::NyxError;
+use crate::core::graph::Graph;
+use crate::core::pretty::CommitFormat;
+use crate::core::refs::refs;
+use crate::core::rev_walk::{walk, WalkOrder};
+use crate::core::revision::resolve;
+use crate::core::tree::tree::Tree;
+
+#[derive(Debug, Default)]
+pub struct LogOptions {
+    pub max_count: Option<usize>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub author: Option<String>,
+    pub grep: Option<String>,
+    pub format: Option<String>,
+    pub oneline: bool,
+    pub graph: bool,
+    pub topo_order: bool,
+    pub date_order: bool,
+    pub show_signature: bool,
+}
+
+/// Splits the revisions into included and excluded commits,
+/// `A..B` and `^A` exclude everything reachable from `A`.
+fn parse_revisions(revisions: &[String]) -> Result<(Vec<String>, Vec<String>), NyxError> {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+
+    for revision in revisions {
+        if let Some((from, to)) = revision.split_once("..") {
+            let or_head = |rev: &str| {
+                if rev.is_empty() {
+                    String::from("HEAD")
+                } else {
+                    rev.to_string()
+                }
+            };
+            exclude.push(resolve(&or_head(from))?);
+            include.push(resolve(&or_head(to))?);
+        } else if let Some(rev) = revision.strip_prefix('^') {
+            exclude.push(resolve(rev)?);
+        } else {
+            include.push(resolve(revision)?);
+        }
+    }
+
+    if include.is_empty() {
+        let head = refs::head_hash();
+        if head.is_empty() {
+            let branch =
+                refs::current_branch().unwrap_or_else(|| String::from(refs::DEFAULT_BRANCH));
+            return Err(NyxError::InvalidRevision(format!(
+                "your current branch '{}' does not have any commits yet",
+                branch
+            )));
+        }
+        include.push(head);
+    }
+    Ok((include, exclude))
+}
+
+fn touches_paths(commit: &Commit, paths: &[String]) -> bool {
+    let matches = |path: &String| {
+        paths.iter().any(|p| {
+            let p = p.trim_end_matches('/');
+            p.is_empty() || p == "." || path == p || path.starts_with(&format!("{}/", p))
+        })
+    };
+    let filtered = |hash: Option<&str>| -> Vec<(String, String)> {
+        Tree::read_paths(hash)
+            .into_iter()
+            .filter(|(path, _)| matches(path))
+            .collect()
+    };
+
+    let own = filtered(Some(commit.tree_hash()));
+    if commit.parent_hashes().is_empty() {
+        return !own.is_empty();
+    }
+    // A commit is skipped if its paths are identical to any parent's
+    !commit.parent_hashes().iter().any(|parent| {
+        let tree = Commit::from_hash(parent).map(|c| c.tree_hash().to_string());
+        filtered(tree.as_deref()) == own
+    })
+}
+
+fn parse_date_option(value: &Option<String>) -> Result<Option<i64>, NyxError> {
+    match value {
+        Some(value) => parse_date(value)
+            .map(Some)
+            .ok_or_else(|| NyxError::Usage(format!("invalid date '{}'", value))),
+        None => Ok(None),
+    }
+}
+
+/// Puts the outcome of checking the signature of a signed commit
+/// below the first line of its text.
+fn with_signature(commit: &Commit, text: String) -> Result<String, NyxError> {
+    let Some(verification) = commit.verify()? else {
+        return Ok(text);
+    };
+    Ok(match text.split_once('\n') {
+        Some((first, rest)) => format!("{}\n{}\n{}", first, verification, rest),
+        None => format!("{}\n{}", text, verification),
+    })
+}
+
+/// Parents of the walked commits as `--graph` draws them. Commits that
+/// are not shown are skipped for their nearest shown ancestors, parents
+/// outside of the walk end their line of history.
+fn graph_parents(walked: &[Commit], shown: &HashSet<&str>) -> HashMap<String, Vec<String>> {
+    let mut rewritten: HashMap<String, Vec<String>> = HashMap::new();
+    // Parents come after their children in topological order
+    for commit in walked.iter().rev() {
+        let mut parents: Vec<String> = Vec::new();
+        for parent in commit.parent_hashes() {
+            let targets = match shown.contains(parent.as_str()) {
+                true => vec![parent.clone()],
+                false => rewritten.get(parent).cloned().unwrap_or_default(),
+            };
+            for target in targets {
+                if !parents.contains(&target) {
+                    parents.push(target);
+                }
+            }
+        }
+        rewritten.insert(commit.get_hash().to_string(), parents);
+    }
+    rewritten
+}
+
+pub fn log(revisions: &[String], paths: &[String], options: LogOptions) -> Result<(), NyxError> {
+    let (include, exclude) = parse_revisions(revisions)?;
+    let order = if options.topo_order || options.graph {
+        WalkOrder::Topo
+    } else if options.date_order {
+        WalkOrder::DateTopo
+    } else {
+        WalkOrder::Date
+    };
+    let since = parse_date_option(&options.since)?;
+    let until = parse_date_option(&options.until)?;
+    let format = match (&options.format, options.oneline) {
+        (Some(format), _) => CommitFormat::parse(format),
+        (None, true) => CommitFormat::Oneline,
+        (None, false) => CommitFormat::Medium,
+    };
+
+    let walked = walk(&include, &exclude, order);
+    let matches = |commit: &Commit| {
+        let time = commit
+            .committer()
+            .or(commit.author())
+            .map(|s| s.time)
+            .unwrap_or(0);
+        let author = commit
+            .author()
+            .map(|a| format!("{} <{}>", a.name, a.email))
+            .unwrap_or_default();
+        since.is_none_or(|since| time >= since)
+            && until.is_none_or(|until| time <= until)
+            && options
+                .author
+                .as_ref()
+                .is_none_or(|a| author.contains(a.as_str()))
+            && options
+                .grep
+                .as_ref()
+                .is_none_or(|g| commit.message().contains(g.as_str()))
+            && (paths.is_empty() || touches_paths(commit, paths))
+    };
+    let max_count = options.max_count.unwrap_or(usize::MAX);
+    let commits = walked.iter().filter(|commit| matches(commit));
+    // The graph joins the lines of all matching commits, not only of
+    // the first `max_count`
+    let (commits, parents): (Vec<&Commit>, _) = match options.graph {
+        true => {
+            let commits: Vec<&Commit> = commits.collect();
+            let shown = commits.iter().map(|commit| commit.get_hash()).collect();
+            (commits, graph_parents(&walked, &shown))
+        }
+        false => (commits.take(max_count).collect(), HashMap::new()),
+    };
 
-pub fn log() {
-    let mut commit = Commit::from_head();
+    let mut graph = Graph::new();
+    let mut first = true;
+    for commit in commits.into_iter().take(max_count) {
+        if format.is_multiline() && !first {
+            match options.graph {
+                true => println!("{}", graph.padding()),
+                false => println!(),
+            }
+        }
+        first = false;
 
-    while let Some(c) = &commit {
-        println!("{}\n", c);
-        commit = Commit::from_hash(&c.get_parent_hash());
+        let mut text = format.format(commit);
+        if options.show_signature {
+            text = with_signature(commit, text)?;
+        }
+        if !options.graph {
+            println!("{}", text);
+            continue;
+        }
+        let (marker, transition) = graph.next(commit.get_hash(), &parents[commit.get_hash()]);
+        let mut lines = text.lines();
+        println!("{} {}", marker, lines.next().unwrap_or_default());
+        let padding = marker.replace('*', "|");
+        for line in lines {
+            println!("{}", format!("{} {}", padding, line).trim_end());
+        }
+        if let Some(transition) = transition {
+            println!("{}", transition);
+        }
     }
+    Ok(())
 }
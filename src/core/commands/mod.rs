@@ -1,12 +1,38 @@
 // ##### HIGH-LEVEL #####
 pub mod add;
+pub mod bisect;
+pub mod blame;
+pub mod bundle;
 pub mod checkout;
+pub mod cherry_pick;
+pub mod clone;
 pub mod commit;
+pub mod convert_objects;
+pub mod fast_export;
+pub mod fast_import;
+pub mod fetch;
+pub mod gc;
+pub mod import_git;
 pub mod init;
+pub mod lfs;
 pub mod log;
+pub mod push;
+pub mod rebase;
+pub mod remote;
+pub mod reset;
+pub mod restore;
+pub mod revert;
+pub mod serve;
+pub mod show;
+pub mod stash;
 pub mod status;
+pub mod tag;
 
 // ##### LOW-LEVEL #####
 pub mod cat_file;
+pub mod fsck;
 pub mod hash_object;
 pub mod ls_file;
+pub mod prune;
+pub mod verify_commit;
+pub mod verify_tag;
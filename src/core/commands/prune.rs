@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use crate::core::{
+    date::parse_date,
+    errors::NyxError,
+    odb::database::database,
+    reachability::{reachable_objects, root_hashes},
+    shared::{list_objects, read_object_type},
+};
+
+/// Objects removed by a prune and the bytes they occupied.
+pub struct PruneResult {
+    pub objects: usize,
+    pub bytes: u64,
+}
+
+impl PruneResult {
+    pub fn summary(&self, dry_run: bool) -> String {
+        format!(
+            "{} {} unreachable object{}, {} bytes",
+            if dry_run { "Would remove" } else { "Removed" },
+            self.objects,
+            if self.objects == 1 { "" } else { "s" },
+            self.bytes
+        )
+    }
+}
+
+/// Parses the expiry of `--expire`/`--prune`, `never` yields `None`.
+pub fn parse_expire(value: &str) -> Result<Option<i64>, NyxError> {
+    if value == "never" {
+        return Ok(None);
+    }
+    parse_date(value)
+        .map(Some)
+        .ok_or_else(|| NyxError::Usage(format!("invalid expiry date '{}'", value)))
+}
+
+/// Deletes objects that are neither reachable from refs, reflogs,
+/// the index or operations in progress and were written before
+/// `expire`, and the chunks only they were stored in. With `dry_run`
+/// nothing is deleted.
+pub fn prune_objects(expire: i64, dry_run: bool, verbose: bool) -> Result<PruneResult, NyxError> {
+    let database = database();
+    let reachable = reachable_objects(&root_hashes(true));
+    let mut removed = HashSet::new();
+
+    for hash in list_objects() {
+        if reachable.contains(&hash) {
+            continue;
+        }
+        if database.written_at(&hash)?.unwrap_or(0) > expire {
+            continue;
+        }
+
+        if verbose || dry_run {
+            let object_type = read_object_type(&hash)
+                .map(|t| t.to_string())
+                .unwrap_or_else(|_| String::from("unknown"));
+            println!("{} {}", hash, object_type);
+        }
+        removed.insert(hash);
+    }
+
+    let bytes = match dry_run {
+        true => database.removable_size(&removed)?,
+        false => database.remove(&removed)?,
+    };
+    Ok(PruneResult {
+        objects: removed.len(),
+        bytes,
+    })
+}
+
+pub fn prune(expire: &Option<String>, dry_run: bool, verbose: bool) -> Result<(), NyxError> {
+    let expire = match expire {
+        Some(expire) => parse_expire(expire)?,
+        None => Some(i64::MAX),
+    };
+    if let Some(expire) = expire {
+        let result = prune_objects(expire, dry_run, verbose)?;
+        if dry_run {
+            println!("{}", result.summary(dry_run));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use crate::core::{
+    errors::NyxError,
+    refs::refs,
+    remote::{remote::Remote, transfer::push_objects, transport::RefUpdate},
+    rev_walk::is_ancestor,
+    revision::resolve_commit,
+    sequencer::short_hash,
+    shared::object_exists,
+};
+
+/// Uploads a branch to a remote. The remote branch is only moved if the
+/// new commit contains it, unless `force` is given.
+pub fn push(
+    remote: &Option<String>,
+    refspec: &Option<String>,
+    force: bool,
+    set_upstream: bool,
+) -> Result<(), NyxError> {
+    let remote = Remote::load(&remote.clone().unwrap_or_else(Remote::default_name))?;
+    let refspec = match refspec {
+        Some(refspec) => refspec.clone(),
+        None => refs::current_branch()
+            .ok_or_else(|| NyxError::Usage(String::from("You are not currently on a branch.")))?,
+    };
+    let (force, refspec) = match refspec.strip_prefix('+') {
+        Some(refspec) => (true, refspec.to_string()),
+        None => (force, refspec),
+    };
+    let (source, destination) = match refspec.split_once(':') {
+        Some((source, destination)) => (source.to_string(), destination.to_string()),
+        None => (refspec.clone(), refspec.clone()),
+    };
+    if !refs::is_branch_name(&destination) {
+        return Err(NyxError::InvalidRef(destination));
+    }
+
+    let new = resolve_commit(&source)?.get_hash().to_string();
+    let transport = remote.transport()?;
+    let remote_refs = transport.list_refs()?;
+    let old = remote_refs
+        .iter()
+        .find(|(name, _)| *name == destination)
+        .map(|(_, hash)| hash.clone());
+
+    if old.as_deref() == Some(new.as_str()) {
+        println!("Everything up-to-date");
+        return Ok(());
+    }
+
+    let line = match &old {
+        None => format!(" * [new branch]      {} -> {}", source, destination),
+        Some(old) if !force && !object_exists(old) => {
+            println!("To {}", remote.url);
+            println!(
+                " ! [rejected]        {} -> {} (fetch first)",
+                source, destination
+            );
+            return Err(NyxError::Usage(format!(
+                "failed to push some refs to '{}', the remote contains work that you do not have locally",
+                remote.url
+            )));
+        }
+        Some(old) if is_ancestor(old, &new) => format!(
+            "   {}..{}  {} -> {}",
+            short_hash(old),
+            short_hash(&new),
+            source,
+            destination
+        ),
+        Some(old) if force => format!(
+            " + {}...{} {} -> {} (forced update)",
+            short_hash(old),
+            short_hash(&new),
+            source,
+            destination
+        ),
+        Some(_) => {
+            println!("To {}", remote.url);
+            println!(
+                " ! [rejected]        {} -> {} (non-fast-forward)",
+                source, destination
+            );
+            return Err(NyxError::Usage(format!(
+                "failed to push some refs to '{}', integrate the remote changes before pushing again",
+                remote.url
+            )));
+        }
+    };
+
+    let remote_tips: Vec<String> = remote_refs.into_iter().map(|(_, hash)| hash).collect();
+    let update = RefUpdate {
+        name: destination.clone(),
+        old,
+        new: new.clone(),
+    };
+    push_objects(transport.as_ref(), &remote_tips, &[update])?;
+    if let Some(tracking) = remote.tracking_ref(&destination) {
+        refs::update_ref(&tracking, &new, "update by push")?;
+    }
+    println!("To {}", remote.url);
+    println!("{}", line);
+
+    if set_upstream && refs::is_branch_name(&source) && refs::ref_exists(&source) {
+        remote.set_upstream(&source, &destination)?;
+        println!(
+            "branch '{}' set up to track '{}'.",
+            source,
+            remote.tracking_name(&destination)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::core::{errors::NyxError, rebase::rebase::Rebase};
+
+pub fn rebase(
+    upstream: &Option<String>,
+    onto: &Option<String>,
+    interactive: bool,
+    proceed: bool,
+    abort: bool,
+    skip: bool,
+) -> Result<(), NyxError> {
+    if proceed {
+        return Rebase::resume();
+    }
+    if abort {
+        return Rebase::abort();
+    }
+    if skip {
+        return Rebase::skip();
+    }
+    match upstream {
+        Some(upstream) => Rebase::start(upstream, onto.as_deref(), interactive),
+        None => Err(NyxError::Usage(String::from(
+            "no upstream given, usage: nyx rebase [-i] [--onto <newbase>] <upstream>",
+        ))),
+    }
+}
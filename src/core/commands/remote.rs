@@ -0,0 +1,29 @@
+use std::fs;
+
+use crate::core::{cl_args::RemoteCommand, errors::NyxError, remote::remote::Remote};
+
+pub fn remote(command: &Option<RemoteCommand>, verbose: bool) -> Result<(), NyxError> {
+    match command {
+        None => {
+            for remote in Remote::list() {
+                match verbose {
+                    true => {
+                        println!("{}\t{} (fetch)", remote.name, remote.url);
+                        println!("{}\t{} (push)", remote.name, remote.url);
+                    }
+                    false => println!("{}", remote.name),
+                }
+            }
+        }
+        Some(RemoteCommand::Add { name, url }) => {
+            // Paths are stored absolute so they work from any directory
+            let url = match fs::canonicalize(url) {
+                Ok(path) => path.to_string_lossy().to_string(),
+                Err(_) => url.clone(),
+            };
+            Remote::add(name, &url)?;
+        }
+        Some(RemoteCommand::Remove { name }) => Remote::remove(name)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::core::{
+    commit::Commit,
+    diff::{
+        select::{HunkSelector, PatchMode, Selection},
+        tree_diff::diff_maps,
+    },
+    errors::NyxError,
+    index::{file_state::NyxFileState, index::Index},
+    object_type::NyxObjectType,
+    pathspec::Pathspec,
+    shared::{generate_object, read_object_data},
+    tree::tree::Tree,
+    worktree::{unstaged_changes, working_path},
+};
+
+/// Resets the index entries of the paths to HEAD, or only the hunks of
+/// the staged changes the user picks with `patch`. The working tree is
+/// left as it is.
+pub fn reset(paths: &[String], patch: bool) -> Result<(), NyxError> {
+    let pathspec = Pathspec::parse(paths)?;
+    let head_paths = Tree::read_paths(Commit::from_head().as_ref().map(|c| c.tree_hash()));
+    let mut index = Index::new();
+    let changes: Vec<_> = diff_maps(&head_paths, &index.to_map())
+        .into_iter()
+        .filter(|change| pathspec.matches(&change.path))
+        .collect();
+    let mut selector = HunkSelector::new(PatchMode::Unstage);
+
+    for change in &changes {
+        if !patch {
+            match &change.old_hash {
+                Some(hash) => index.set_entry(&change.path, hash, NyxFileState::Committed),
+                None => index.remove_entry(&change.path),
+            }
+            continue;
+        }
+        let read = |hash: &Option<String>| hash.as_deref().map(read_object_data).transpose();
+        let (Ok(old), Ok(new)) = (read(&change.old_hash), read(&change.new_hash)) else {
+            println!("Skipping binary file {}", change.path);
+            continue;
+        };
+        match selector.select(&change.path, old.as_deref(), new.as_deref())? {
+            Selection::Content(content) => {
+                let hash = generate_object(content.as_bytes(), NyxObjectType::Blob);
+                let state = match change.old_hash.as_ref() == Some(&hash) {
+                    true => NyxFileState::Committed,
+                    false => NyxFileState::Staged,
+                };
+                index.set_entry(&change.path, &hash, state);
+            }
+            Selection::Removed => index.remove_entry(&change.path),
+            Selection::Unchanged => (),
+        }
+        if selector.has_quit() {
+            break;
+        }
+    }
+
+    if patch && changes.is_empty() {
+        println!("No changes.");
+        return Ok(());
+    }
+    index.write();
+    if !patch {
+        let unstaged = unstaged_changes(&index);
+        if !unstaged.is_empty() {
+            println!("Unstaged changes after reset:");
+        }
+        for path in unstaged {
+            let letter = match working_path(&path).exists() {
+                true => 'M',
+                false => 'D',
+            };
+            println!("{}\t{}", letter, path);
+        }
+    }
+    Ok(())
+}
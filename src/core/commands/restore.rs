@@ -0,0 +1,81 @@
+use std::fs;
+
+use crate::core::{
+    diff::select::{HunkSelector, PatchMode, Selection},
+    errors::NyxError,
+    index::{file_state::NyxFileState, index::Index},
+    lfs::track::is_tracked,
+    pathspec::Pathspec,
+    shared::read_object_data,
+    worktree::{
+        hash_working_file, remove_working_file, working_path, write_working_blob,
+        write_working_file,
+    },
+};
+
+/// Restores working tree files of the paths from the index, or only
+/// discards the hunks of the unstaged changes the user picks with
+/// `patch`.
+pub fn restore(paths: &[String], patch: bool) -> Result<(), NyxError> {
+    if paths.is_empty() && !patch {
+        return Err(NyxError::Usage(String::from(
+            "you must specify path(s) to restore",
+        )));
+    }
+    let pathspec = Pathspec::parse(paths)?;
+    let index = Index::new();
+    let indexed = index.entries().iter().map(|entry| entry.path.as_str());
+    if let Some(spec) = pathspec.unmatched(indexed).first() {
+        return Err(NyxError::Usage(format!(
+            "pathspec '{}' did not match any file(s) known to nyx",
+            spec
+        )));
+    }
+    let mut selector = HunkSelector::new(PatchMode::Discard);
+    let mut changed = false;
+
+    for entry in index.entries() {
+        if !pathspec.matches(&entry.path) {
+            continue;
+        }
+        if entry.state == NyxFileState::Unmerged {
+            eprintln!("error: path '{}' is unmerged", entry.path);
+            continue;
+        }
+        let working = hash_working_file(&entry.path);
+        if working.as_ref() == Some(&entry.hash) {
+            continue;
+        }
+        changed = true;
+        if !patch {
+            write_working_blob(&entry.path, &entry.hash)?;
+            continue;
+        }
+        // Large files can only be restored as a whole
+        if is_tracked(&entry.path) {
+            continue;
+        }
+
+        let old = read_object_data(&entry.hash);
+        let new = working
+            .map(|_| fs::read_to_string(working_path(&entry.path)))
+            .transpose();
+        let (Ok(old), Ok(new)) = (old, new) else {
+            println!("Skipping binary file {}", entry.path);
+            continue;
+        };
+        match selector.select(&entry.path, Some(&old), new.as_deref())? {
+            Selection::Content(content) => write_working_file(&entry.path, content.as_bytes())?,
+            Selection::Removed => remove_working_file(&entry.path)?,
+            Selection::Unchanged => (),
+        }
+        if selector.has_quit() {
+            break;
+        }
+    }
+
+    if patch && !changed {
+        println!("No changes.");
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+
+use tiny_http::{Header, Method, Request, Response, ResponseBox, Server};
+
+use crate::core::{
+    errors::NyxError,
+    remote::{
+        http::{
+            encode_advertisement, parse_negotiation, parse_push, INFO_REFS, LFS_OBJECTS,
+            PACK_CONTENT_TYPE, RECEIVE_PACK, UPLOAD_PACK,
+        },
+        local::LocalTransport,
+        transport::Transport,
+    },
+};
+use crate::FILE_SYSTEM;
+
+fn respond(status: u16, content_type: &str, body: Vec<u8>) -> Response<Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    Response::from_data(body)
+        .with_status_code(status)
+        .with_header(header)
+}
+
+fn text(status: u16, message: &str) -> Response<Cursor<Vec<u8>>> {
+    respond(status, "text/plain", format!("{}\n", message).into_bytes())
+}
+
+fn handle(transport: &LocalTransport, request: &mut Request) -> Result<ResponseBox, NyxError> {
+    let mut body = Vec::new();
+    request.as_reader().read_to_end(&mut body)?;
+
+    let path = request.url().split('?').next().unwrap_or_default();
+    if let (Method::Get, Some(oid)) = (request.method(), path.strip_prefix(LFS_OBJECTS)) {
+        let object = transport.lfs_object(oid)?;
+        return Ok(Response::new(200.into(), Vec::new(), object, None, None).boxed());
+    }
+    Ok(match (request.method(), path) {
+        (Method::Get, INFO_REFS) => {
+            let advertisement =
+                encode_advertisement(&transport.list_refs()?, &transport.head_branch()?);
+            respond(200, "text/plain", advertisement.into_bytes())
+        }
+        (Method::Post, UPLOAD_PACK) => {
+            let (wants, haves) = parse_negotiation(&String::from_utf8(body)?);
+            respond(
+                200,
+                PACK_CONTENT_TYPE,
+                transport.fetch_pack(&wants, &haves)?,
+            )
+        }
+        (Method::Post, RECEIVE_PACK) => {
+            let (updates, pack) = parse_push(&body)?;
+            transport.send_pack(pack, &updates)?;
+            for update in updates {
+                println!("{} -> {}", update.name, update.new);
+            }
+            text(200, "ok")
+        }
+        (_, INFO_REFS) | (_, UPLOAD_PACK) | (_, RECEIVE_PACK) => text(405, "method not allowed"),
+        _ => text(404, "not found"),
+    }
+    .boxed())
+}
+
+/// Serves the repository over HTTP so that others can clone, fetch and
+/// push with `http://<address>:<port>` as URL. Requests are handled one
+/// after another, so concurrent pushes cannot interleave. A request that
+/// makes its handler panic is answered with an error, the server keeps
+/// running.
+pub fn serve(address: &str, port: u16) -> Result<(), NyxError> {
+    let repo_dir = FILE_SYSTEM.get_repo_dir();
+    let transport = LocalTransport::new(&repo_dir.to_string_lossy())?;
+    let server = Server::http((address, port)).map_err(|err| {
+        NyxError::Usage(format!("unable to listen on {}:{}: {}", address, port, err))
+    })?;
+    println!(
+        "Serving {} on http://{}:{}",
+        FILE_SYSTEM.get_root_dir().display(),
+        address,
+        port
+    );
+
+    for mut request in server.incoming_requests() {
+        let response =
+            match panic::catch_unwind(AssertUnwindSafe(|| handle(&transport, &mut request))) {
+                Ok(Ok(response)) => response,
+                Ok(Err(err)) => {
+                    eprintln!("{} {}: {}", request.method(), request.url(), err);
+                    text(400, &err.to_string()).boxed()
+                }
+                Err(_) => {
+                    eprintln!("{} {}: request failed", request.method(), request.url());
+                    text(500, "internal error").boxed()
+                }
+            };
+        if let Err(err) = request.respond(response) {
+            eprintln!("error: {}", err);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::io;
+
+use colored::Colorize;
+
+use crate::core::{
+    commit::Commit,
+    date::format_date,
+    diff::{tree_diff::diff_trees, unified::colorize},
+    errors::NyxError,
+    object_type::NyxObjectType,
+    odb::stream,
+    refs::refs,
+    revision::resolve,
+    shared::{open_object, read_object_data, read_object_type},
+    tag::Tag,
+};
+
+pub fn show(revs: &[String]) -> Result<(), NyxError> {
+    if revs.is_empty() {
+        return show_object(&resolve(refs::HEAD)?, refs::HEAD);
+    }
+    for rev in revs {
+        show_object(&resolve(rev)?, rev)?;
+    }
+    Ok(())
+}
+
+fn show_object(hash: &str, rev: &str) -> Result<(), NyxError> {
+    match read_object_type(hash)? {
+        NyxObjectType::Commit => show_commit(&Commit::read(hash)?),
+        NyxObjectType::Tree => show_tree(hash, rev),
+        NyxObjectType::Blob => {
+            let mut blob = open_object(hash)?;
+            stream::copy(&mut blob, &mut io::stdout().lock())?;
+            Ok(())
+        }
+        NyxObjectType::Tag => show_tag(&Tag::read(hash)?),
+    }
+}
+
+/// Prints the commit followed by its changes against the first parent.
+fn show_commit(commit: &Commit) -> Result<(), NyxError> {
+    println!("{}", commit);
+
+    let parent_tree =
+        Commit::from_hash(commit.get_parent_hash()).map(|p| p.tree_hash().to_string());
+    let changes = diff_trees(parent_tree.as_deref(), Some(commit.tree_hash()));
+    if !changes.is_empty() {
+        println!();
+    }
+    for change in changes {
+        print!("{}", colorize(&change.patch()));
+    }
+    Ok(())
+}
+
+/// Lists the entries of a tree, directories with a trailing slash.
+fn show_tree(hash: &str, rev: &str) -> Result<(), NyxError> {
+    println!("{} {}\n", "tree".yellow(), rev.yellow());
+    for line in read_object_data(hash)?.lines() {
+        let line: Vec<&str> = line.split_whitespace().collect();
+        match line.as_slice() {
+            ["tree", _, name] => println!("{}/", name),
+            [_, _, name] => println!("{}", name),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn show_tag(tag: &Tag) -> Result<(), NyxError> {
+    println!("{} {}", "tag".yellow(), tag.name().yellow());
+    if let Some(tagger) = tag.tagger() {
+        println!("Tagger: {} <{}>", tagger.name, tagger.email);
+        println!("Date:   {}", format_date(tagger.time, &tagger.offset));
+    }
+    if !tag.message().is_empty() {
+        println!("\n{}", tag.message());
+    }
+    println!();
+    show_object(tag.object(), tag.object())
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use colored::Colorize;
+
+use crate::core::{
+    cl_args::StashCommand,
+    commit::Commit,
+    diff::{tree_diff::diff_trees, unified::colorize},
+    errors::NyxError,
+    index::{file_state::NyxFileState, index::Index},
+    merge::tree_merge::merge_trees,
+    refs::{reflog::Reflog, refs},
+    revision::resolve_commit,
+    tree::tree::Tree,
+    worktree::{
+        apply_tree_merge, snapshot_tracked_files, staged_changes, unstaged_changes,
+        update_working_tree,
+    },
+};
+
+const STASH_REF: &str = "stash";
+
+pub fn stash(command: &Option<StashCommand>) -> Result<(), NyxError> {
+    match command {
+        None => push(&None, false),
+        Some(StashCommand::Push {
+            message,
+            keep_index,
+        }) => push(message, *keep_index),
+        Some(StashCommand::List) => list(),
+        Some(StashCommand::Show { stash, patch }) => show(stash, *patch),
+        Some(StashCommand::Apply { stash, index }) => apply(stash, *index),
+        Some(StashCommand::Pop { stash, index }) => {
+            apply(stash, *index)?;
+            drop_entry(stash)
+        }
+        Some(StashCommand::Drop { stash }) => drop_entry(stash),
+    }
+}
+
+fn push(message: &Option<String>, keep_index: bool) -> Result<(), NyxError> {
+    let head = Commit::from_head()
+        .ok_or_else(|| NyxError::Usage(String::from("You do not have the initial commit yet")))?;
+    let head_paths = Tree::read_paths(Some(head.tree_hash()));
+    let mut index = Index::new();
+
+    if staged_changes(&index, &head_paths).is_empty() && unstaged_changes(&index).is_empty() {
+        println!("No local changes to save");
+        return Ok(());
+    }
+
+    let branch = refs::current_branch().unwrap_or_else(|| String::from("(no branch)"));
+    let head_description = format!("{} {}", &head.get_hash()[..7], head.summary());
+
+    // The index state is a commit on top of HEAD ...
+    let index_tree = index.to_tree();
+    let mut index_commit = Commit::with_parents(
+        &index_tree.hash,
+        vec![head.get_hash().to_string()],
+        &format!("index on {}: {}", branch, head_description),
+    );
+    index_commit.write_object();
+
+    // ... and the working tree state a commit with HEAD and the index commit as parents
+    let working_paths = snapshot_tracked_files(&index);
+    let working_tree = Index::write_tree_from_map(&working_paths);
+    let stash_message = match message {
+        Some(message) => format!("On {}: {}", branch, message),
+        None => format!("WIP on {}: {}", branch, head_description),
+    };
+    let mut working_commit = Commit::with_parents(
+        &working_tree.hash,
+        vec![
+            head.get_hash().to_string(),
+            index_commit.get_hash().to_string(),
+        ],
+        &stash_message,
+    );
+    working_commit.write_object();
+    refs::update_ref(STASH_REF, working_commit.get_hash(), &stash_message)?;
+
+    if keep_index {
+        update_working_tree(&working_paths, &index.to_map())?;
+    } else {
+        update_working_tree(&working_paths, &head_paths)?;
+        index.reset_to(&head_paths, NyxFileState::Committed);
+    }
+
+    println!("Saved working directory and index state {}", stash_message);
+    Ok(())
+}
+
+fn list() -> Result<(), NyxError> {
+    let reflog = Reflog::new(STASH_REF);
+    for (n, entry) in reflog.newest_first().iter().enumerate() {
+        println!("{}: {}", format!("stash@{{{}}}", n).yellow(), entry.message);
+    }
+    Ok(())
+}
+
+fn show(stash: &Option<String>, patch: bool) -> Result<(), NyxError> {
+    let stash_commit = read_stash(stash)?;
+    let base = Commit::read(stash_commit.get_parent_hash())?;
+
+    for change in diff_trees(Some(base.tree_hash()), Some(stash_commit.tree_hash())) {
+        if patch {
+            print!("{}", colorize(&change.patch()));
+        } else {
+            println!("{}\t{}", change.status_letter(), change.path);
+        }
+    }
+    Ok(())
+}
+
+fn apply(stash: &Option<String>, restore_index: bool) -> Result<(), NyxError> {
+    let stash_commit = read_stash(stash)?;
+    let parents = stash_commit.parent_hashes();
+    if parents.len() < 2 {
+        return Err(NyxError::Usage(format!(
+            "{} is not a stash commit",
+            stash_commit.get_hash()
+        )));
+    }
+    let base_paths = Tree::read_paths(Some(Commit::read(&parents[0])?.tree_hash()));
+    let stashed_index_paths = Tree::read_paths(Some(Commit::read(&parents[1])?.tree_hash()));
+    let stashed_paths = Tree::read_paths(Some(stash_commit.tree_hash()));
+    let head_paths = match Commit::from_head() {
+        Some(head) => Tree::read_paths(Some(head.tree_hash())),
+        None => BTreeMap::new(),
+    };
+
+    let mut index = Index::new();
+    let previous_entries = index.entries().clone();
+    let our_paths = index.to_map();
+
+    let merge = merge_trees(
+        &base_paths,
+        &our_paths,
+        &stashed_paths,
+        "Updated upstream",
+        "Stashed changes",
+    )?;
+    let merged_paths = merge.to_map();
+
+    // Refuse to overwrite changes that are not staged
+    let touched: Vec<String> = unstaged_changes(&index)
+        .into_iter()
+        .filter(|path| {
+            merged_paths.get(path) != our_paths.get(path) || merge.conflicts().contains(path)
+        })
+        .collect();
+    if !touched.is_empty() {
+        return Err(NyxError::LocalChanges(touched));
+    }
+
+    let index_merge = if restore_index {
+        let index_merge = merge_trees(
+            &base_paths,
+            &our_paths,
+            &stashed_index_paths,
+            "Updated upstream",
+            "Stashed changes",
+        )?;
+        if !index_merge.is_clean() {
+            return Err(NyxError::Usage(String::from(
+                "Conflicts in index. Try without --index.",
+            )));
+        }
+        Some(index_merge.to_map())
+    } else {
+        None
+    };
+
+    apply_tree_merge(&merge, &our_paths, &head_paths, &mut index)?;
+
+    // Only the stashed index is staged again, everything else becomes
+    // an unstaged change. New files stay staged so they are not lost.
+    let conflicts = merge.conflicts();
+    for previous in &previous_entries {
+        if conflicts.contains(&previous.path) || !merged_paths.contains_key(&previous.path) {
+            continue;
+        }
+        let staged_hash = index_merge
+            .as_ref()
+            .and_then(|paths| paths.get(&previous.path));
+        match staged_hash {
+            Some(hash) if *hash != previous.hash => {
+                index.set_entry(&previous.path, hash, NyxFileState::Staged)
+            }
+            _ => index.set_entry(&previous.path, &previous.hash, previous.state),
+        }
+    }
+    index.write();
+
+    if !conflicts.is_empty() {
+        for path in &conflicts {
+            println!("CONFLICT (content): Merge conflict in {}", path);
+        }
+        return Err(NyxError::MergeConflict(conflicts));
+    }
+    Ok(())
+}
+
+fn drop_entry(stash: &Option<String>) -> Result<(), NyxError> {
+    let n = stash_number(stash)?;
+    let mut reflog = Reflog::new(STASH_REF);
+    let removed = reflog
+        .remove(n)
+        .ok_or_else(|| NyxError::InvalidRevision(format!("stash@{{{}}}", n)))?;
+    reflog.write()?;
+
+    match reflog.get(0) {
+        Some(newest) => refs::write_ref(STASH_REF, &newest.new_hash)?,
+        None => refs::delete_ref(STASH_REF)?,
+    }
+    println!("Dropped stash@{{{}}} ({})", n, removed.new_hash);
+    Ok(())
+}
+
+/// Accepts `stash@{<n>}` or just `<n>`, defaulting to the newest stash.
+fn stash_number(stash: &Option<String>) -> Result<usize, NyxError> {
+    let stash = match stash {
+        Some(stash) => stash,
+        None => return Ok(0),
+    };
+    let number = stash
+        .strip_prefix("stash@{")
+        .and_then(|s| s.strip_suffix('}'))
+        .unwrap_or(stash);
+    number
+        .parse()
+        .map_err(|_| NyxError::InvalidRevision(stash.to_string()))
+}
+
+fn read_stash(stash: &Option<String>) -> Result<Commit, NyxError> {
+    let n = stash_number(stash)?;
+    if Reflog::new(STASH_REF).is_empty() {
+        return Err(NyxError::Usage(String::from("No stash entries found.")));
+    }
+    resolve_commit(&format!("stash@{{{}}}", n))
+}
@@ -1,72 +1,415 @@
-use std::{fs, path::PathBuf};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use colored::Colorize;
 
 use crate::{
     core::{
+        commit::Commit,
         display_strings::DisplayStrings,
+        errors::NyxError,
         index::{file_state::NyxFileState, index::Index},
-        object_type::NyxObjectType,
-        shared::{append_object_header, calculate_sha1},
+        pathspec::Pathspec,
+        refs::refs,
+        remote::remote::Remote,
+        rev_walk::ahead_behind,
+        sequencer::short_hash,
+        tree::tree::Tree,
+        worktree::hash_working_file,
     },
     FILE_SYSTEM,
 };
 
-pub fn status() {
-    // TODO: Error: Empty file is not displayed as untracked
-    let root_dir = FILE_SYSTEM.get_root_dir();
-    let index = Index::new();
-    let mut unstaged = DisplayStrings::new(4, "red");
-    let mut modified = DisplayStrings::new(4, "red");
-    let mut staged = DisplayStrings::new(4, "green");
-    _status(
-        &root_dir,
-        &root_dir,
-        &index,
-        &mut unstaged,
-        &mut modified,
-        &mut staged,
-    );
-
-    if staged.is_empty() && modified.is_empty() && unstaged.is_empty() {
-        println!("Nothing to commit, working tree clean");
+const FILE_MODE: &str = "100644";
+const NO_MODE: &str = "000000";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Long,
+    Short,
+    PorcelainV1,
+    PorcelainV2,
+}
+
+/// A tracked path whose version in HEAD, the index or the working tree
+/// differs. `staged` and `unstaged` are the letters of the `XY` code.
+struct Change {
+    path: String,
+    staged: char,
+    unstaged: char,
+    head: Option<String>,
+    index: Option<String>,
+    in_worktree: bool,
+}
+
+impl Change {
+    fn is_unmerged(&self) -> bool {
+        self.staged == 'U'
     }
-    staged.try_print_with_prefix("Changes to be committed:");
-    modified.try_print_with_prefix("Files not staged for commit:");
-    unstaged.try_print_with_prefix("Untracked files:");
+
+    fn label(letter: char) -> &'static str {
+        match letter {
+            'A' => "new file:",
+            'D' => "deleted:",
+            _ => "modified:",
+        }
+    }
+}
+
+/// The current branch and how it relates to its upstream.
+struct BranchInfo {
+    head: Option<String>,
+    branch: Option<String>,
+    upstream: Option<String>,
+    /// Commits only on the branch and only on the upstream, `None` if
+    /// the upstream ref is gone
+    ahead_behind: Option<(usize, usize)>,
 }
 
-fn _status(
-    fixed_root: &PathBuf,
-    root: &PathBuf,
-    index: &Index,
-    unstaged: &mut DisplayStrings,
-    modified: &mut DisplayStrings,
-    staged: &mut DisplayStrings,
-) {
-    for path in fs::read_dir(root).unwrap() {
-        let path = &path.unwrap().path();
-        if FILE_SYSTEM.is_ignored(&path) {
+impl BranchInfo {
+    fn read() -> Self {
+        let head = Some(refs::head_hash()).filter(|hash| !hash.is_empty());
+        let branch = refs::current_branch();
+        let mut info = Self {
+            head,
+            branch,
+            upstream: None,
+            ahead_behind: None,
+        };
+        let Some((remote, remote_branch)) = info.branch.as_deref().and_then(Remote::upstream)
+        else {
+            return info;
+        };
+        info.upstream = Some(remote.tracking_name(&remote_branch));
+        let upstream_hash = remote
+            .tracking_ref(&remote_branch)
+            .and_then(|name| refs::read_ref(&name));
+        if let Some(upstream_hash) = upstream_hash {
+            let head = info.head.clone().unwrap_or_default();
+            info.ahead_behind = Some(ahead_behind(&head, &upstream_hash));
+        }
+        info
+    }
+
+    /// The `## branch...upstream [ahead 1, behind 2]` line of the short
+    /// format without the `## `.
+    fn short_header(&self) -> String {
+        let Some(branch) = &self.branch else {
+            return String::from("HEAD (no branch)");
+        };
+        if self.head.is_none() {
+            return format!("No commits yet on {}", branch);
+        }
+        let Some(upstream) = &self.upstream else {
+            return branch.to_string();
+        };
+        let tracking = match self.ahead_behind {
+            None => String::from(" [gone]"),
+            Some((0, 0)) => String::new(),
+            Some((ahead, 0)) => format!(" [ahead {}]", ahead),
+            Some((0, behind)) => format!(" [behind {}]", behind),
+            Some((ahead, behind)) => format!(" [ahead {}, behind {}]", ahead, behind),
+        };
+        format!("{}...{}{}", branch, upstream, tracking)
+    }
+
+    fn print_long(&self) {
+        match (&self.branch, &self.head) {
+            (Some(branch), _) => println!("On branch {}", branch),
+            (None, Some(head)) => {
+                println!("{}", format!("HEAD detached at {}", short_hash(head)).red())
+            }
+            (None, None) => (),
+        }
+        if let Some(upstream) = &self.upstream {
+            let plural = |count: usize| match count {
+                1 => String::from("1 commit"),
+                _ => format!("{} commits", count),
+            };
+            match self.ahead_behind {
+                None => println!(
+                    "Your branch is based on '{}', but the upstream is gone.",
+                    upstream
+                ),
+                Some((0, 0)) => println!("Your branch is up to date with '{}'.", upstream),
+                Some((ahead, 0)) => {
+                    println!("Your branch is ahead of '{}' by {}.", upstream, plural(ahead))
+                }
+                Some((0, behind)) => println!(
+                    "Your branch is behind '{}' by {}, and can be fast-forwarded.",
+                    upstream,
+                    plural(behind)
+                ),
+                Some((ahead, behind)) => println!(
+                    "Your branch and '{}' have diverged,\nand have {} and {} different commits each, respectively.",
+                    upstream, ahead, behind
+                ),
+            }
+        }
+        if self.head.is_none() {
+            println!("\nNo commits yet");
+        }
+        println!();
+    }
+
+    fn porcelain_v2_headers(&self) -> Vec<String> {
+        let mut headers = vec![
+            format!(
+                "# branch.oid {}",
+                self.head.as_deref().unwrap_or("(initial)")
+            ),
+            format!(
+                "# branch.head {}",
+                self.branch.as_deref().unwrap_or("(detached)")
+            ),
+        ];
+        if let Some(upstream) = &self.upstream {
+            headers.push(format!("# branch.upstream {}", upstream));
+            if let Some((ahead, behind)) = self.ahead_behind {
+                headers.push(format!("# branch.ab +{} -{}", ahead, behind));
+            }
+        }
+        headers
+    }
+}
+
+/// Untracked files below `dir` that are not ignored, relative to the
+/// repository root.
+fn collect_untracked(
+    dir: &Path,
+    prefix: &str,
+    tracked: &BTreeSet<&str>,
+    untracked: &mut Vec<String>,
+) -> Result<(), NyxError> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    for path in paths {
+        if path == FILE_SYSTEM.get_repo_dir() || FILE_SYSTEM.is_ignored(&path) {
             continue;
         }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let relative = format!("{}{}", prefix, name);
         if path.is_dir() {
-            _status(
-                fixed_root,
-                &root.join(path),
-                index,
-                unstaged,
-                modified,
-                staged,
-            );
-        } else {
-            let content = fs::read_to_string(path).unwrap();
-            let content = append_object_header(content.as_bytes(), NyxObjectType::Blob);
-            let hash = calculate_sha1(&content);
-            let path_str = path.strip_prefix(fixed_root).unwrap().to_str().unwrap();
-            match index.get_status(&hash, path_str) {
-                NyxFileState::Staged => staged.push(path_str),
-                NyxFileState::Modified => modified.push(path_str),
-                NyxFileState::Unstaged => unstaged.push(path_str),
-                _ => (),
+            collect_untracked(&path, &format!("{}/", relative), tracked, untracked)?;
+        } else if !tracked.contains(relative.as_str()) {
+            untracked.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Compares HEAD, the index and the working tree path by path.
+fn collect_changes(pathspec: &Pathspec) -> Result<(Vec<Change>, Vec<String>), NyxError> {
+    let relative = |path: &str| path.strip_prefix("./").unwrap_or(path).to_string();
+    let head_tree = Commit::from_head().map(|commit| commit.tree_hash().to_string());
+    let head: BTreeMap<String, String> = Tree::read_paths(head_tree.as_deref())
+        .into_iter()
+        .map(|(path, hash)| (relative(&path), hash))
+        .collect();
+    let index = Index::new();
+    let entries: BTreeMap<String, (&str, &str, NyxFileState)> = index
+        .entries()
+        .iter()
+        .map(|e| {
+            (
+                relative(&e.path),
+                (e.path.as_str(), e.hash.as_str(), e.state),
+            )
+        })
+        .collect();
+
+    let paths: BTreeSet<&String> = head.keys().chain(entries.keys()).collect();
+    let mut changes = Vec::new();
+    for path in paths {
+        if !pathspec.matches(path) {
+            continue;
+        }
+        let head_hash = head.get(path);
+        let entry = entries.get(path);
+        let working = entry.and_then(|(index_path, _, _)| hash_working_file(index_path));
+        let (staged, unstaged) = match entry {
+            Some((_, _, NyxFileState::Unmerged)) => ('U', 'U'),
+            Some((_, hash, _)) => {
+                let staged = match head_hash {
+                    None => 'A',
+                    Some(head_hash) if head_hash != hash => 'M',
+                    Some(_) => ' ',
+                };
+                let unstaged = match &working {
+                    None => 'D',
+                    Some(working) if working != hash => 'M',
+                    Some(_) => ' ',
+                };
+                (staged, unstaged)
+            }
+            None => ('D', ' '),
+        };
+        if staged == ' ' && unstaged == ' ' {
+            continue;
+        }
+        changes.push(Change {
+            path: path.clone(),
+            staged,
+            unstaged,
+            head: head_hash.cloned(),
+            index: entry.map(|(_, hash, _)| hash.to_string()),
+            in_worktree: working.is_some(),
+        });
+    }
+
+    let tracked: BTreeSet<&str> = entries.keys().map(String::as_str).collect();
+    let mut untracked = Vec::new();
+    collect_untracked(FILE_SYSTEM.get_root_dir(), "", &tracked, &mut untracked)?;
+    untracked.retain(|path| pathspec.matches(path));
+    Ok((changes, untracked))
+}
+
+fn parse_format(short: bool, porcelain: &Option<String>, null: bool) -> Result<Format, NyxError> {
+    match porcelain.as_deref() {
+        Some("v1" | "1") => Ok(Format::PorcelainV1),
+        Some("v2" | "2") => Ok(Format::PorcelainV2),
+        Some(version) => Err(NyxError::Usage(format!(
+            "unsupported porcelain version '{}'",
+            version
+        ))),
+        None if short => Ok(Format::Short),
+        None if null => Ok(Format::PorcelainV1),
+        None => Ok(Format::Long),
+    }
+}
+
+/// Shows the changes of the paths selected by the pathspec: staged and
+/// unstaged changes, unmerged and untracked paths. `short` and
+/// `porcelain` print one `XY path` line per path, `branch` adds a line
+/// about the branch and its upstream, `null` ends lines with NUL.
+pub fn status(
+    paths: &[String],
+    short: bool,
+    branch: bool,
+    porcelain: &Option<String>,
+    null: bool,
+) -> Result<(), NyxError> {
+    let format = parse_format(short, porcelain, null)?;
+    let pathspec = Pathspec::parse(paths)?;
+    let (changes, untracked) = collect_changes(&pathspec)?;
+    let info = BranchInfo::read();
+    let terminator = match null {
+        true => '\0',
+        false => '\n',
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    match format {
+        Format::Long => {
+            print_long(&info, &changes, &untracked);
+            return Ok(());
+        }
+        Format::Short | Format::PorcelainV1 => {
+            if branch {
+                lines.push(format!("## {}", info.short_header()));
+            }
+            let colored = format == Format::Short && !null;
+            for change in &changes {
+                let (staged, unstaged) = (change.staged.to_string(), change.unstaged.to_string());
+                lines.push(match colored {
+                    true => format!("{}{} {}", staged.green(), unstaged.red(), change.path),
+                    false => format!("{}{} {}", staged, unstaged, change.path),
+                });
+            }
+            for path in &untracked {
+                lines.push(match colored {
+                    true => format!("{} {}", "??".red(), path),
+                    false => format!("?? {}", path),
+                });
+            }
+        }
+        Format::PorcelainV2 => {
+            if branch {
+                lines.extend(info.porcelain_v2_headers());
             }
+            let zero = refs::zero_hash();
+            let mode = |present: bool| match present {
+                true => FILE_MODE,
+                false => NO_MODE,
+            };
+            for change in &changes {
+                let code = format!("{}{}", change.staged, change.unstaged).replace(' ', ".");
+                let index = change.index.as_deref().unwrap_or(&zero);
+                lines.push(match change.is_unmerged() {
+                    // Only one side of a conflict is kept in the index
+                    true => format!(
+                        "u {} N... {} {} {} {} {} {} {} {}",
+                        code,
+                        NO_MODE,
+                        FILE_MODE,
+                        NO_MODE,
+                        mode(change.in_worktree),
+                        zero,
+                        index,
+                        zero,
+                        change.path
+                    ),
+                    false => format!(
+                        "1 {} N... {} {} {} {} {} {}",
+                        code,
+                        mode(change.head.is_some()),
+                        mode(change.index.is_some()),
+                        mode(change.in_worktree),
+                        change.head.as_deref().unwrap_or(&zero),
+                        index,
+                        change.path
+                    ),
+                });
+            }
+            for path in &untracked {
+                lines.push(format!("? {}", path));
+            }
+        }
+    }
+    for line in lines {
+        print!("{}{}", line, terminator);
+    }
+    Ok(())
+}
+
+fn print_long(info: &BranchInfo, changes: &[Change], untracked: &[String]) {
+    info.print_long();
+    let mut unmerged = DisplayStrings::new(4, "red");
+    let mut staged = DisplayStrings::new(4, "green");
+    let mut modified = DisplayStrings::new(4, "red");
+    let mut unstaged = DisplayStrings::new(4, "red");
+    for change in changes {
+        if change.is_unmerged() {
+            unmerged.push(&format!("{:<17}{}", "both modified:", change.path));
+            continue;
+        }
+        if change.staged != ' ' {
+            staged.push(&format!(
+                "{:<12}{}",
+                Change::label(change.staged),
+                change.path
+            ));
+        }
+        if change.unstaged != ' ' {
+            modified.push(&format!(
+                "{:<12}{}",
+                Change::label(change.unstaged),
+                change.path
+            ));
         }
     }
+    for path in untracked {
+        unstaged.push(path);
+    }
+
+    if staged.is_empty() && modified.is_empty() && unstaged.is_empty() && unmerged.is_empty() {
+        println!("Nothing to commit, working tree clean");
+    }
+    unmerged.try_print_with_prefix("Unmerged paths:");
+    staged.try_print_with_prefix("Changes to be committed:");
+    modified.try_print_with_prefix("Files not staged for commit:");
+    unstaged.try_print_with_prefix("Untracked files:");
 }
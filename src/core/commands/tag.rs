@@ -0,0 +1,90 @@
+use std::fs;
+
+use crate::core::{
+    editor::{edit_file, strip_comments},
+    errors::NyxError,
+    refs::refs,
+    revision::resolve,
+    sequencer::short_hash,
+    shared::read_object_type,
+    tag::Tag,
+};
+use crate::FILE_SYSTEM;
+
+const TAGS_PREFIX: &str = "tags";
+
+pub fn tag(
+    name: &Option<String>,
+    rev: &Option<String>,
+    annotate: bool,
+    message: &Option<String>,
+    delete: bool,
+    force: bool,
+    sign: bool,
+) -> Result<(), NyxError> {
+    let name = match name {
+        Some(name) => name,
+        None => {
+            list();
+            return Ok(());
+        }
+    };
+    let ref_name = format!("{}/{}", TAGS_PREFIX, name);
+    if !refs::is_valid_ref_name(&ref_name) {
+        return Err(NyxError::Usage(format!(
+            "'{}' is not a valid tag name",
+            name
+        )));
+    }
+
+    if delete {
+        let hash = refs::read_ref(&ref_name)
+            .ok_or_else(|| NyxError::Usage(format!("tag '{}' not found.", name)))?;
+        refs::delete_ref(&ref_name)?;
+        println!("Deleted tag '{}' (was {})", name, short_hash(&hash));
+        return Ok(());
+    }
+
+    if refs::ref_exists(&ref_name) && !force {
+        return Err(NyxError::Usage(format!("tag '{}' already exists", name)));
+    }
+
+    let target = resolve(rev.as_deref().unwrap_or(refs::HEAD))?;
+    let hash = if annotate || sign || message.is_some() {
+        let message = match message {
+            Some(message) => message.clone(),
+            None => edit_message(name)?,
+        };
+        let mut tag = Tag::new(&target, read_object_type(&target)?, name, &message);
+        if sign {
+            tag.sign()?;
+        }
+        tag.write_object().to_string()
+    } else {
+        target
+    };
+    refs::write_ref(&ref_name, &hash)
+}
+
+fn list() {
+    for (name, _) in refs::list_refs(TAGS_PREFIX) {
+        println!("{}", name.trim_start_matches("tags/"));
+    }
+}
+
+fn edit_message(name: &str) -> Result<String, NyxError> {
+    let path = FILE_SYSTEM.get_state_file_path("TAG_EDITMSG");
+    fs::write(
+        &path,
+        format!(
+            "\n#\n# Write a message for tag:\n#   {}\n# Lines starting with '#' will be ignored.\n",
+            name
+        ),
+    )?;
+    edit_file(&path)?;
+    let message = strip_comments(&fs::read_to_string(&path)?);
+    if message.is_empty() {
+        return Err(NyxError::Usage(String::from("no tag message given")));
+    }
+    Ok(message)
+}
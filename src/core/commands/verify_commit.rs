@@ -0,0 +1,20 @@
+use crate::core::{errors::NyxError, revision::resolve_commit};
+
+/// Checks the signatures of the commits against the allowed signers and
+/// fails unless all of them are good.
+pub fn verify_commit(revs: &[String]) -> Result<(), NyxError> {
+    for rev in revs {
+        let commit = resolve_commit(rev)?;
+        let verification = commit
+            .verify()?
+            .ok_or_else(|| NyxError::Usage(format!("{}: no signature found", rev)))?;
+        eprintln!("{}", verification);
+        if !verification.is_good() {
+            return Err(NyxError::Usage(format!(
+                "{}: signature could not be verified",
+                rev
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use crate::core::{errors::NyxError, refs::refs, tag::Tag};
+
+/// Checks the signatures of the annotated tags against the allowed
+/// signers and fails unless all of them are good.
+pub fn verify_tag(names: &[String]) -> Result<(), NyxError> {
+    for name in names {
+        let hash = refs::read_ref(&format!("tags/{}", name))
+            .ok_or_else(|| NyxError::InvalidRef(format!("tags/{}", name)))?;
+        let tag = Tag::from_hash(&hash)
+            .ok_or_else(|| NyxError::Usage(format!("{}: cannot verify a lightweight tag", name)))?;
+        let verification = tag
+            .verify()?
+            .ok_or_else(|| NyxError::Usage(format!("{}: no signature found", name)))?;
+        eprintln!("{}", verification);
+        if !verification.is_good() {
+            return Err(NyxError::Usage(format!(
+                "{}: signature could not be verified",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
@@ -1,29 +1,52 @@
-use colored::Colorize;
 use std::fmt::Display;
-use std::fs;
-
-use crate::FILE_SYSTEM;
 
+use super::errors::NyxError;
 use super::object_type::NyxObjectType;
+use super::pretty;
+use super::refs::refs;
 use super::shared::{generate_object, read_object_data};
+use super::signature::Signature;
+use super::signing::{self, Verification};
+
+/// Header of the signature of a signed commit, named like in git.
+const SIGNATURE_HEADER: &str = "gpgsig";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Commit {
     tree_hash: String,
-    parent_hash: String,
+    parent_hashes: Vec<String>,
+    author: Option<Signature>,
+    committer: Option<Signature>,
+    signature: Option<String>,
     hash: String,
     message: String,
+    /// Content of the object the commit was read from.
+    raw: Option<String>,
 }
 
 impl Commit {
     pub fn new(tree_hash: &str, message: &str) -> Self {
         let parent_hash = Commit::read_current_commit_hash();
+        let parents = if parent_hash.is_empty() {
+            Vec::new()
+        } else {
+            vec![parent_hash]
+        };
+        Commit::with_parents(tree_hash, parents, message)
+    }
+
+    pub fn with_parents(tree_hash: &str, parent_hashes: Vec<String>, message: &str) -> Self {
+        let signature = Signature::now();
 
         Self {
             tree_hash: tree_hash.to_string(),
-            parent_hash,
+            parent_hashes,
+            author: Some(signature.clone()),
+            committer: Some(signature),
+            signature: None,
             hash: String::new(),
             message: message.to_string(),
+            raw: None,
         }
     }
 
@@ -36,57 +59,162 @@ impl Commit {
             return None;
         }
         // TODO: Implement general read object to struct method (maybe in NyxFileSystem)
-        let content = read_object_data(&hash).unwrap();
-        let content: Vec<&str> = content.split("\n").filter(|e| !e.is_empty()).collect();
-
-        let message: String;
-        let tree_hash: &str;
-        let mut parent_hash = "";
-
-        if content.len() == 2 {
-            tree_hash = (content[0].split_whitespace().collect::<Vec<&str>>())[1];
-            message = content[1].to_string();
-        } else if content.len() == 3 {
-            tree_hash = (content[0].split_whitespace().collect::<Vec<&str>>())[1];
-            parent_hash = (content[1].split_whitespace().collect::<Vec<&str>>())[1];
-            message = content[2].to_string();
-        } else {
-            return None;
+        let content = read_object_data(hash).ok()?;
+        Commit::parse(hash, &content)
+    }
+
+    /// Like `from_hash` but reports a missing or malformed commit as error.
+    pub fn read(hash: &str) -> Result<Self, NyxError> {
+        Commit::from_hash(hash).ok_or_else(|| NyxError::InvalidObject(hash.to_string()))
+    }
+
+    fn parse(hash: &str, content: &str) -> Option<Self> {
+        let mut tree_hash: Option<String> = None;
+        let mut parent_hashes = Vec::new();
+        let mut author = None;
+        let mut committer = None;
+        let mut signature: Option<String> = None;
+
+        // Headers come first, the message follows after an empty line.
+        // Commits written by older versions have no empty line.
+        let mut lines = content.split('\n').peekable();
+        while let Some(line) = lines.peek() {
+            let (key, value) = match line.split_once(' ') {
+                Some(split) => split,
+                None => break,
+            };
+            match key {
+                "tree" => tree_hash = Some(value.to_string()),
+                "parent" => parent_hashes.push(value.to_string()),
+                "author" => author = Signature::parse(value),
+                "committer" => committer = Signature::parse(value),
+                SIGNATURE_HEADER => {
+                    // The signature continues on lines starting with a space
+                    let mut text = value.to_string();
+                    lines.next();
+                    while let Some(line) = lines.peek().and_then(|line| line.strip_prefix(' ')) {
+                        text = format!("{}\n{}", text, line);
+                        lines.next();
+                    }
+                    signature = Some(text);
+                    continue;
+                }
+                _ => break,
+            }
+            lines.next();
         }
+        if let Some(line) = lines.peek() {
+            if line.is_empty() {
+                lines.next();
+            }
+        }
+        let message = lines.collect::<Vec<&str>>().join("\n");
 
         Some(Self {
-            tree_hash: tree_hash.to_string(),
-            parent_hash: parent_hash.to_string(),
+            tree_hash: tree_hash?,
+            parent_hashes,
+            author,
+            committer,
+            signature,
             hash: hash.to_string(),
-            message,
+            message: message.trim_end().to_string(),
+            raw: Some(content.to_string()),
         })
     }
 
     pub fn get_content(&self) -> String {
         let mut content = format!("tree {}\n", self.tree_hash);
-        if !self.parent_hash.is_empty() {
-            content = format!("{}parent {}\n", content, self.parent_hash);
+        for parent_hash in &self.parent_hashes {
+            content = format!("{}parent {}\n", content, parent_hash);
+        }
+        if let Some(author) = &self.author {
+            content = format!("{}author {}\n", content, author);
+        }
+        if let Some(committer) = &self.committer {
+            content = format!("{}committer {}\n", content, committer);
+        }
+        if let Some(signature) = &self.signature {
+            let signature = signature.replace('\n', "\n ");
+            content = format!("{}{} {}\n", content, SIGNATURE_HEADER, signature);
         }
         if !self.message.is_empty() {
-            content = format!("{}{}", content, self.message);
+            content = format!("{}\n{}\n", content, self.message);
         }
         content
     }
 
-    pub fn write(&mut self) {
-        self.hash = generate_object(self.get_content().as_bytes(), NyxObjectType::Commit);
+    /// The content without the signature, which is what gets signed.
+    /// Commits that were read keep their content byte for byte, only
+    /// the lines of the signature header are left out.
+    pub fn payload(&self) -> String {
+        let raw = match &self.raw {
+            Some(raw) => raw,
+            None => {
+                return Commit {
+                    signature: None,
+                    ..self.clone()
+                }
+                .get_content()
+            }
+        };
+        let mut payload = String::new();
+        let mut in_headers = true;
+        let mut in_signature = false;
+        for line in raw.split_inclusive('\n') {
+            if in_headers {
+                if in_signature && line.starts_with(' ') {
+                    continue;
+                }
+                in_signature = line.starts_with(&format!("{} ", SIGNATURE_HEADER));
+                if in_signature {
+                    continue;
+                }
+                in_headers = line != "\n";
+            }
+            payload.push_str(line);
+        }
+        payload
+    }
+
+    /// Signs the commit with the key of `user.signingKey`, must happen
+    /// before it is written.
+    pub fn sign(&mut self) -> Result<(), NyxError> {
+        // Reading the object trims the message, the payload must match
+        self.message = self.message.trim_end().to_string();
+        self.raw = None;
+        self.signature = Some(signing::sign(self.payload().as_bytes())?);
+        Ok(())
+    }
 
-        // TODO: Move master to FILE_SYSTEM
-        let master_path = FILE_SYSTEM.get_refs_dir_path().join("master");
+    /// Checks the signature, `None` if the commit is not signed.
+    pub fn verify(&self) -> Result<Option<Verification>, NyxError> {
+        match &self.signature {
+            Some(signature) => signing::verify(self.payload().as_bytes(), signature).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the commit object and advances HEAD to it.
+    pub fn write(&mut self) {
+        if self.parent_hashes.is_empty() {
+            self.write_as("commit (initial)");
+        } else {
+            self.write_as("commit");
+        }
+    }
 
-        // Create master with current hash in refs dir
-        fs::write(master_path, &self.hash).unwrap();
+    /// Like `write` but records `action` (e.g. `cherry-pick`) in the reflog.
+    pub fn write_as(&mut self, action: &str) {
+        self.write_object();
 
-        let head_path = FILE_SYSTEM.get_head_path();
+        let reflog_message = format!("{}: {}", action, self.summary());
+        refs::update_head(&self.hash, &reflog_message).unwrap();
+    }
 
-        // Create HEAD with ref to master
-        let head_content = format!("ref: refs/master");
-        fs::write(head_path, head_content).unwrap();
+    /// Writes the commit object only, no ref is updated.
+    pub fn write_object(&mut self) -> &str {
+        self.hash = generate_object(self.get_content().as_bytes(), NyxObjectType::Commit);
+        &self.hash
     }
 
     pub fn get_hash(&self) -> &str {
@@ -94,21 +222,15 @@ impl Commit {
     }
 
     pub fn get_parent_hash(&self) -> &str {
-        &self.parent_hash
+        self.parent_hashes.first().map(|p| p.as_str()).unwrap_or("")
+    }
+
+    pub fn parent_hashes(&self) -> &Vec<String> {
+        &self.parent_hashes
     }
 
     fn read_current_commit_hash() -> String {
-        let head_path = FILE_SYSTEM.get_head_path();
-        if head_path.exists() {
-            let head_content = fs::read_to_string(&head_path).unwrap();
-            if head_content.contains("ref: ") {
-                fs::read_to_string(FILE_SYSTEM.get_refs_dir_path().join("master")).unwrap()
-            } else {
-                fs::read_to_string(&head_path).unwrap()
-            }
-        } else {
-            String::new()
-        }
+        refs::head_hash()
     }
 
     pub fn tree_hash(&self) -> &str {
@@ -118,16 +240,35 @@ impl Commit {
     pub fn message(&self) -> &str {
         self.message.as_ref()
     }
+
+    /// First line of the message.
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or("")
+    }
+
+    pub fn author(&self) -> Option<&Signature> {
+        self.author.as_ref()
+    }
+
+    pub fn committer(&self) -> Option<&Signature> {
+        self.committer.as_ref()
+    }
+
+    pub fn set_author(&mut self, author: Option<Signature>) {
+        self.author = author;
+    }
+
+    pub fn set_committer(&mut self, committer: Option<Signature>) {
+        self.committer = committer;
+    }
+
+    pub fn set_message(&mut self, message: &str) {
+        self.message = message.to_string();
+    }
 }
 
 impl Display for Commit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let output = format!(
-            "{} {}\n\n    {}",
-            "commit".yellow(),
-            self.hash.as_str().yellow(),
-            self.message
-        );
-        write!(f, "{}", output)
+        write!(f, "{}", pretty::medium(self))
     }
 }
@@ -0,0 +1,190 @@
+use std::fs;
+
+use crate::FILE_SYSTEM;
+
+use super::errors::NyxError;
+
+/// A single `[section "subsection"]` block of the config file.
+#[derive(Debug, Clone)]
+struct ConfigSection {
+    name: String,
+    subsection: Option<String>,
+    values: Vec<(String, String)>,
+}
+
+impl ConfigSection {
+    fn header(&self) -> String {
+        match &self.subsection {
+            Some(subsection) => format!("[{} \"{}\"]", self.name, subsection),
+            None => format!("[{}]", self.name),
+        }
+    }
+
+    fn matches(&self, name: &str, subsection: Option<&str>) -> bool {
+        self.name == name && self.subsection.as_deref() == subsection
+    }
+}
+
+/// Repository configuration stored in `.nyx/config` using the
+/// ini-like format known from git. Keys are addressed with dots,
+/// e.g. `user.name` or `remote.origin.url`.
+#[derive(Debug)]
+pub struct Config {
+    sections: Vec<ConfigSection>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        let content = fs::read_to_string(FILE_SYSTEM.get_config_path()).unwrap_or_default();
+        Config::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut sections: Vec<ConfigSection> = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                let header = &line[1..line.len() - 1];
+                let (name, subsection) = match header.find(' ') {
+                    Some(idx) => (
+                        &header[..idx],
+                        Some(header[idx + 1..].trim().trim_matches('"').to_string()),
+                    ),
+                    None => (header, None),
+                };
+                sections.push(ConfigSection {
+                    name: name.to_lowercase(),
+                    subsection,
+                    values: Vec::new(),
+                });
+            } else if let Some(section) = sections.last_mut() {
+                let (key, value) = match line.find('=') {
+                    Some(idx) => (line[..idx].trim(), line[idx + 1..].trim()),
+                    None => (line, "true"),
+                };
+                section
+                    .values
+                    .push((key.to_lowercase(), value.trim_matches('"').to_string()));
+            }
+        }
+        Self { sections }
+    }
+
+    /// Splits `section.subsection.key` into its three parts.
+    fn split_key(key: &str) -> Option<(String, Option<String>, String)> {
+        let first = key.find('.')?;
+        let last = key.rfind('.')?;
+        let section = key[..first].to_lowercase();
+        let name = key[last + 1..].to_lowercase();
+        if first == last {
+            Some((section, None, name))
+        } else {
+            Some((section, Some(key[first + 1..last].to_string()), name))
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.get_all(key).pop()
+    }
+
+    pub fn get_all(&self, key: &str) -> Vec<String> {
+        let (section, subsection, name) = match Config::split_key(key) {
+            Some(parts) => parts,
+            None => return Vec::new(),
+        };
+        self.sections
+            .iter()
+            .filter(|s| s.matches(&section, subsection.as_deref()))
+            .flat_map(|s| s.values.iter())
+            .filter(|(k, _)| *k == name)
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)
+            .map(|v| matches!(v.to_lowercase().as_str(), "true" | "yes" | "on" | "1"))
+    }
+
+    /// Returns the subsection names of all sections called `section`,
+    /// e.g. the names of all configured remotes.
+    pub fn subsections(&self, section: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for s in &self.sections {
+            if let Some(subsection) = &s.subsection {
+                if s.name == section && !names.contains(subsection) {
+                    names.push(subsection.clone());
+                }
+            }
+        }
+        names
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), NyxError> {
+        let (section, subsection, name) = Config::split_key(key)
+            .ok_or_else(|| NyxError::Usage(format!("invalid config key '{}'", key)))?;
+
+        let idx = match self
+            .sections
+            .iter()
+            .position(|s| s.matches(&section, subsection.as_deref()))
+        {
+            Some(idx) => idx,
+            None => {
+                self.sections.push(ConfigSection {
+                    name: section,
+                    subsection,
+                    values: Vec::new(),
+                });
+                self.sections.len() - 1
+            }
+        };
+        let values = &mut self.sections[idx].values;
+        match values.iter_mut().find(|(k, _)| *k == name) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => values.push((name, value.to_string())),
+        }
+        Ok(())
+    }
+
+    pub fn unset(&mut self, key: &str) {
+        if let Some((section, subsection, name)) = Config::split_key(key) {
+            for s in &mut self.sections {
+                if s.matches(&section, subsection.as_deref()) {
+                    s.values.retain(|(k, _)| *k != name);
+                }
+            }
+        }
+    }
+
+    pub fn remove_section(&mut self, section: &str, subsection: Option<&str>) {
+        self.sections.retain(|s| !s.matches(section, subsection));
+    }
+
+    pub fn write(&self) -> Result<(), NyxError> {
+        fs::write(FILE_SYSTEM.get_config_path(), self.to_string())?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for section in &self.sections {
+            writeln!(f, "{}", section.header())?;
+            for (key, value) in &section.values {
+                writeln!(f, "\t{} = {}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
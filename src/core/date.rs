@@ -0,0 +1,180 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: i64 = 24 * 60 * 60;
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`, returns `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts an offset like `+0130` to seconds.
+pub fn offset_seconds(offset: &str) -> i64 {
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+    let digits = offset.trim_start_matches(['+', '-']);
+    if digits.len() != 4 {
+        return 0;
+    }
+    let hours: i64 = digits[..2].parse().unwrap_or(0);
+    let minutes: i64 = digits[2..].parse().unwrap_or(0);
+    sign * (hours * 3600 + minutes * 60)
+}
+
+/// Formats a timestamp like git does by default,
+/// e.g. `Tue Jul 19 12:34:56 2022 +0000`.
+pub fn format_date(time: i64, offset: &str) -> String {
+    let local = time + offset_seconds(offset);
+    let days = local.div_euclid(DAY);
+    let seconds = local.rem_euclid(DAY);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{} {} {} {:02}:{:02}:{:02} {} {}",
+        WEEKDAYS[days.rem_euclid(7) as usize],
+        MONTHS[(month - 1) as usize],
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        year,
+        offset
+    )
+}
+
+/// Formats a timestamp as `2022-07-19`.
+pub fn format_short_date(time: i64, offset: &str) -> String {
+    let (year, month, day) = civil_from_days((time + offset_seconds(offset)).div_euclid(DAY));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats a timestamp as `2022-07-19 12:34:56 +0000`.
+pub fn format_iso_date(time: i64, offset: &str) -> String {
+    let local = time + offset_seconds(offset);
+    let seconds = local.rem_euclid(DAY);
+    format!(
+        "{} {:02}:{:02}:{:02} {}",
+        format_short_date(time, offset),
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+        offset
+    )
+}
+
+/// Formats a timestamp relative to now, e.g. `3 days ago`.
+pub fn format_relative_date(time: i64) -> String {
+    let diff = now() - time;
+    if diff < 0 {
+        return String::from("in the future");
+    }
+    let units = [
+        (365 * DAY, "year"),
+        (30 * DAY, "month"),
+        (7 * DAY, "week"),
+        (DAY, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+    for (seconds, name) in units {
+        if diff >= seconds {
+            let count = diff / seconds;
+            let plural = if count == 1 { "" } else { "s" };
+            return format!("{} {}{} ago", count, name, plural);
+        }
+    }
+    String::from("just now")
+}
+
+/// Parses dates as accepted by `--since` and `--until`: `now`,
+/// `yesterday`, `<n> <unit>s ago`, `YYYY-MM-DD[ HH:MM[:SS]]`
+/// and unix timestamps (`1658000000` or `@1658000000`).
+pub fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim().to_lowercase();
+    match value.as_str() {
+        "now" => return Some(now()),
+        "yesterday" => return Some(now() - DAY),
+        _ => (),
+    }
+    if let Ok(timestamp) = value.trim_start_matches('@').parse::<i64>() {
+        if value.starts_with('@') || value.len() > 8 {
+            return Some(timestamp);
+        }
+    }
+
+    let words: Vec<&str> = value
+        .split(|c: char| c.is_whitespace() || c == '.')
+        .collect();
+    if words.len() == 3 && words[2] == "ago" {
+        let count: i64 = words[0].parse().ok()?;
+        let unit = match words[1].trim_end_matches('s') {
+            "second" => 1,
+            "minute" => 60,
+            "hour" => 3600,
+            "day" => DAY,
+            "week" => 7 * DAY,
+            "month" => 30 * DAY,
+            "year" => 365 * DAY,
+            _ => return None,
+        };
+        return Some(now() - count * unit);
+    }
+
+    let (date, time) = match value.split_once(['t', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value.as_str(), None),
+    };
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    if date.len() != 3 {
+        return None;
+    }
+    let mut seconds = days_from_civil(date[0], date[1], date[2]) * DAY;
+    if let Some(time) = time {
+        let time: Vec<i64> = time
+            .split(':')
+            .map(|p| p.parse().ok())
+            .collect::<Option<_>>()?;
+        let factors = [3600, 60, 1];
+        for (value, factor) in time.iter().zip(factors) {
+            seconds += value * factor;
+        }
+    }
+    Some(seconds)
+}
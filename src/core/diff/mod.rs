@@ -0,0 +1,4 @@
+pub mod myers;
+pub mod select;
+pub mod tree_diff;
+pub mod unified;
@@ -0,0 +1,75 @@
+/// A single step of an edit script turning `a` into `b`.
+/// The values are line indices into `a` and `b` respectively.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Computes the shortest edit script between two sequences of lines
+/// using the O(ND) algorithm by Eugene W. Myers.
+pub fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<DiffOp> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let offset = n + m + 1;
+
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // The diagonals `-d - 1..=d + 1` of `v` at the start of every round,
+    // the only ones backtracking reads, so the trace grows with D² only
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'rounds: for d in 0..=(n + m) {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'rounds;
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let mut x = n;
+    let mut y = m;
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(DiffOp::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push(DiffOp::Insert(prev_y as usize));
+            } else {
+                ops.push(DiffOp::Delete(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
@@ -0,0 +1,307 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use colored::Colorize;
+
+use crate::core::editor::edit_file;
+use crate::core::errors::NyxError;
+use crate::FILE_SYSTEM;
+
+use super::unified::{apply_hunks, colorize, hunks, Hunk, DEFAULT_CONTEXT};
+
+const EDIT_FILE: &str = "ADD_EDIT.patch";
+
+/// What the hunks picked by the user are used for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PatchMode {
+    /// Apply hunks of the unstaged changes to the index
+    Stage,
+    /// Take hunks of the staged changes back out of the index
+    Unstage,
+    /// Take hunks of the unstaged changes back out of the working tree
+    Discard,
+}
+
+impl PatchMode {
+    fn question(&self, what: &str) -> String {
+        match self {
+            PatchMode::Stage => format!("Stage {}", what),
+            PatchMode::Unstage => format!("Unstage {}", what),
+            PatchMode::Discard => format!("Discard {} from worktree", what),
+        }
+    }
+
+    /// Whether selected hunks are undone instead of applied.
+    fn is_reverse(&self) -> bool {
+        *self != PatchMode::Stage
+    }
+}
+
+/// The content a file gets from the selected changes.
+pub enum Selection {
+    Unchanged,
+    Content(String),
+    Removed,
+}
+
+impl Selection {
+    fn of(content: Option<&str>) -> Self {
+        match content {
+            Some(content) => Selection::Content(content.to_string()),
+            None => Selection::Removed,
+        }
+    }
+}
+
+/// Walks the user through the hunks of changed files and builds the
+/// content the selected hunks make up.
+pub struct HunkSelector {
+    mode: PatchMode,
+    quit: bool,
+}
+
+impl HunkSelector {
+    pub fn new(mode: PatchMode) -> Self {
+        Self { mode, quit: false }
+    }
+
+    /// Whether the user quit, no further files are offered then.
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    fn help(&self, can_split: bool, can_edit: bool) -> String {
+        let action = match self.mode {
+            PatchMode::Stage => "stage",
+            PatchMode::Unstage => "unstage",
+            PatchMode::Discard => "discard",
+        };
+        let mut help = vec![
+            format!("y - {} this hunk", action),
+            format!("n - do not {} this hunk", action),
+            format!(
+                "q - quit; do not {} this hunk or any of the remaining ones",
+                action
+            ),
+            format!("a - {} this hunk and all later hunks in the file", action),
+            format!(
+                "d - do not {} this hunk or any of the later hunks in the file",
+                action
+            ),
+        ];
+        if can_split {
+            help.push(String::from(
+                "s - split the current hunk into smaller hunks",
+            ));
+        }
+        if can_edit {
+            help.push(String::from("e - manually edit the current hunk"));
+        }
+        help.push(String::from("? - print help"));
+        help.join("\n")
+    }
+
+    /// Asks until one of the options is answered, end of input quits.
+    fn ask(&self, question: &str, can_split: bool, can_edit: bool) -> Result<char, NyxError> {
+        let mut options = vec!['y', 'n', 'q', 'a', 'd'];
+        if can_split {
+            options.push('s');
+        }
+        if can_edit {
+            options.push('e');
+        }
+        let list: Vec<String> = options
+            .iter()
+            .chain(['?'].iter())
+            .map(char::to_string)
+            .collect();
+        let stdin = io::stdin();
+        loop {
+            print!(
+                "{} ",
+                format!("{} [{}]?", question, list.join(",")).blue().bold()
+            );
+            io::stdout().flush()?;
+            let mut answer = String::new();
+            if stdin.lock().read_line(&mut answer)? == 0 {
+                println!();
+                return Ok('q');
+            }
+            match answer.trim().chars().next() {
+                Some(answer) if options.contains(&answer) => return Ok(answer),
+                Some(_) => println!("{}", self.help(can_split, can_edit).red().bold()),
+                None => (),
+            }
+        }
+    }
+
+    /// Offers the changes of `path` from `old` to `new`, `None` stands
+    /// for a missing file. Additions and deletions of whole files are
+    /// offered as one change.
+    pub fn select(
+        &mut self,
+        path: &str,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) -> Result<Selection, NyxError> {
+        if self.quit || old == new {
+            return Ok(Selection::Unchanged);
+        }
+        let label = |content: Option<&str>, side: &str| match content {
+            Some(_) => format!("{}/{}", side, path),
+            None => String::from("/dev/null"),
+        };
+        print!(
+            "{}",
+            colorize(&format!(
+                "diff --nyx a/{} b/{}\n--- {}\n+++ {}\n",
+                path,
+                path,
+                label(old, "a"),
+                label(new, "b")
+            ))
+        );
+        match (old, new) {
+            (Some(old), Some(new)) => self.select_hunks(old, new),
+            _ => {
+                for hunk in hunks(
+                    old.unwrap_or_default(),
+                    new.unwrap_or_default(),
+                    DEFAULT_CONTEXT,
+                ) {
+                    print!("{}", colorize(&hunk.to_patch()));
+                }
+                let what = match old {
+                    Some(_) => "deletion",
+                    None => "addition",
+                };
+                match self.ask(&self.mode.question(what), false, false)? {
+                    'y' | 'a' => Ok(match self.mode.is_reverse() {
+                        true => Selection::of(old),
+                        false => Selection::of(new),
+                    }),
+                    'q' => {
+                        self.quit = true;
+                        Ok(Selection::Unchanged)
+                    }
+                    _ => Ok(Selection::Unchanged),
+                }
+            }
+        }
+    }
+
+    fn select_hunks(&mut self, old: &str, new: &str) -> Result<Selection, NyxError> {
+        let mut hunks = hunks(old, new, DEFAULT_CONTEXT);
+        let mut selected = vec![false; hunks.len()];
+        let mut idx = 0;
+        while idx < hunks.len() {
+            print!("{}", colorize(&hunks[idx].to_patch()));
+            let can_split = hunks[idx].split().len() > 1;
+            let question = format!(
+                "({}/{}) {}",
+                idx + 1,
+                hunks.len(),
+                self.mode.question("this hunk")
+            );
+            match self.ask(&question, can_split, true)? {
+                'y' => selected[idx] = true,
+                'n' => (),
+                'a' => {
+                    selected[idx..].fill(true);
+                    break;
+                }
+                'd' => break,
+                'q' => {
+                    self.quit = true;
+                    break;
+                }
+                's' => {
+                    let parts = hunks[idx].split();
+                    println!("{}", format!("Split into {} hunks.", parts.len()).cyan());
+                    selected.splice(idx..idx + 1, vec![false; parts.len()]);
+                    hunks.splice(idx..idx + 1, parts);
+                    continue;
+                }
+                _ => match self.edit(&hunks[idx])? {
+                    Some(edited) => {
+                        hunks[idx] = edited;
+                        selected[idx] = true;
+                    }
+                    None => continue,
+                },
+            }
+            idx += 1;
+        }
+
+        let picked: Vec<Hunk> = hunks
+            .into_iter()
+            .zip(selected)
+            .filter(|(_, selected)| *selected)
+            .map(|(hunk, _)| match self.mode.is_reverse() {
+                true => hunk.reversed(),
+                false => hunk,
+            })
+            .collect();
+        if picked.is_empty() {
+            return Ok(Selection::Unchanged);
+        }
+        Ok(Selection::Content(match self.mode.is_reverse() {
+            true => apply_hunks(new, &picked),
+            false => apply_hunks(old, &picked),
+        }))
+    }
+
+    /// Lets the user edit the hunk, `None` if the edited hunk no longer
+    /// fits the text it belongs to.
+    fn edit(&self, hunk: &Hunk) -> Result<Option<Hunk>, NyxError> {
+        let guide = match self.mode.is_reverse() {
+            true => "# To remove '-' lines, delete them.\n# To remove '+' lines, make them ' ' lines (context).",
+            false => "# To remove '-' lines, make them ' ' lines (context).\n# To remove '+' lines, delete them.",
+        };
+        let path = FILE_SYSTEM.get_repo_dir().join(EDIT_FILE);
+        fs::write(
+            &path,
+            format!(
+                "# Manual hunk edit mode - see bottom for a quick guide.\n{}# ---\n{}\n# Lines starting with # will be removed.\n",
+                hunk.to_patch(),
+                guide
+            ),
+        )?;
+        edit_file(&path)?;
+        let content = fs::read_to_string(&path)?;
+        _ = fs::remove_file(&path);
+
+        let mut lines: Vec<(char, String)> = Vec::new();
+        for line in content.lines() {
+            if line.starts_with('#') || line.starts_with("@@") {
+                continue;
+            }
+            if line.starts_with('\\') {
+                if let Some((_, last)) = lines.last_mut() {
+                    last.pop();
+                }
+                continue;
+            }
+            let mut chars = line.chars();
+            let tag = chars.next().unwrap_or(' ');
+            if !matches!(tag, ' ' | '-' | '+') {
+                eprintln!("Your edited hunk does not apply.");
+                return Ok(None);
+            }
+            lines.push((tag, format!("{}\n", chars.as_str())));
+        }
+
+        let mut edited = hunk.clone();
+        edited.set_lines(lines);
+        // The side the hunk is applied to must stay as it is
+        let applies = match self.mode.is_reverse() {
+            true => edited.new_lines() == hunk.new_lines(),
+            false => edited.old_lines() == hunk.old_lines(),
+        };
+        if !applies {
+            eprintln!("Your edited hunk does not apply.");
+            return Ok(None);
+        }
+        Ok(Some(edited))
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use crate::core::shared::read_object_data;
+use crate::core::tree::tree::Tree;
+
+use super::unified::unified_diff;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Deleted,
+    Modified,
+}
+
+/// Change of a single file between two trees.
+#[derive(Debug, Clone)]
+pub struct TreeChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old_hash: Option<String>,
+    pub new_hash: Option<String>,
+}
+
+impl TreeChange {
+    pub fn status_letter(&self) -> char {
+        match self.kind {
+            ChangeKind::Added => 'A',
+            ChangeKind::Deleted => 'D',
+            ChangeKind::Modified => 'M',
+        }
+    }
+
+    /// Unified diff of the file contents with a `diff --nyx` header.
+    pub fn patch(&self) -> String {
+        // `None` for content that is not text
+        let read = |hash: &Option<String>| match hash {
+            Some(hash) => read_object_data(hash).ok(),
+            None => Some(String::new()),
+        };
+        let old_label = match self.old_hash {
+            Some(_) => format!("a/{}", self.path),
+            None => String::from("/dev/null"),
+        };
+        let new_label = match self.new_hash {
+            Some(_) => format!("b/{}", self.path),
+            None => String::from("/dev/null"),
+        };
+
+        let mut patch = format!("diff --nyx a/{} b/{}\n", self.path, self.path);
+        match self.kind {
+            ChangeKind::Added => patch.push_str("new file\n"),
+            ChangeKind::Deleted => patch.push_str("deleted file\n"),
+            ChangeKind::Modified => (),
+        }
+        match (read(&self.old_hash), read(&self.new_hash)) {
+            (Some(old), Some(new)) => {
+                patch.push_str(&unified_diff(&old, &new, &old_label, &new_label))
+            }
+            _ => patch.push_str(&format!(
+                "Binary files {} and {} differ\n",
+                old_label, new_label
+            )),
+        }
+        patch
+    }
+}
+
+/// Compares two flattened trees (path -> blob hash).
+pub fn diff_maps(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<TreeChange> {
+    let mut changes = Vec::new();
+    for (path, old_hash) in old {
+        match new.get(path) {
+            Some(new_hash) if new_hash == old_hash => (),
+            Some(new_hash) => changes.push(TreeChange {
+                path: path.clone(),
+                kind: ChangeKind::Modified,
+                old_hash: Some(old_hash.clone()),
+                new_hash: Some(new_hash.clone()),
+            }),
+            None => changes.push(TreeChange {
+                path: path.clone(),
+                kind: ChangeKind::Deleted,
+                old_hash: Some(old_hash.clone()),
+                new_hash: None,
+            }),
+        }
+    }
+    for (path, new_hash) in new {
+        if !old.contains_key(path) {
+            changes.push(TreeChange {
+                path: path.clone(),
+                kind: ChangeKind::Added,
+                old_hash: None,
+                new_hash: Some(new_hash.clone()),
+            });
+        }
+    }
+    changes.sort_by(|c1, c2| c1.path.cmp(&c2.path));
+    changes
+}
+
+/// Compares two trees by hash. `None` stands for the empty tree.
+pub fn diff_trees(old_tree: Option<&str>, new_tree: Option<&str>) -> Vec<TreeChange> {
+    diff_maps(&Tree::read_paths(old_tree), &Tree::read_paths(new_tree))
+}
@@ -0,0 +1,288 @@
+use colored::Colorize;
+
+use super::myers::{diff, DiffOp};
+
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// A consecutive block of changes with surrounding context lines.
+/// Every line keeps its line terminator and is prefixed with
+/// one of `' '`, `'-'` or `'+'`.
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<(char, String)>,
+}
+
+impl Hunk {
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{} +{} @@",
+            Hunk::range(self.old_start, self.old_len),
+            Hunk::range(self.new_start, self.new_len)
+        )
+    }
+
+    fn range(start: usize, len: usize) -> String {
+        // Empty ranges point to the line before the change
+        let start = if len == 0 { start } else { start + 1 };
+        if len == 1 {
+            start.to_string()
+        } else {
+            format!("{},{}", start, len)
+        }
+    }
+
+    pub fn to_patch(&self) -> String {
+        let mut patch = format!("{}\n", self.header());
+        for (tag, line) in &self.lines {
+            patch.push(*tag);
+            patch.push_str(line);
+            if !line.ends_with('\n') {
+                patch.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        patch
+    }
+
+    fn count(lines: &[(char, String)], skip: char) -> usize {
+        lines.iter().filter(|(tag, _)| *tag != skip).count()
+    }
+
+    /// Lines of the old text the hunk covers.
+    pub fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|(tag, _)| *tag != '+')
+            .map(|(_, line)| line.as_str())
+            .collect()
+    }
+
+    /// Lines of the new text the hunk covers.
+    pub fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|(tag, _)| *tag != '-')
+            .map(|(_, line)| line.as_str())
+            .collect()
+    }
+
+    /// The hunk turning the new text back into the old one.
+    pub fn reversed(&self) -> Hunk {
+        Hunk {
+            old_start: self.new_start,
+            old_len: self.new_len,
+            new_start: self.old_start,
+            new_len: self.old_len,
+            lines: self
+                .lines
+                .iter()
+                .map(|(tag, line)| {
+                    let tag = match tag {
+                        '-' => '+',
+                        '+' => '-',
+                        _ => ' ',
+                    };
+                    (tag, line.clone())
+                })
+                .collect(),
+        }
+    }
+
+    /// Splits the hunk at its inner context lines into hunks with a
+    /// single block of changes each. Neighbouring hunks share the
+    /// context lines between their changes.
+    pub fn split(&self) -> Vec<Hunk> {
+        let mut blocks: Vec<(usize, usize)> = Vec::new();
+        for (idx, (tag, _)) in self.lines.iter().enumerate() {
+            if *tag == ' ' {
+                continue;
+            }
+            match blocks.last_mut() {
+                Some((_, end)) if *end == idx => *end = idx + 1,
+                _ => blocks.push((idx, idx + 1)),
+            }
+        }
+        if blocks.len() < 2 {
+            return vec![self.clone()];
+        }
+
+        let mut hunks = Vec::with_capacity(blocks.len());
+        for idx in 0..blocks.len() {
+            let from = match idx {
+                0 => 0,
+                _ => blocks[idx - 1].1,
+            };
+            let to = blocks
+                .get(idx + 1)
+                .map_or(self.lines.len(), |(start, _)| *start);
+            let before = &self.lines[..from];
+            let lines = self.lines[from..to].to_vec();
+            hunks.push(Hunk {
+                old_start: self.old_start + Hunk::count(before, '+'),
+                old_len: Hunk::count(&lines, '+'),
+                new_start: self.new_start + Hunk::count(before, '-'),
+                new_len: Hunk::count(&lines, '-'),
+                lines,
+            });
+        }
+        hunks
+    }
+
+    /// Replaces the lines and updates the lengths, e.g. after an edit.
+    pub fn set_lines(&mut self, lines: Vec<(char, String)>) {
+        self.old_len = Hunk::count(&lines, '+');
+        self.new_len = Hunk::count(&lines, '-');
+        self.lines = lines;
+    }
+}
+
+/// Splits text into lines keeping the line terminators, so a missing
+/// newline at the end of a file is a visible difference.
+pub fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+pub fn hunks(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    let ops = diff(&old_lines, &new_lines);
+
+    // Cursor positions in old and new before each op
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut a, mut b) = (0, 0);
+    for op in &ops {
+        positions.push((a, b));
+        match op {
+            DiffOp::Equal(_, _) => {
+                a += 1;
+                b += 1;
+            }
+            DiffOp::Delete(_) => a += 1,
+            DiffOp::Insert(_) => b += 1,
+        }
+    }
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(_, _)))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let mut hunks = Vec::new();
+    let mut idx = 0;
+    while idx < changes.len() {
+        let first = changes[idx];
+        let mut last = first;
+        while idx + 1 < changes.len() && changes[idx + 1] - last <= 2 * context + 1 {
+            idx += 1;
+            last = changes[idx];
+        }
+        idx += 1;
+
+        let start = first.saturating_sub(context);
+        let end = (last + context + 1).min(ops.len());
+        let mut hunk = Hunk {
+            old_start: positions[start].0,
+            old_len: 0,
+            new_start: positions[start].1,
+            new_len: 0,
+            lines: Vec::new(),
+        };
+        for op in &ops[start..end] {
+            match *op {
+                DiffOp::Equal(i, _) => {
+                    hunk.old_len += 1;
+                    hunk.new_len += 1;
+                    hunk.lines.push((' ', old_lines[i].to_string()));
+                }
+                DiffOp::Delete(i) => {
+                    hunk.old_len += 1;
+                    hunk.lines.push(('-', old_lines[i].to_string()));
+                }
+                DiffOp::Insert(j) => {
+                    hunk.new_len += 1;
+                    hunk.lines.push(('+', new_lines[j].to_string()));
+                }
+            }
+        }
+        hunks.push(hunk);
+    }
+    hunks
+}
+
+/// Applies hunks of changes to `text`, ordered by their position in
+/// it. A hunk may share leading context lines with the hunk before it,
+/// as hunks from `Hunk::split` do.
+pub fn apply_hunks(text: &str, hunks: &[Hunk]) -> String {
+    let lines = split_lines(text);
+    let mut result = String::new();
+    let mut cursor = 0;
+    for hunk in hunks {
+        while cursor < hunk.old_start.min(lines.len()) {
+            result.push_str(lines[cursor]);
+            cursor += 1;
+        }
+        let mut position = hunk.old_start;
+        for (tag, line) in &hunk.lines {
+            match tag {
+                '+' => result.push_str(line),
+                // Context the previous hunk already covered
+                _ if position < cursor => position += 1,
+                tag => {
+                    if *tag == ' ' {
+                        result.push_str(line);
+                    }
+                    position += 1;
+                    cursor = position;
+                }
+            }
+        }
+    }
+    for line in lines.iter().skip(cursor) {
+        result.push_str(line);
+    }
+    result
+}
+
+/// Formats the difference of two texts as unified diff. Returns an
+/// empty string if both texts are equal.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let hunks = hunks(old, new, DEFAULT_CONTEXT);
+    if hunks.is_empty() {
+        return String::new();
+    }
+    let mut patch = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for hunk in hunks {
+        patch.push_str(&hunk.to_patch());
+    }
+    patch
+}
+
+/// Colors the lines of a patch the way git does on a terminal.
+pub fn colorize(patch: &str) -> String {
+    let lines: Vec<String> = patch
+        .lines()
+        .map(|line| {
+            if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+                line.bold().to_string()
+            } else if line.starts_with('+') {
+                line.green().to_string()
+            } else if line.starts_with('-') {
+                line.red().to_string()
+            } else if line.starts_with("@@") {
+                line.cyan().to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    let mut colored = lines.join("\n");
+    if patch.ends_with('\n') {
+        colored.push('\n');
+    }
+    colored
+}
@@ -11,7 +11,7 @@ impl DisplayStrings {
     pub fn new(offset: usize, color: &str) -> Self {
         Self {
             strings: Vec::new(),
-            offset: offset,
+            offset,
             color: String::from(color),
         }
     }
@@ -38,15 +38,13 @@ impl DisplayStrings {
 
 impl Display for DisplayStrings {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.strings.iter().fold(Ok(()), |result, string| {
-            result.and_then(|_| {
-                writeln!(
-                    f,
-                    "{}{}",
-                    " ".repeat(self.offset),
-                    string.as_str().color(&*self.color)
-                )
-            })
+        self.strings.iter().try_for_each(|string| {
+            writeln!(
+                f,
+                "{}{}",
+                " ".repeat(self.offset),
+                string.as_str().color(&*self.color)
+            )
         })
     }
 }
@@ -0,0 +1,38 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use super::errors::NyxError;
+
+/// Returns the editor command configured by `NYX_EDITOR`, `VISUAL`
+/// or `EDITOR`, falling back to `vi`.
+pub fn editor_command() -> String {
+    ["NYX_EDITOR", "VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+/// Opens `path` in the editor and waits until it is closed.
+pub fn edit_file(path: &Path) -> Result<(), NyxError> {
+    let editor = editor_command();
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(&editor)
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        return Err(NyxError::Usage(format!(
+            "there was a problem with the editor '{}'",
+            editor
+        )));
+    }
+    Ok(())
+}
+
+/// Removes comment lines starting with `#` and surrounding blank lines.
+pub fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.starts_with('#')).collect();
+    lines.join("\n").trim().to_string()
+}
@@ -1,10 +1,40 @@
-use std::{io, str, string};
+use std::{fmt, io, str, string};
 
 #[derive(Debug)]
 pub enum NyxError {
     IoError(io::Error),
     Utf8Error(str::Utf8Error),
     FromUtf8Error(string::FromUtf8Error),
+    InvalidRevision(String),
+    InvalidObject(String),
+    InvalidRef(String),
+    MergeConflict(Vec<String>),
+    LocalChanges(Vec<String>),
+    Usage(String),
+    HookFailed(String),
+}
+
+impl fmt::Display for NyxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NyxError::IoError(err) => write!(f, "{}", err),
+            NyxError::Utf8Error(err) => write!(f, "{}", err),
+            NyxError::FromUtf8Error(err) => write!(f, "{}", err),
+            NyxError::InvalidRevision(rev) => write!(f, "unknown revision '{}'", rev),
+            NyxError::InvalidObject(hash) => write!(f, "invalid object '{}'", hash),
+            NyxError::InvalidRef(name) => write!(f, "invalid ref '{}'", name),
+            NyxError::MergeConflict(paths) => {
+                write!(f, "merge conflict in: {}", paths.join(", "))
+            }
+            NyxError::LocalChanges(paths) => write!(
+                f,
+                "your local changes to the following files would be overwritten: {}",
+                paths.join(", ")
+            ),
+            NyxError::Usage(message) => write!(f, "{}", message),
+            NyxError::HookFailed(name) => write!(f, "the '{}' hook failed", name),
+        }
+    }
 }
 
 impl From<io::Error> for NyxError {
@@ -2,7 +2,7 @@ use format_bytes::format_bytes;
 use std::env;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::traits::Byte;
 
@@ -11,6 +11,12 @@ pub struct NyxFileSystem {
     is_repo: bool,
 }
 
+impl Default for NyxFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NyxFileSystem {
     fn nyx_dir() -> String {
         String::from(".nyx")
@@ -32,6 +38,26 @@ impl NyxFileSystem {
         String::from("index")
     }
 
+    fn logs_dir() -> String {
+        String::from("logs")
+    }
+
+    fn config_file() -> String {
+        String::from("config")
+    }
+
+    fn sequencer_dir() -> String {
+        String::from("sequencer")
+    }
+
+    fn rebase_dir() -> String {
+        String::from("rebase-merge")
+    }
+
+    fn hooks_dir() -> String {
+        String::from("hooks")
+    }
+
     pub fn new() -> Self {
         let mut path = env::current_dir().unwrap();
         let mut root_dir = "";
@@ -98,10 +124,58 @@ impl NyxFileSystem {
         self.get_repo_dir().join(NyxFileSystem::index_file())
     }
 
-    pub fn write_contents<T: Byte>(&self, content: &Vec<T>, path: &str) {
+    pub fn get_config_path(&self) -> PathBuf {
+        self.get_repo_dir().join(NyxFileSystem::config_file())
+    }
+
+    /// Returns the path of a state file directly inside of the
+    /// .nyx directory, e.g. `CHERRY_PICK_HEAD` or `MERGE_MSG`.
+    pub fn get_state_file_path(&self, name: &str) -> PathBuf {
+        self.get_repo_dir().join(name)
+    }
+
+    /// Returns the directory in which an interrupted cherry-pick
+    /// or revert keeps its remaining work.
+    pub fn get_sequencer_dir_path(&self) -> PathBuf {
+        self.get_repo_dir().join(NyxFileSystem::sequencer_dir())
+    }
+
+    /// Returns the directory in which a rebase keeps its progress.
+    pub fn get_rebase_dir_path(&self) -> PathBuf {
+        self.get_repo_dir().join(NyxFileSystem::rebase_dir())
+    }
+
+    /// Returns the directory of the executable hook scripts.
+    pub fn get_hooks_dir_path(&self) -> PathBuf {
+        self.get_repo_dir().join(NyxFileSystem::hooks_dir())
+    }
+
+    /// Returns the path of a ref file, e.g. `master` or `stash`
+    /// resolve to files inside of the refs directory.
+    pub fn get_ref_path(&self, name: &str) -> PathBuf {
+        self.get_refs_dir_path().join(name)
+    }
+
+    pub fn get_logs_dir_path(&self) -> PathBuf {
+        self.get_repo_dir().join(NyxFileSystem::logs_dir())
+    }
+
+    /// Returns the path of the reflog of a ref. The reflog of HEAD
+    /// lives in `logs/HEAD`, all others in `logs/refs/<name>`.
+    pub fn get_reflog_path(&self, name: &str) -> PathBuf {
+        let logs_dir = self.get_logs_dir_path();
+        if name == NyxFileSystem::head_file() {
+            logs_dir.join(name)
+        } else {
+            logs_dir.join(NyxFileSystem::refs_dir()).join(name)
+        }
+    }
+
+    pub fn write_contents<T: Byte>(&self, content: &[T], path: &str) {
         let mut file = fs::OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .open(path)
             .unwrap();
 
@@ -125,13 +199,13 @@ impl NyxFileSystem {
         ignored
     }
 
-    pub fn is_ignored(&self, path: &PathBuf) -> bool {
+    pub fn is_ignored(&self, path: &Path) -> bool {
         let ignored = self.get_ignored_files();
         for ignore in ignored {
             if path.ends_with(ignore) {
                 return true;
             }
         }
-        return false;
+        false
     }
 }
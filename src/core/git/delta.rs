@@ -0,0 +1,74 @@
+use crate::core::errors::NyxError;
+
+fn invalid() -> NyxError {
+    NyxError::InvalidObject(String::from("malformed git delta"))
+}
+
+/// Reads a little-endian base-128 number as used for the sizes in the
+/// delta header.
+fn read_size(delta: &[u8], pos: &mut usize) -> Result<usize, NyxError> {
+    let mut size = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *delta.get(*pos).ok_or_else(invalid)?;
+        *pos += 1;
+        if shift >= usize::BITS {
+            return Err(invalid());
+        }
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok(size);
+        }
+    }
+}
+
+/// Rebuilds an object from its base and a git delta. The delta starts
+/// with the sizes of base and result, followed by instructions that either
+/// copy a range of the base or insert the bytes that follow them.
+pub fn apply(base: &[u8], delta: &[u8]) -> Result<Vec<u8>, NyxError> {
+    let mut pos = 0;
+    if read_size(delta, &mut pos)? != base.len() {
+        return Err(invalid());
+    }
+    let size = read_size(delta, &mut pos)?;
+    // The header is not to be trusted, reserve what the delta plausibly makes up
+    let mut result = Vec::with_capacity(size.min(base.len() + delta.len().saturating_mul(8)));
+
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+        if op & 0x80 != 0 {
+            // The low four bits select the offset bytes, the next three
+            // the size bytes that are present
+            let mut fields = [0usize; 2];
+            let mut bit = 0;
+            for (field, bytes) in fields.iter_mut().zip([4, 3]) {
+                for shift in 0..bytes {
+                    if op & (1 << bit) != 0 {
+                        let byte = *delta.get(pos).ok_or_else(invalid)?;
+                        pos += 1;
+                        *field |= (byte as usize) << (8 * shift);
+                    }
+                    bit += 1;
+                }
+            }
+            let [offset, length] = fields;
+            let length = if length == 0 { 0x10000 } else { length };
+            let range = base.get(offset..offset + length).ok_or_else(invalid)?;
+            result.extend_from_slice(range);
+        } else if op != 0 {
+            let length = op as usize;
+            let range = delta.get(pos..pos + length).ok_or_else(invalid)?;
+            result.extend_from_slice(range);
+            pos += length;
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    if result.len() != size {
+        return Err(invalid());
+    }
+    Ok(result)
+}
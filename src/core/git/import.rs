@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+use crate::core::shared::generate_object;
+
+use super::pack::GitObject;
+use super::repository::GitRepository;
+
+const TREE_MODE: &str = "40000";
+const GITLINK_MODE: &str = "160000";
+
+struct GitTreeEntry {
+    mode: String,
+    name: String,
+    hash: String,
+}
+
+fn invalid(hash: &str) -> NyxError {
+    NyxError::InvalidObject(format!("git object {}", hash))
+}
+
+/// Entries of a binary git tree, each `<mode> <name>\0<20 byte hash>`.
+fn tree_entries(hash: &str, data: &[u8]) -> Result<Vec<GitTreeEntry>, NyxError> {
+    let mut entries = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest
+            .iter()
+            .position(|b| *b == b' ')
+            .ok_or_else(|| invalid(hash))?;
+        let nul = rest
+            .iter()
+            .position(|b| *b == 0)
+            .filter(|nul| space < *nul)
+            .ok_or_else(|| invalid(hash))?;
+        let id = rest.get(nul + 1..nul + 21).ok_or_else(|| invalid(hash))?;
+        entries.push(GitTreeEntry {
+            mode: String::from_utf8_lossy(&rest[..space]).to_string(),
+            name: String::from_utf8_lossy(&rest[space + 1..nul]).to_string(),
+            hash: hex::encode(id),
+        });
+        rest = &rest[nul + 21..];
+    }
+    Ok(entries)
+}
+
+/// Header lines and message of a commit or tag. Continuation lines of
+/// multi-line headers like `gpgsig` start with a space and are dropped.
+fn split_headers(data: &[u8]) -> (Vec<(String, String)>, String) {
+    let content = String::from_utf8_lossy(data);
+    let (headers, message) = content.split_once("\n\n").unwrap_or((&content, ""));
+    let headers = headers
+        .lines()
+        .filter(|line| !line.starts_with(' '))
+        .filter_map(|line| line.split_once(' '))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    (headers, message.trim_end().to_string())
+}
+
+/// Converts git objects into nyx objects. Blobs keep their hash in SHA-1
+/// repositories, all other objects get new hashes because the formats
+/// differ, so every object is converted after the objects it references.
+pub struct Importer<'a> {
+    git: &'a GitRepository,
+    mapping: HashMap<String, String>,
+    counts: BTreeMap<String, usize>,
+    skipped: BTreeSet<String>,
+}
+
+impl<'a> Importer<'a> {
+    pub fn new(git: &'a GitRepository) -> Self {
+        Self {
+            git,
+            mapping: HashMap::new(),
+            counts: BTreeMap::new(),
+            skipped: BTreeSet::new(),
+        }
+    }
+
+    /// Maps git hashes to the hashes of the converted objects.
+    pub fn mapping(&self) -> &HashMap<String, String> {
+        &self.mapping
+    }
+
+    /// Number of converted objects per type name.
+    pub fn counts(&self) -> &BTreeMap<String, usize> {
+        &self.counts
+    }
+
+    /// Tree entries nyx cannot represent, e.g. submodules.
+    pub fn skipped(&self) -> &BTreeSet<String> {
+        &self.skipped
+    }
+
+    fn references(&self, hash: &str, object: &GitObject) -> Result<Vec<String>, NyxError> {
+        let (object_type, data) = object;
+        Ok(match object_type {
+            NyxObjectType::Blob => Vec::new(),
+            NyxObjectType::Tree => tree_entries(hash, data)?
+                .into_iter()
+                .filter(|entry| entry.mode != GITLINK_MODE)
+                .map(|entry| entry.hash)
+                .collect(),
+            NyxObjectType::Commit | NyxObjectType::Tag => split_headers(data)
+                .0
+                .into_iter()
+                .filter(|(key, _)| key == "tree" || key == "parent" || key == "object")
+                .map(|(_, value)| value)
+                .collect(),
+        })
+    }
+
+    fn mapped(&self, hash: &str) -> Result<&str, NyxError> {
+        self.mapping
+            .get(hash)
+            .map(|hash| hash.as_str())
+            .ok_or_else(|| invalid(hash))
+    }
+
+    /// Content of the nyx object, in the format `Tree`, `Commit` and
+    /// `Tag` write themselves.
+    fn convert(&mut self, hash: &str, object: &GitObject) -> Result<Vec<u8>, NyxError> {
+        let (object_type, data) = object;
+        let content = match object_type {
+            NyxObjectType::Blob => return Ok(data.to_vec()),
+            NyxObjectType::Tree => {
+                let mut content = String::new();
+                for entry in tree_entries(hash, data)? {
+                    if entry.mode == GITLINK_MODE {
+                        self.skipped.insert(format!("submodule '{}'", entry.name));
+                        continue;
+                    }
+                    if entry.name.contains(char::is_whitespace) {
+                        self.skipped
+                            .insert(format!("'{}', names with whitespace", entry.name));
+                        continue;
+                    }
+                    let entry_type = match entry.mode.as_str() {
+                        TREE_MODE => NyxObjectType::Tree,
+                        _ => NyxObjectType::Blob,
+                    };
+                    let entry_hash = self.mapped(&entry.hash)?;
+                    content.push_str(&format!("{} {} {}\n", entry_type, entry_hash, entry.name));
+                }
+                content
+            }
+            NyxObjectType::Commit => {
+                let (headers, message) = split_headers(data);
+                let mut content = String::new();
+                for (key, value) in headers {
+                    match key.as_str() {
+                        "tree" | "parent" => {
+                            content.push_str(&format!("{} {}\n", key, self.mapped(&value)?))
+                        }
+                        "author" | "committer" => content.push_str(&format!("{} {}\n", key, value)),
+                        _ => (),
+                    }
+                }
+                if !message.is_empty() {
+                    content.push_str(&format!("\n{}\n", message));
+                }
+                content
+            }
+            NyxObjectType::Tag => {
+                let (headers, message) = split_headers(data);
+                let mut content = String::new();
+                for (key, value) in headers {
+                    match key.as_str() {
+                        "object" => content.push_str(&format!("object {}\n", self.mapped(&value)?)),
+                        "type" | "tag" | "tagger" => {
+                            content.push_str(&format!("{} {}\n", key, value))
+                        }
+                        _ => (),
+                    }
+                }
+                format!("{}\n{}\n", content, message)
+            }
+        };
+        Ok(content.into_bytes())
+    }
+
+    /// Converts the object and everything it references, returns the
+    /// nyx hash of the object.
+    pub fn import(&mut self, hash: &str) -> Result<String, NyxError> {
+        // Objects are expanded on the first visit and converted on the
+        // second, once everything they reference has been converted
+        let mut pending: HashMap<String, GitObject> = HashMap::new();
+        let mut stack: Vec<(String, bool)> = vec![(hash.to_string(), false)];
+        while let Some((hash, expanded)) = stack.pop() {
+            if self.mapping.contains_key(&hash) {
+                continue;
+            }
+            if !expanded {
+                if pending.contains_key(&hash) {
+                    continue;
+                }
+                let object = self.git.read_object(&hash)?;
+                stack.push((hash.clone(), true));
+                for reference in self.references(&hash, &object)? {
+                    if !self.mapping.contains_key(&reference) {
+                        stack.push((reference, false));
+                    }
+                }
+                pending.insert(hash, object);
+                continue;
+            }
+
+            let object = pending.remove(&hash).ok_or_else(|| invalid(&hash))?;
+            let content = self.convert(&hash, &object)?;
+            let nyx_hash = generate_object(&content, object.0);
+            *self.counts.entry(object.0.to_string()).or_default() += 1;
+            self.mapping.insert(hash, nyx_hash);
+        }
+        self.mapped(hash).map(|hash| hash.to_string())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod delta;
+pub mod import;
+pub mod pack;
+pub mod repository;
@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::rc::Rc;
+
+use flate2::read::ZlibDecoder;
+
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+
+use super::delta;
+
+const INDEX_MAGIC: &[u8] = b"\xfftOc";
+const OFS_DELTA: u8 = 6;
+const REF_DELTA: u8 = 7;
+/// Number of resolved objects kept as delta bases.
+const CACHE_SIZE: usize = 1024;
+
+pub type GitObject = (NyxObjectType, Rc<Vec<u8>>);
+
+fn invalid(path: &Path) -> NyxError {
+    NyxError::InvalidObject(format!("malformed git pack '{}'", path.display()))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(pos..pos + 8)?.try_into().ok()?))
+}
+
+/// Inflates the zlib stream at the start of `data`, ignoring what follows.
+/// The size hint only reserves up to a multiple of the compressed data.
+pub fn inflate(data: &[u8], size_hint: usize) -> Result<Vec<u8>, NyxError> {
+    let mut content = Vec::with_capacity(size_hint.min(data.len().saturating_mul(8)));
+    ZlibDecoder::new(data).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// A git packfile together with the offsets of its objects, read from
+/// the `.idx` file next to it.
+pub struct PackFile {
+    path: String,
+    data: Vec<u8>,
+    offsets: HashMap<String, usize>,
+    cache: RefCell<HashMap<usize, GitObject>>,
+    /// Offsets whose delta chain is being resolved, to catch cycles.
+    resolving: RefCell<HashSet<usize>>,
+}
+
+impl PackFile {
+    pub fn open(pack_path: &Path) -> Result<Self, NyxError> {
+        let data = fs::read(pack_path)?;
+        if !data.starts_with(b"PACK") {
+            return Err(invalid(pack_path));
+        }
+        let index = fs::read(pack_path.with_extension("idx"))?;
+        let offsets = PackFile::read_index(&index).ok_or_else(|| invalid(pack_path))?;
+        Ok(Self {
+            path: pack_path.display().to_string(),
+            data,
+            offsets,
+            cache: RefCell::new(HashMap::new()),
+            resolving: RefCell::new(HashSet::new()),
+        })
+    }
+
+    /// Parses version 1 and 2 pack indexes into hash to offset pairs.
+    fn read_index(index: &[u8]) -> Option<HashMap<String, usize>> {
+        let mut offsets = HashMap::new();
+        if index.starts_with(INDEX_MAGIC) {
+            if read_u32(index, 4)? != 2 {
+                return None;
+            }
+            let count = read_u32(index, 8 + 255 * 4)? as usize;
+            let names = 8 + 256 * 4;
+            let small_offsets = names + count * 20 + count * 4;
+            let large_offsets = small_offsets + count * 4;
+            for i in 0..count {
+                let hash = hex::encode(index.get(names + i * 20..names + (i + 1) * 20)?);
+                let offset = read_u32(index, small_offsets + i * 4)?;
+                let offset = match offset & 0x8000_0000 {
+                    0 => offset as u64,
+                    _ => read_u64(index, large_offsets + (offset & 0x7fff_ffff) as usize * 8)?,
+                };
+                offsets.insert(hash, offset as usize);
+            }
+        } else {
+            let count = read_u32(index, 255 * 4)? as usize;
+            let entries = 256 * 4;
+            for i in 0..count {
+                let entry = entries + i * 24;
+                let offset = read_u32(index, entry)?;
+                let hash = hex::encode(index.get(entry + 4..entry + 24)?);
+                offsets.insert(hash, offset as usize);
+            }
+        }
+        Some(offsets)
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.offsets.contains_key(hash)
+    }
+
+    /// Reads an object of this pack. Deltas against objects outside of
+    /// the pack get their base from `external`.
+    pub fn read(
+        &self,
+        hash: &str,
+        external: &dyn Fn(&str) -> Result<GitObject, NyxError>,
+    ) -> Result<GitObject, NyxError> {
+        match self.offsets.get(hash) {
+            Some(offset) => self.read_at(*offset, external),
+            None => Err(NyxError::InvalidObject(hash.to_string())),
+        }
+    }
+
+    fn read_at(
+        &self,
+        offset: usize,
+        external: &dyn Fn(&str) -> Result<GitObject, NyxError>,
+    ) -> Result<GitObject, NyxError> {
+        if let Some(object) = self.cache.borrow().get(&offset) {
+            return Ok(object.clone());
+        }
+        // A delta that is its own base, directly or through other deltas
+        if !self.resolving.borrow_mut().insert(offset) {
+            return Err(invalid(Path::new(&self.path)));
+        }
+        let object = self.read_entry(offset, external);
+        self.resolving.borrow_mut().remove(&offset);
+        let object = object?;
+
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(offset, object.clone());
+        Ok(object)
+    }
+
+    fn read_entry(
+        &self,
+        offset: usize,
+        external: &dyn Fn(&str) -> Result<GitObject, NyxError>,
+    ) -> Result<GitObject, NyxError> {
+        let invalid = || invalid(Path::new(&self.path));
+        let byte_at = |pos: usize| self.data.get(pos).copied().ok_or_else(invalid);
+
+        // Type and size of the entry, the size continues in 7 bit steps
+        let mut pos = offset;
+        let mut byte = byte_at(pos)?;
+        pos += 1;
+        let kind = (byte >> 4) & 0x7;
+        let mut size = (byte & 0x0f) as usize;
+        let mut shift = 4;
+        while byte & 0x80 != 0 {
+            byte = byte_at(pos)?;
+            pos += 1;
+            if shift >= usize::BITS {
+                return Err(invalid());
+            }
+            size |= ((byte & 0x7f) as usize) << shift;
+            shift += 7;
+        }
+
+        let object = match kind {
+            OFS_DELTA => {
+                byte = byte_at(pos)?;
+                pos += 1;
+                let mut distance = (byte & 0x7f) as usize;
+                while byte & 0x80 != 0 {
+                    byte = byte_at(pos)?;
+                    pos += 1;
+                    distance = distance
+                        .checked_add(1)
+                        .filter(|distance| distance.leading_zeros() >= 7)
+                        .ok_or_else(invalid)?
+                        << 7
+                        | (byte & 0x7f) as usize;
+                }
+                let base_offset = offset
+                    .checked_sub(distance)
+                    .filter(|_| distance > 0)
+                    .ok_or_else(invalid)?;
+                let (object_type, base) = self.read_at(base_offset, external)?;
+                let content = delta::apply(&base, &inflate(&self.data[pos..], size)?)?;
+                (object_type, Rc::new(content))
+            }
+            REF_DELTA => {
+                let base_hash = hex::encode(self.data.get(pos..pos + 20).ok_or_else(invalid)?);
+                pos += 20;
+                let (object_type, base) = match self.offsets.get(&base_hash) {
+                    Some(base_offset) => self.read_at(*base_offset, external)?,
+                    None => external(&base_hash)?,
+                };
+                let content = delta::apply(&base, &inflate(&self.data[pos..], size)?)?;
+                (object_type, Rc::new(content))
+            }
+            kind => {
+                let object_type = match kind {
+                    1 => NyxObjectType::Commit,
+                    2 => NyxObjectType::Tree,
+                    3 => NyxObjectType::Blob,
+                    4 => NyxObjectType::Tag,
+                    _ => return Err(invalid()),
+                };
+                (object_type, Rc::new(inflate(&self.data[pos..], size)?))
+            }
+        };
+        Ok(object)
+    }
+}
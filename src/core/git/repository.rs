@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+
+use super::pack::{inflate, GitObject, PackFile};
+
+const SYMBOLIC_REF_PREFIX: &str = "ref: ";
+
+/// Read access to the objects and refs of a git repository.
+pub struct GitRepository {
+    git_dir: PathBuf,
+    packs: Vec<PackFile>,
+}
+
+impl GitRepository {
+    /// Opens a `.git` directory, a bare repository or a working tree
+    /// containing a `.git` directory.
+    pub fn open(path: &Path) -> Result<Self, NyxError> {
+        let git_dir = if path.join(".git").is_dir() {
+            path.join(".git")
+        } else {
+            path.to_path_buf()
+        };
+        if !git_dir.join("objects").is_dir() || !git_dir.join("HEAD").is_file() {
+            return Err(NyxError::Usage(format!(
+                "'{}' is not a git repository",
+                path.display()
+            )));
+        }
+
+        let mut pack_paths: Vec<PathBuf> = fs::read_dir(git_dir.join("objects").join("pack"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "pack"))
+            .collect();
+        pack_paths.sort();
+        let packs = pack_paths
+            .iter()
+            .map(|path| PackFile::open(path))
+            .collect::<Result<Vec<PackFile>, NyxError>>()?;
+
+        Ok(Self { git_dir, packs })
+    }
+
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    fn loose_object_path(&self, hash: &str) -> PathBuf {
+        self.git_dir
+            .join("objects")
+            .join(&hash[..2])
+            .join(&hash[2..])
+    }
+
+    fn read_loose(&self, hash: &str) -> Result<Option<GitObject>, NyxError> {
+        let path = self.loose_object_path(hash);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let raw = inflate(&fs::read(path)?, 0)?;
+        let invalid = || NyxError::InvalidObject(format!("git object {}", hash));
+        let nul = raw.iter().position(|b| *b == 0).ok_or_else(invalid)?;
+        let header = std::str::from_utf8(&raw[..nul]).map_err(|_| invalid())?;
+        let object_type = header
+            .split(' ')
+            .next()
+            .and_then(NyxObjectType::parse)
+            .ok_or_else(invalid)?;
+        Ok(Some((object_type, Rc::new(raw[nul + 1..].to_vec()))))
+    }
+
+    pub fn read_object(&self, hash: &str) -> Result<GitObject, NyxError> {
+        if hash.len() != 40 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(NyxError::InvalidObject(hash.to_string()));
+        }
+        if let Some(object) = self.read_loose(hash)? {
+            return Ok(object);
+        }
+        match self.packs.iter().find(|pack| pack.contains(hash)) {
+            Some(pack) => pack.read(hash, &|base| self.read_object(base)),
+            None => Err(NyxError::InvalidObject(format!(
+                "git object {} is missing",
+                hash
+            ))),
+        }
+    }
+
+    fn read_ref_file(&self, name: &str) -> Option<String> {
+        let content = fs::read_to_string(self.git_dir.join(name)).ok()?;
+        Some(content.trim().to_string())
+    }
+
+    /// Follows symbolic refs down to an object hash.
+    fn resolve_ref(&self, name: &str, packed: &[(String, String)]) -> Option<String> {
+        let mut name = name.to_string();
+        for _ in 0..10 {
+            let value = match self.read_ref_file(&name) {
+                Some(value) => value,
+                None => packed.iter().find(|(n, _)| *n == name)?.1.clone(),
+            };
+            match value.strip_prefix(SYMBOLIC_REF_PREFIX) {
+                Some(target) => name = target.to_string(),
+                None => return Some(value),
+            }
+        }
+        None
+    }
+
+    fn packed_refs(&self) -> Vec<(String, String)> {
+        let content = fs::read_to_string(self.git_dir.join("packed-refs")).unwrap_or_default();
+        content
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .filter_map(|line| line.split_once(' '))
+            .map(|(hash, name)| (name.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    /// All refs below `refs/` with their full names, e.g.
+    /// `refs/heads/main`, sorted by name.
+    pub fn refs(&self) -> Vec<(String, String)> {
+        let packed = self.packed_refs();
+        let mut names: Vec<String> = packed.iter().map(|(name, _)| name.clone()).collect();
+        let mut dirs = vec![self.git_dir.join("refs")];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(name) = path.strip_prefix(&self.git_dir) {
+                    names.push(name.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let hash = self.resolve_ref(&name, &packed)?;
+                Some((name, hash))
+            })
+            .collect()
+    }
+
+    /// The ref HEAD points to, `None` if it is detached.
+    pub fn head_ref(&self) -> Option<String> {
+        let head = self.read_ref_file("HEAD")?;
+        head.strip_prefix(SYMBOLIC_REF_PREFIX)
+            .map(|name| name.to_string())
+    }
+}
@@ -0,0 +1,90 @@
+/// Draws the ASCII history graph of `log --graph` one commit at a time.
+/// Every column is a line of history waiting for the commit with the
+/// hash stored in it.
+#[derive(Debug, Default)]
+pub struct Graph {
+    columns: Vec<String>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the line with the `*` marking the commit and, if lines
+    /// of history fork, join or shift, a line drawing the transition.
+    /// `parents` are the parents the graph continues with.
+    pub fn next(&mut self, hash: &str, parents: &[String]) -> (String, Option<String>) {
+        let hash = hash.to_string();
+        let index = match self.columns.iter().position(|c| *c == hash) {
+            Some(index) => index,
+            None => {
+                self.columns.push(hash.clone());
+                self.columns.len() - 1
+            }
+        };
+
+        let marker: Vec<&str> = (0..self.columns.len())
+            .map(|i| if i == index { "*" } else { "|" })
+            .collect();
+
+        // Each edge goes from an old column to the hash it continues with
+        let mut edges: Vec<(usize, String)> = Vec::new();
+        let mut columns: Vec<String> = Vec::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            let targets = if i == index {
+                parents.to_vec()
+            } else if *column == hash {
+                // Another line waiting for this commit joins the marker
+                edges.push((i, hash.clone()));
+                continue;
+            } else {
+                vec![column.clone()]
+            };
+            for target in targets {
+                if !columns.contains(&target) {
+                    columns.push(target.clone());
+                }
+                edges.push((i, target));
+            }
+        }
+
+        let width = 2 * self.columns.len().max(columns.len());
+        let mut transition = vec![' '; width];
+        let mut straight = true;
+        for (from, target) in &edges {
+            let to = if *target == hash {
+                index
+            } else {
+                columns.iter().position(|c| c == target).unwrap_or(*from)
+            };
+            if to == *from {
+                transition[2 * from] = '|';
+            } else if to > *from {
+                transition[2 * from + 1] = '\\';
+                straight = false;
+            } else {
+                transition[2 * from - 1] = '/';
+                straight = false;
+            }
+        }
+
+        self.columns = columns;
+        let transition = match straight {
+            true => None,
+            false => Some(
+                transition
+                    .into_iter()
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string(),
+            ),
+        };
+        (marker.join(" "), transition)
+    }
+
+    /// Prefix for the lines following a commit line, e.g. its message.
+    pub fn padding(&self) -> String {
+        vec!["|"; self.columns.len()].join(" ")
+    }
+}
@@ -0,0 +1,51 @@
+use std::fs::{self, Metadata};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+use super::errors::NyxError;
+use crate::FILE_SYSTEM;
+
+pub const PRE_COMMIT: &str = "pre-commit";
+pub const COMMIT_MSG: &str = "commit-msg";
+pub const POST_COMMIT: &str = "post-commit";
+pub const POST_CHECKOUT: &str = "post-checkout";
+
+#[cfg(unix)]
+fn is_executable(meta: &Metadata) -> bool {
+    meta.is_file() && meta.permissions().mode() & 0o111 != 0
+}
+
+/// Without execute bits every file counts as executable.
+#[cfg(not(unix))]
+fn is_executable(meta: &Metadata) -> bool {
+    meta.is_file()
+}
+
+/// Runs the executable `.nyx/hooks/<name>` from the root of the working
+/// tree. Returns `None` if there is no such hook, otherwise whether it
+/// exited successfully.
+pub fn run_hook(name: &str, args: &[&str]) -> Result<Option<bool>, NyxError> {
+    let path = FILE_SYSTEM.get_hooks_dir_path().join(name);
+    let executable = fs::metadata(&path)
+        .map(|meta| is_executable(&meta))
+        .unwrap_or(false);
+    if !executable {
+        return Ok(None);
+    }
+
+    let status = Command::new(&path)
+        .args(args)
+        .current_dir(FILE_SYSTEM.get_root_dir())
+        .env("NYX_DIR", FILE_SYSTEM.get_repo_dir())
+        .status()?;
+    Ok(Some(status.success()))
+}
+
+/// Runs a hook that may abort the current operation.
+pub fn run_pre_hook(name: &str, args: &[&str]) -> Result<(), NyxError> {
+    match run_hook(name, args)? {
+        Some(false) => Err(NyxError::HookFailed(name.to_string())),
+        _ => Ok(()),
+    }
+}
@@ -1,10 +1,11 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NyxFileState {
     Invalid = 0,
     Unstaged = 1,
     Staged = 2,
     Modified = 3,
     Committed = 4,
+    Unmerged = 5,
 }
 
 // TODO: Search for safe approach
@@ -15,6 +16,7 @@ impl NyxFileState {
             2 => NyxFileState::Staged,
             3 => NyxFileState::Modified,
             4 => NyxFileState::Committed,
+            5 => NyxFileState::Unmerged,
             _ => NyxFileState::Invalid,
         }
     }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::{fs, vec};
 
@@ -16,6 +17,12 @@ pub struct Index {
     entries: Vec<IndexEntry>,
 }
 
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Index {
     pub fn new() -> Self {
         let path = FILE_SYSTEM.get_index_path();
@@ -24,7 +31,7 @@ impl Index {
             let content = fs::read_to_string(&path).unwrap();
             let raw_entries: Vec<String> = content
                 .split('\n')
-                .map(|val| String::from(val))
+                .map(String::from)
                 .filter(|val| !val.is_empty())
                 .collect();
             for entry in raw_entries {
@@ -43,11 +50,11 @@ impl Index {
     }
 
     pub fn add(&mut self, hash: &str, path: &str) -> Result<(), NyxError> {
-        if self.contains_hash(&hash) {
+        if self.contains_resolved(hash, path) {
             return Ok(());
         }
 
-        self.entries.drain_filter(|entry| entry.path == path);
+        self.entries.retain(|entry| entry.path != path);
 
         self.entries.push(IndexEntry {
             hash: hash.to_string(),
@@ -55,7 +62,7 @@ impl Index {
             state: NyxFileState::Staged,
         });
 
-        FILE_SYSTEM.write_contents(&self.entries, &self.path.to_str().unwrap());
+        FILE_SYSTEM.write_contents(&self.entries, self.path.to_str().unwrap());
         Ok(())
     }
 
@@ -63,18 +70,38 @@ impl Index {
         self.entries.sort_by(|e1, e2| e1.path.cmp(&e2.path));
         // TODO: Check for errors befor writing
         self.mark_as_committed_and_write();
-        let tree = Index::write_tree_recursiv(&mut self.entries);
-        tree
+        Index::write_tree_recursiv(&mut self.entries)
+    }
+
+    /// Writes the tree objects for the current entries without
+    /// changing their state.
+    pub fn to_tree(&self) -> Tree {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|e1, e2| e1.path.cmp(&e2.path));
+        Index::write_tree_recursiv(&mut entries)
+    }
+
+    /// Writes the tree objects for arbitrary paths mapped to blob hashes.
+    pub fn write_tree_from_map(paths: &BTreeMap<String, String>) -> Tree {
+        let mut entries: Vec<IndexEntry> = paths
+            .iter()
+            .map(|(path, hash)| IndexEntry {
+                hash: hash.clone(),
+                path: path.clone(),
+                state: NyxFileState::Committed,
+            })
+            .collect();
+        Index::write_tree_recursiv(&mut entries)
     }
 
     fn mark_as_committed_and_write(&mut self) {
-        for mut entry in &mut self.entries {
+        for entry in &mut self.entries {
             entry.state = NyxFileState::Committed;
         }
-        FILE_SYSTEM.write_contents(&self.entries, &self.path.to_str().unwrap());
+        FILE_SYSTEM.write_contents(&self.entries, self.path.to_str().unwrap());
     }
 
-    fn write_tree_recursiv(index: &mut Vec<IndexEntry>) -> Tree {
+    fn write_tree_recursiv(index: &mut [IndexEntry]) -> Tree {
         let mut tree = Tree::new();
 
         let mut idx = 0;
@@ -89,8 +116,7 @@ impl Index {
 
                 let mut same_dir_entries = vec![index[idx].clone()];
 
-                for j in idx + 1..index.len() {
-                    let entry = &index[j];
+                for entry in index[idx + 1..].iter() {
                     if entry.path.starts_with(&prefix) {
                         let mut entry = entry.clone();
                         entry.path = entry.path.replacen(&prefix, "", 1);
@@ -117,13 +143,78 @@ impl Index {
         tree
     }
 
-    fn contains_hash(&self, hash: &str) -> bool {
-        self.entries.iter().any(|entry| entry.hash == hash)
+    pub fn entries(&self) -> &Vec<IndexEntry> {
+        &self.entries
+    }
+
+    pub fn get_entry(&self, path: &str) -> Option<&IndexEntry> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    /// Maps every indexed path to its blob hash.
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        self.entries
+            .iter()
+            .map(|e| (e.path.clone(), e.hash.clone()))
+            .collect()
+    }
+
+    /// Replaces all entries with the given paths, e.g. to match a commit.
+    pub fn reset_to(&mut self, paths: &BTreeMap<String, String>, state: NyxFileState) {
+        self.entries = paths
+            .iter()
+            .map(|(path, hash)| IndexEntry {
+                hash: hash.clone(),
+                path: path.clone(),
+                state,
+            })
+            .collect();
+        self.write();
+    }
+
+    /// Inserts or replaces the entry of `path` without writing the index.
+    pub fn set_entry(&mut self, path: &str, hash: &str, state: NyxFileState) {
+        match self.entries.iter_mut().find(|e| e.path == path) {
+            Some(entry) => {
+                entry.hash = hash.to_string();
+                entry.state = state;
+            }
+            None => self.entries.push(IndexEntry {
+                hash: hash.to_string(),
+                path: path.to_string(),
+                state,
+            }),
+        }
+    }
+
+    /// Removes the entry of `path` without writing the index.
+    pub fn remove_entry(&mut self, path: &str) {
+        self.entries.retain(|e| e.path != path);
+    }
+
+    pub fn write(&mut self) {
+        self.entries.sort_by(|e1, e2| e1.path.cmp(&e2.path));
+        FILE_SYSTEM.write_contents(&self.entries, self.path.to_str().unwrap());
+    }
+
+    /// True if `path` is already indexed with `hash` and not in conflict.
+    fn contains_resolved(&self, hash: &str, path: &str) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.hash == hash && entry.path == path && entry.state != NyxFileState::Unmerged
+        })
+    }
+
+    pub fn unmerged_paths(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| e.state == NyxFileState::Unmerged)
+            .map(|e| e.path.clone())
+            .collect()
     }
 
     pub fn get_status(&self, hash: &str, path: &str) -> NyxFileState {
         match self.entries.iter().find(|e| e.hash == hash) {
-            Some(entry) => entry.state.clone(),
+            Some(entry) => entry.state,
             None => match self.entries.iter().find(|e| e.path == path) {
                 Some(_) => NyxFileState::Modified,
                 None => NyxFileState::Unstaged,
@@ -0,0 +1,64 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+use crate::core::odb::stream;
+use crate::core::shared::{
+    generate_object, hash_file, hash_object, open_object, write_file_object,
+};
+
+use crate::FILE_SYSTEM;
+
+use super::pointer::Pointer;
+use super::store;
+use super::track::is_tracked;
+
+/// Blob hash of the working tree `file` at `path` as `add` stores it,
+/// the hash of its pointer for tracked large files. Files that are a
+/// pointer already, because their content was not fetched, stay as is.
+pub fn hash_working(file: &Path, path: &str) -> Result<String, NyxError> {
+    if !is_tracked(path) || Pointer::from_file(file).is_some() {
+        return hash_file(file, NyxObjectType::Blob);
+    }
+    let pointer = store::pointer_for_file(file)?;
+    Ok(hash_object(&pointer.to_bytes(), NyxObjectType::Blob))
+}
+
+/// Stores the working tree `file` at `path` and returns its blob hash.
+/// Tracked large files go to the large file store behind a pointer.
+pub fn write_working(file: &Path, path: &str) -> Result<String, NyxError> {
+    if !is_tracked(path) || Pointer::from_file(file).is_some() {
+        return write_file_object(file, NyxObjectType::Blob);
+    }
+    let pointer = store::store_file(file)?;
+    Ok(generate_object(&pointer.to_bytes(), NyxObjectType::Blob))
+}
+
+/// Writes the content of a blob to `file`. Pointers are replaced by the
+/// large file if it is in the store, otherwise the pointer is written.
+pub fn restore(hash: &str, file: &Path) -> Result<(), NyxError> {
+    let mut output = BufWriter::with_capacity(stream::buffer_size(), File::create(file)?);
+    match Pointer::read(hash) {
+        Some(pointer) if store::contains(&pointer.oid) => {
+            stream::copy(
+                &mut File::open(store::object_path(&pointer.oid))?,
+                &mut output,
+            )?;
+        }
+        pointer => {
+            if let Some(pointer) = pointer {
+                let root = FILE_SYSTEM.get_root_dir();
+                eprintln!(
+                    "warning: {}: large file {} is missing, run `nyx lfs fetch`",
+                    file.strip_prefix(root).unwrap_or(file).display(),
+                    &pointer.oid[..10]
+                );
+            }
+            stream::copy(&mut open_object(hash)?, &mut output)?;
+        }
+    }
+    output.flush()?;
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod filter;
+pub mod pointer;
+pub mod store;
+pub mod track;
@@ -0,0 +1,80 @@
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::core::object_type::NyxObjectType;
+use crate::core::shared::open_object;
+
+const VERSION: &str = "https://git-lfs.github.com/spec/v1";
+const OID_PREFIX: &str = "sha256:";
+/// Blobs above this size are never read as pointers.
+const MAX_POINTER_SIZE: u64 = 1024;
+
+/// Small blob stored in trees in place of a large file, in the format
+/// of git-lfs so pointers can be exchanged with git repositories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pointer {
+    /// SHA-256 of the file content
+    pub oid: String,
+    pub size: u64,
+}
+
+impl Pointer {
+    pub fn parse(content: &[u8]) -> Option<Self> {
+        let content = std::str::from_utf8(content).ok()?;
+        let mut lines = content.lines();
+        if lines.next()? != format!("version {}", VERSION) {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            match line.split_once(' ')? {
+                ("oid", value) => oid = value.strip_prefix(OID_PREFIX),
+                ("size", value) => size = value.parse().ok(),
+                _ => (),
+            }
+        }
+        let oid =
+            oid.filter(|oid| oid.len() == 64 && oid.chars().all(|c| c.is_ascii_hexdigit()))?;
+        Some(Self {
+            oid: oid.to_string(),
+            size: size?,
+        })
+    }
+
+    /// The pointer stored in a blob, `None` for blobs with other content.
+    pub fn read(hash: &str) -> Option<Self> {
+        let object = open_object(hash).ok()?;
+        if object.object_type != NyxObjectType::Blob || object.size > MAX_POINTER_SIZE {
+            return None;
+        }
+        let mut content = Vec::new();
+        object
+            .take(MAX_POINTER_SIZE)
+            .read_to_end(&mut content)
+            .ok()?;
+        Pointer::parse(&content)
+    }
+
+    /// The pointer a file consists of, e.g. a large file not fetched yet.
+    pub fn from_file(path: &Path) -> Option<Self> {
+        if fs::metadata(path).ok()?.len() > MAX_POINTER_SIZE {
+            return None;
+        }
+        Pointer::parse(&fs::read(path).ok()?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version {}", VERSION)?;
+        writeln!(f, "oid {}{}", OID_PREFIX, self.oid)?;
+        writeln!(f, "size {}", self.size)
+    }
+}
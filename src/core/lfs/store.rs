@@ -0,0 +1,126 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use sha2::{Digest, Sha256};
+
+use crate::core::errors::NyxError;
+use crate::core::odb::stream;
+use crate::FILE_SYSTEM;
+
+use super::pointer::Pointer;
+
+/// Hashes everything written through it with SHA-256.
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Copies `reader` into `writer` and returns the pointer of the content.
+fn copy_hashed(reader: &mut dyn Read, writer: impl Write) -> Result<Pointer, NyxError> {
+    let mut writer = HashingWriter {
+        inner: writer,
+        hasher: Sha256::new(),
+        size: 0,
+    };
+    stream::copy(reader, &mut writer)?;
+    writer.flush()?;
+    Ok(Pointer {
+        oid: hex::encode(writer.hasher.finalize()),
+        size: writer.size,
+    })
+}
+
+/// Directory of the large file store of a repository directory.
+pub fn objects_dir(repo_dir: &Path) -> PathBuf {
+    repo_dir.join("lfs").join("objects")
+}
+
+/// Location of a large file below `objects_dir`, `ab/cd/abcd...` like git-lfs.
+pub fn object_path_in(objects_dir: &Path, oid: &str) -> PathBuf {
+    objects_dir.join(&oid[..2]).join(&oid[2..4]).join(oid)
+}
+
+pub fn object_path(oid: &str) -> PathBuf {
+    object_path_in(&objects_dir(&FILE_SYSTEM.get_repo_dir()), oid)
+}
+
+pub fn contains(oid: &str) -> bool {
+    object_path(oid).is_file()
+}
+
+/// Computes the pointer of a file without storing it.
+pub fn pointer_for_file(path: &Path) -> Result<Pointer, NyxError> {
+    copy_hashed(&mut File::open(path)?, io::sink())
+}
+
+/// Stores the content of `reader` and returns its pointer. With
+/// `expected` the content must match that pointer.
+pub fn store(reader: &mut dyn Read, expected: Option<&Pointer>) -> Result<Pointer, NyxError> {
+    let dir = objects_dir(&FILE_SYSTEM.get_repo_dir());
+    fs::create_dir_all(&dir)?;
+    let temp_path = dir.join(format!("tmp_{}", process::id()));
+    let pointer = match copy_hashed(reader, File::create(&temp_path)?) {
+        Ok(pointer) if expected.is_none_or(|expected| *expected == pointer) => pointer,
+        Ok(pointer) => {
+            fs::remove_file(&temp_path)?;
+            return Err(NyxError::InvalidObject(format!(
+                "large file {} arrived as {}",
+                expected.map(|e| e.oid.as_str()).unwrap_or_default(),
+                pointer.oid
+            )));
+        }
+        Err(err) => {
+            _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+    };
+
+    let path = object_path(&pointer.oid);
+    if path.is_file() {
+        fs::remove_file(&temp_path)?;
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&temp_path, &path)?;
+    }
+    Ok(pointer)
+}
+
+pub fn store_file(path: &Path) -> Result<Pointer, NyxError> {
+    store(&mut File::open(path)?, None)
+}
+
+/// Object ids of all stored large files, sorted.
+pub fn list() -> Vec<String> {
+    let mut oids = Vec::new();
+    let mut dirs = vec![objects_dir(&FILE_SYSTEM.get_repo_dir())];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() {
+                dirs.push(entry.path());
+            } else if name.len() == 64 {
+                oids.push(name);
+            }
+        }
+    }
+    oids.sort();
+    oids
+}
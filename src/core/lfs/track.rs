@@ -0,0 +1,61 @@
+use std::fs;
+
+use glob::{MatchOptions, Pattern};
+
+use crate::core::errors::NyxError;
+use crate::FILE_SYSTEM;
+
+pub const ATTRIBUTES_FILE: &str = ".nyxattributes";
+const LFS_ATTRIBUTE: &str = "filter=lfs";
+
+/// Patterns of the paths stored as large files, one
+/// `<pattern> filter=lfs` line each in `.nyxattributes`.
+pub fn tracked_patterns() -> Vec<String> {
+    let content =
+        fs::read_to_string(FILE_SYSTEM.get_root_dir().join(ATTRIBUTES_FILE)).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next().filter(|pattern| !pattern.starts_with('#'))?;
+            parts
+                .any(|attribute| attribute == LFS_ATTRIBUTE)
+                .then(|| pattern.to_string())
+        })
+        .collect()
+}
+
+/// Adds a pattern to `.nyxattributes`, returns false if it is tracked already.
+pub fn track(pattern: &str) -> Result<bool, NyxError> {
+    if tracked_patterns().iter().any(|tracked| tracked == pattern) {
+        return Ok(false);
+    }
+    Pattern::new(pattern)
+        .map_err(|err| NyxError::Usage(format!("invalid pattern '{}': {}", pattern, err)))?;
+    let path = FILE_SYSTEM.get_root_dir().join(ATTRIBUTES_FILE);
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("{} {}\n", pattern, LFS_ATTRIBUTE));
+    fs::write(path, content)?;
+    Ok(true)
+}
+
+/// Whether a path relative to the repository root is stored as large
+/// file. Patterns without a slash match the file name in any directory.
+pub fn is_tracked(path: &str) -> bool {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    tracked_patterns().iter().any(|pattern| {
+        let (pattern, candidate) = match pattern.contains('/') {
+            true => (pattern.trim_start_matches('/'), path),
+            false => (pattern.as_str(), name),
+        };
+        Pattern::new(pattern).is_ok_and(|p| p.matches_with(candidate, options))
+    })
+}
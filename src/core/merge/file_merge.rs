@@ -0,0 +1,127 @@
+use crate::core::diff::myers::{diff, DiffOp};
+use crate::core::diff::unified::split_lines;
+
+/// Result of a three-way merge of two texts.
+#[derive(Debug)]
+pub struct FileMerge {
+    pub content: String,
+    pub conflicts: usize,
+}
+
+impl FileMerge {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts == 0
+    }
+}
+
+/// For every line of `base` the index of the matching line in `other`.
+fn matching_lines(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for op in diff(base, other) {
+        if let DiffOp::Equal(i, j) = op {
+            matches[i] = Some(j);
+        }
+    }
+    matches
+}
+
+/// Merges the changes from `base` to `ours` and from `base` to `theirs`
+/// (diff3). Regions changed differently on both sides are wrapped in
+/// conflict markers labeled with `ours_label` and `theirs_label`.
+pub fn merge_text(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    ours_label: &str,
+    theirs_label: &str,
+) -> FileMerge {
+    let base_lines = split_lines(base);
+    let our_lines = split_lines(ours);
+    let their_lines = split_lines(theirs);
+    let our_matches = matching_lines(&base_lines, &our_lines);
+    let their_matches = matching_lines(&base_lines, &their_lines);
+
+    let mut merged = String::new();
+    let mut conflicts = 0;
+    let (mut i, mut a, mut b) = (0, 0, 0);
+
+    loop {
+        // Extend the stable chunk as long as all three sides agree
+        let mut stable = 0;
+        while i + stable < base_lines.len()
+            && our_matches[i + stable] == Some(a + stable)
+            && their_matches[i + stable] == Some(b + stable)
+        {
+            stable += 1;
+        }
+        if stable > 0 {
+            for line in &base_lines[i..i + stable] {
+                merged.push_str(line);
+            }
+            i += stable;
+            a += stable;
+            b += stable;
+            continue;
+        }
+
+        // Find the next base line that is kept on both sides
+        let next = (i..base_lines.len()).find_map(|o| match (our_matches[o], their_matches[o]) {
+            (Some(x), Some(y)) if x >= a && y >= b => Some((o, x, y)),
+            _ => None,
+        });
+        let (o, x, y) = next.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+
+        let base_chunk = &base_lines[i..o];
+        let our_chunk = &our_lines[a..x];
+        let their_chunk = &their_lines[b..y];
+
+        if our_chunk == base_chunk {
+            their_chunk.iter().for_each(|line| merged.push_str(line));
+        } else if their_chunk == base_chunk || our_chunk == their_chunk {
+            our_chunk.iter().for_each(|line| merged.push_str(line));
+        } else {
+            conflicts += 1;
+            push_conflict(
+                &mut merged,
+                our_chunk,
+                their_chunk,
+                ours_label,
+                theirs_label,
+            );
+        }
+
+        if next.is_none() {
+            break;
+        }
+        i = o;
+        a = x;
+        b = y;
+    }
+
+    FileMerge {
+        content: merged,
+        conflicts,
+    }
+}
+
+fn push_conflict(
+    merged: &mut String,
+    ours: &[&str],
+    theirs: &[&str],
+    ours_label: &str,
+    theirs_label: &str,
+) {
+    let push_lines = |merged: &mut String, lines: &[&str]| {
+        for line in lines {
+            merged.push_str(line);
+            if !line.ends_with('\n') {
+                merged.push('\n');
+            }
+        }
+    };
+    merged.push_str(&format!("<<<<<<< {}\n", ours_label));
+    push_lines(merged, ours);
+    merged.push_str("=======\n");
+    push_lines(merged, theirs);
+    merged.push_str(&format!(">>>>>>> {}\n", theirs_label));
+}
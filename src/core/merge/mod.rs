@@ -0,0 +1,2 @@
+pub mod file_merge;
+pub mod tree_merge;
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+use crate::core::shared::{generate_object, read_object_content};
+
+use super::file_merge::merge_text;
+
+#[derive(Debug, Clone)]
+pub enum MergedEntry {
+    /// The path merged cleanly into the blob with this hash
+    Clean(String),
+    /// Both sides changed the path in incompatible ways. `content` is
+    /// what ends up in the working tree, usually with conflict markers.
+    Conflict {
+        content: Vec<u8>,
+        ours: Option<String>,
+        theirs: Option<String>,
+    },
+}
+
+/// Outcome of a three-way merge of flattened trees. Paths deleted by
+/// the merge do not appear in `entries`.
+#[derive(Debug)]
+pub struct TreeMerge {
+    pub entries: BTreeMap<String, MergedEntry>,
+}
+
+impl TreeMerge {
+    pub fn conflicts(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| matches!(entry, MergedEntry::Conflict { .. }))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.conflicts().is_empty()
+    }
+
+    /// Paths with their resulting blob hash. Conflicted paths keep the
+    /// version of our side if there is one.
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut paths = BTreeMap::new();
+        for (path, entry) in &self.entries {
+            match entry {
+                MergedEntry::Clean(hash) => {
+                    paths.insert(path.clone(), hash.clone());
+                }
+                MergedEntry::Conflict { ours, theirs, .. } => {
+                    if let Some(hash) = ours.as_ref().or(theirs.as_ref()) {
+                        paths.insert(path.clone(), hash.clone());
+                    }
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// Reads the content of a blob as text, `None` if it is binary.
+fn read_text(hash: &str) -> Result<Option<String>, NyxError> {
+    let content = read_object_content(hash)?;
+    if content.contains(&0) {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(content).ok())
+}
+
+/// Merges the lines of a blob changed on both sides. Binary blobs and
+/// blobs of the base or their side that cannot be read conflict with
+/// our version left in the working tree.
+fn merge_blobs(
+    path: &str,
+    base: Option<&String>,
+    ours: &str,
+    theirs: &str,
+    ours_label: &str,
+    theirs_label: &str,
+) -> Result<MergedEntry, NyxError> {
+    let conflict = |content: Vec<u8>| MergedEntry::Conflict {
+        content,
+        ours: Some(ours.to_string()),
+        theirs: Some(theirs.to_string()),
+    };
+    let our_text = match read_text(ours)? {
+        Some(text) => text,
+        None => {
+            eprintln!(
+                "warning: Cannot merge binary files: {} ({} vs. {})",
+                path, ours_label, theirs_label
+            );
+            return Ok(conflict(read_object_content(ours)?));
+        }
+    };
+    let other_texts = base
+        .map_or(Ok(Some(String::new())), |hash| read_text(hash))
+        .and_then(|base| Ok(base.zip(read_text(theirs)?)));
+    let (base_text, their_text) = match other_texts {
+        Ok(Some(texts)) => texts,
+        Ok(None) => {
+            eprintln!(
+                "warning: Cannot merge binary files: {} ({} vs. {})",
+                path, ours_label, theirs_label
+            );
+            return Ok(conflict(our_text.into_bytes()));
+        }
+        Err(err) => {
+            eprintln!("error: cannot merge {}: {}", path, err);
+            return Ok(conflict(our_text.into_bytes()));
+        }
+    };
+
+    let result = merge_text(&base_text, &our_text, &their_text, ours_label, theirs_label);
+    if result.is_clean() {
+        let hash = generate_object(result.content.as_bytes(), NyxObjectType::Blob);
+        Ok(MergedEntry::Clean(hash))
+    } else {
+        Ok(conflict(result.content.into_bytes()))
+    }
+}
+
+/// Merges the changes between `base` and `theirs` into `ours`. Fails
+/// if a blob of our side cannot be read.
+pub fn merge_trees(
+    base: &BTreeMap<String, String>,
+    ours: &BTreeMap<String, String>,
+    theirs: &BTreeMap<String, String>,
+    ours_label: &str,
+    theirs_label: &str,
+) -> Result<TreeMerge, NyxError> {
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    let mut entries = BTreeMap::new();
+
+    for path in paths {
+        let b = base.get(path);
+        let o = ours.get(path);
+        let t = theirs.get(path);
+
+        let merged = if o == t || b == t {
+            o.map(|hash| MergedEntry::Clean(hash.clone()))
+        } else if b == o {
+            t.map(|hash| MergedEntry::Clean(hash.clone()))
+        } else {
+            match (o, t) {
+                (Some(o), Some(t)) => Some(merge_blobs(path, b, o, t, ours_label, theirs_label)?),
+                // Modified on one side, deleted on the other
+                (Some(kept), None) | (None, Some(kept)) => Some(MergedEntry::Conflict {
+                    content: read_object_content(kept)?,
+                    ours: o.cloned(),
+                    theirs: t.cloned(),
+                }),
+                (None, None) => None,
+            }
+        };
+
+        if let Some(entry) = merged {
+            entries.insert(path.clone(), entry);
+        }
+    }
+
+    Ok(TreeMerge { entries })
+}
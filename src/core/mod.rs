@@ -1,11 +1,37 @@
+pub mod bisect;
+pub mod blame;
 pub mod cl_args;
 pub mod commands;
 pub mod commit;
+pub mod config;
+pub mod date;
+pub mod diff;
 pub mod display_strings;
+pub mod editor;
 pub mod errors;
 pub mod file_system;
+pub mod git;
+pub mod graph;
+pub mod hooks;
 pub mod index;
+pub mod lfs;
+pub mod merge;
+pub mod object_format;
 pub mod object_type;
+pub mod odb;
+pub mod pathspec;
+pub mod pretty;
+pub mod reachability;
+pub mod rebase;
+pub mod refs;
+pub mod remote;
+pub mod rev_walk;
+pub mod revision;
+pub mod sequencer;
 pub mod shared;
+pub mod signature;
+pub mod signing;
+pub mod tag;
 pub mod traits;
 pub mod tree;
+pub mod worktree;
@@ -0,0 +1,131 @@
+use std::fmt;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+use super::config::Config;
+use super::errors::NyxError;
+
+/// Config key naming the hash function of the repository. Repositories
+/// without it use SHA-1.
+pub const OBJECT_FORMAT_KEY: &str = "extensions.objectFormat";
+
+/// The hash function objects are named by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectFormat {
+    Sha1,
+    Sha256,
+}
+
+impl ObjectFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sha1" => Some(ObjectFormat::Sha1),
+            "sha256" => Some(ObjectFormat::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Number of hex digits of a full hash.
+    pub fn hex_len(&self) -> usize {
+        match self {
+            ObjectFormat::Sha1 => 40,
+            ObjectFormat::Sha256 => 64,
+        }
+    }
+
+    /// The hash standing for "no object", e.g. in reflogs of new refs.
+    pub fn zero_hash(&self) -> String {
+        "0".repeat(self.hex_len())
+    }
+
+    /// Whether `hash` is a full hash of this format.
+    pub fn is_hash(&self, hash: &str) -> bool {
+        hash.len() == self.hex_len() && hash.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// The format a full hash belongs to, judged by its length.
+    pub fn of_hash(hash: &str) -> Option<Self> {
+        [ObjectFormat::Sha1, ObjectFormat::Sha256]
+            .into_iter()
+            .find(|format| format.is_hash(hash))
+    }
+
+    pub fn hasher(&self) -> Hasher {
+        match self {
+            ObjectFormat::Sha1 => Hasher::Sha1(Sha1::new()),
+            ObjectFormat::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    pub fn digest(&self, content: &[u8]) -> String {
+        let mut hasher = self.hasher();
+        hasher.update(content);
+        hasher.finish()
+    }
+}
+
+impl fmt::Display for ObjectFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+/// Computes a hash of the function of an object format piece by piece.
+pub enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    /// The hash as lowercase hex digits.
+    pub fn finish(self) -> String {
+        match self {
+            Hasher::Sha1(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Sha256(hasher) => hex::encode(hasher.finalize()),
+        }
+    }
+}
+
+lazy_static! {
+    static ref OBJECT_FORMAT: RwLock<Option<ObjectFormat>> = RwLock::new(None);
+}
+
+/// The object format of the repository, read from the config once.
+pub fn object_format() -> ObjectFormat {
+    if let Some(format) = *OBJECT_FORMAT.read().unwrap() {
+        return format;
+    }
+    let format = Config::new()
+        .get(OBJECT_FORMAT_KEY)
+        .and_then(|name| ObjectFormat::parse(&name))
+        .unwrap_or(ObjectFormat::Sha1);
+    *OBJECT_FORMAT.write().unwrap().get_or_insert(format)
+}
+
+/// Switches the object format for all following hashing, e.g. once a
+/// clone learned the format of its source.
+pub fn set_object_format(format: ObjectFormat) {
+    *OBJECT_FORMAT.write().unwrap() = Some(format);
+}
+
+/// Records the object format in the config and uses it from now on.
+pub fn write_object_format(format: ObjectFormat) -> Result<(), NyxError> {
+    let mut config = Config::new();
+    match format {
+        ObjectFormat::Sha1 => config.unset(OBJECT_FORMAT_KEY),
+        _ => config.set(OBJECT_FORMAT_KEY, &format.to_string())?,
+    }
+    config.write()?;
+    set_object_format(format);
+    Ok(())
+}
@@ -1,10 +1,23 @@
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NyxObjectType {
     Commit,
     Tree,
     Blob,
+    Tag,
+}
+
+impl NyxObjectType {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "commit" => Some(NyxObjectType::Commit),
+            "tree" => Some(NyxObjectType::Tree),
+            "blob" => Some(NyxObjectType::Blob),
+            "tag" => Some(NyxObjectType::Tag),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for NyxObjectType {
@@ -0,0 +1,104 @@
+use std::io::{self, Read};
+
+/// Chunks are never cut before this size, except at the end of the content.
+pub const MIN_CHUNK_SIZE: usize = 16 * 1024;
+/// Cut points are placed so chunks are about this size on average.
+pub const AVERAGE_CHUNK_SIZE: usize = 64 * 1024;
+/// Chunks are always cut at this size.
+pub const MAX_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Harder to hit before the average size, easier after it, which keeps
+/// chunk sizes close to the average (normalized chunking of FastCDC).
+const MASK_SMALL: u64 = mask(AVERAGE_CHUNK_SIZE.trailing_zeros() + 2);
+const MASK_LARGE: u64 = mask(AVERAGE_CHUNK_SIZE.trailing_zeros() - 2);
+
+const GEAR: [u64; 256] = gear_table();
+
+/// A mask of the `bits` highest bits, which the gear hash mixes best.
+const fn mask(bits: u32) -> u64 {
+    !0u64 << (64 - bits)
+}
+
+/// Fixed pseudo random values per byte, generated with splitmix64 so
+/// the same content is always cut at the same places.
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x6e79_785f_6364_6331;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Length of the first chunk of `data`. The cut depends only on the
+/// bytes before it, so an edit only changes the chunks around it.
+pub fn cut_point(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK_SIZE {
+        return data.len();
+    }
+    let normal = data.len().min(AVERAGE_CHUNK_SIZE);
+    let end = data.len().min(MAX_CHUNK_SIZE);
+    let mut hash: u64 = 0;
+    for (i, byte) in data.iter().enumerate().take(end).skip(MIN_CHUNK_SIZE) {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+        let mask = if i < normal { MASK_SMALL } else { MASK_LARGE };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Splits the content of a reader into content-defined chunks, holding
+/// at most `MAX_CHUNK_SIZE` bytes in memory.
+pub struct Chunker<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> Chunker<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::with_capacity(MAX_CHUNK_SIZE),
+            eof: false,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut block = [0u8; 8192];
+        while !self.eof && self.buffer.len() < MAX_CHUNK_SIZE {
+            let wanted = block.len().min(MAX_CHUNK_SIZE - self.buffer.len());
+            match self.reader.read(&mut block[..wanted]) {
+                Ok(0) => self.eof = true,
+                Ok(count) => self.buffer.extend_from_slice(&block[..count]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for Chunker<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(err) = self.fill() {
+            return Some(Err(err));
+        }
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let cut = cut_point(&self.buffer);
+        let rest = self.buffer.split_off(cut);
+        Some(Ok(std::mem::replace(&mut self.buffer, rest)))
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::core::errors::NyxError;
+use crate::core::object_format::ObjectFormat;
+use crate::core::object_type::NyxObjectType;
+use crate::core::reachability::object_references;
+use crate::core::shared::{append_object_header, open_object, read_object_data};
+
+use super::database::ObjectDatabase;
+
+/// Header keys of commits and tags whose values are object hashes.
+const HASH_HEADERS: [&str; 3] = ["tree", "parent", "object"];
+
+/// Header keys of commits and tags. Commits written by older versions
+/// have no empty line before the message, their headers end at the
+/// first line with another key.
+const COMMIT_HEADERS: [&str; 5] = ["tree", "parent", "author", "committer", "gpgsig"];
+const TAG_HEADERS: [&str; 4] = ["object", "type", "tag", "tagger"];
+
+/// Rewrites the objects of the repository for another object format.
+/// Every object gets a new hash and objects name the objects they
+/// reference by hash, so every object is converted after those.
+pub struct Converter<'a> {
+    target: &'a dyn ObjectDatabase,
+    format: ObjectFormat,
+    mapping: HashMap<String, String>,
+    counts: BTreeMap<String, usize>,
+}
+
+impl<'a> Converter<'a> {
+    /// Converts into `target`, which must name objects by `format`.
+    pub fn new(target: &'a dyn ObjectDatabase, format: ObjectFormat) -> Self {
+        Self {
+            target,
+            format,
+            mapping: HashMap::new(),
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Maps the old hashes to the hashes of the converted objects.
+    pub fn mapping(&self) -> &HashMap<String, String> {
+        &self.mapping
+    }
+
+    /// Number of converted objects per type name.
+    pub fn counts(&self) -> &BTreeMap<String, usize> {
+        &self.counts
+    }
+
+    fn mapped(&self, hash: &str) -> Result<&str, NyxError> {
+        self.mapping
+            .get(hash)
+            .map(|hash| hash.as_str())
+            .ok_or_else(|| NyxError::InvalidObject(format!("{} is missing", hash)))
+    }
+
+    /// Content of the converted object, everything but the hashes it
+    /// contains is kept as is.
+    fn convert_content(
+        &self,
+        object_type: NyxObjectType,
+        content: &str,
+    ) -> Result<String, NyxError> {
+        let mut converted = String::new();
+        let headers: &[&str] = match object_type {
+            NyxObjectType::Tag => &TAG_HEADERS,
+            _ => &COMMIT_HEADERS,
+        };
+        let mut in_headers = true;
+        for line in content.split_inclusive('\n') {
+            // Lines continuing a header start with a space
+            in_headers = in_headers
+                && (line.starts_with(' ')
+                    || line
+                        .split_once(' ')
+                        .is_some_and(|(key, _)| headers.contains(&key)));
+            let entry = match object_type {
+                NyxObjectType::Tree => line.split_once(' ').and_then(|(entry_type, rest)| {
+                    let (hash, name) = rest.split_once(' ')?;
+                    Some((format!("{} ", entry_type), hash, format!(" {}", name)))
+                }),
+                _ if in_headers => line.split_once(' ').and_then(|(key, value)| {
+                    let hash = value.trim_end_matches('\n');
+                    HASH_HEADERS
+                        .contains(&key)
+                        .then(|| (format!("{} ", key), hash, value[hash.len()..].to_string()))
+                }),
+                _ => None,
+            };
+            match entry {
+                Some((before, hash, after)) => {
+                    converted.push_str(&before);
+                    converted.push_str(self.mapped(hash)?);
+                    converted.push_str(&after);
+                }
+                None => converted.push_str(line),
+            }
+        }
+        Ok(converted)
+    }
+
+    fn convert_object(&mut self, hash: &str) -> Result<String, NyxError> {
+        let mut reader = open_object(hash)?;
+        let object_type = reader.object_type;
+        let converted = match object_type {
+            // Blobs contain no hashes and may be too large to hold
+            NyxObjectType::Blob => {
+                let size = reader.size;
+                self.target.write_stream(object_type, size, &mut reader)?
+            }
+            _ => {
+                let content = self.convert_content(object_type, &read_object_data(hash)?)?;
+                let raw = append_object_header(content.as_bytes(), object_type);
+                let converted = self.format.digest(&raw);
+                self.target.write(&converted, &raw)?;
+                converted
+            }
+        };
+        *self.counts.entry(object_type.to_string()).or_default() += 1;
+        Ok(converted)
+    }
+
+    /// Converts the object and everything it references, returns the
+    /// new hash of the object.
+    pub fn convert(&mut self, hash: &str) -> Result<String, NyxError> {
+        // Objects are expanded on the first visit and converted on the
+        // second, once everything they reference has been converted
+        let mut pending: HashSet<String> = HashSet::new();
+        let mut stack: Vec<(String, bool)> = vec![(hash.to_string(), false)];
+        while let Some((hash, expanded)) = stack.pop() {
+            if self.mapping.contains_key(&hash) {
+                continue;
+            }
+            if !expanded {
+                if !pending.insert(hash.clone()) {
+                    continue;
+                }
+                stack.push((hash.clone(), true));
+                for reference in object_references(&hash)? {
+                    if !self.mapping.contains_key(&reference) {
+                        stack.push((reference, false));
+                    }
+                }
+                continue;
+            }
+            let converted = self.convert_object(&hash)?;
+            self.mapping.insert(hash, converted);
+        }
+        self.mapped(hash).map(|hash| hash.to_string())
+    }
+}
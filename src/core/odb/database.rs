@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+
+use crate::core::config::Config;
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+use crate::FILE_SYSTEM;
+
+use super::loose::LooseObjectDatabase;
+use super::reader::ObjectReader;
+use super::stream;
+use super::writer::ObjectWriter;
+
+/// Storage for objects, addressed by their hash. Objects are stored
+/// including their `<type> <size>\0` header.
+pub trait ObjectDatabase: Send + Sync {
+    fn read(&self, hash: &str) -> Result<Vec<u8>, NyxError>;
+
+    /// Opens the object to read its content in chunks.
+    fn reader(&self, hash: &str) -> Result<ObjectReader, NyxError> {
+        ObjectReader::new(hash, Box::new(Cursor::new(self.read(hash)?)))
+    }
+
+    /// Stores the object under its hash, existing objects are kept.
+    fn write(&self, hash: &str, raw: &[u8]) -> Result<(), NyxError>;
+
+    /// Stores `size` bytes of content from `reader` as object of the
+    /// given type and returns its hash.
+    fn write_stream(
+        &self,
+        object_type: NyxObjectType,
+        size: u64,
+        reader: &mut dyn Read,
+    ) -> Result<String, NyxError> {
+        let mut writer = ObjectWriter::new(Vec::new(), object_type, size)?;
+        stream::copy(reader, &mut writer)?;
+        let (raw, hash) = writer.finish()?;
+        self.write(&hash, &raw)?;
+        Ok(hash)
+    }
+
+    fn exists(&self, hash: &str) -> bool;
+
+    /// Hashes of all objects, sorted.
+    fn iter(&self) -> Box<dyn Iterator<Item = String> + '_>;
+
+    /// Size of the object content as declared in its header.
+    fn size(&self, hash: &str) -> Result<u64, NyxError> {
+        Ok(self.reader(hash)?.size)
+    }
+
+    /// Seconds since the epoch the object was stored at, `None` if the
+    /// database does not keep track of it.
+    fn written_at(&self, _hash: &str) -> Result<Option<i64>, NyxError> {
+        Ok(None)
+    }
+
+    /// Bytes that removing the objects would free.
+    fn removable_size(&self, hashes: &HashSet<String>) -> Result<u64, NyxError>;
+
+    /// Deletes the objects, chunked blobs along with the chunks no other
+    /// object uses. Returns the bytes freed.
+    fn remove(&self, hashes: &HashSet<String>) -> Result<u64, NyxError>;
+
+    /// Hashes starting with `prefix`, sorted.
+    fn with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.iter()
+            .filter(|hash| hash.starts_with(prefix))
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref DATABASE: RwLock<Option<Arc<dyn ObjectDatabase>>> = RwLock::new(None);
+}
+
+/// Size above which blobs are stored as chunks, `core.chunkThreshold`
+/// in bytes. Chunking is off unless it is set.
+pub fn chunk_threshold() -> Option<u64> {
+    Config::new()
+        .get("core.chunkThreshold")
+        .and_then(|threshold| threshold.parse().ok())
+}
+
+/// The object database of the repository, the loose objects in the
+/// objects directory unless another database was installed.
+pub fn database() -> Arc<dyn ObjectDatabase> {
+    if let Some(database) = DATABASE.read().unwrap().as_ref() {
+        return database.clone();
+    }
+    let dir = FILE_SYSTEM.get_objects_dir_path();
+    let loose = match chunk_threshold() {
+        Some(threshold) => LooseObjectDatabase::with_chunking(dir, threshold),
+        None => LooseObjectDatabase::new(dir),
+    };
+    let loose: Arc<dyn ObjectDatabase> = Arc::new(loose);
+    DATABASE.write().unwrap().get_or_insert(loose).clone()
+}
+
+/// Replaces the object database for all following object accesses,
+/// e.g. with a `MemoryObjectDatabase` to keep objects off the disk.
+pub fn set_database(database: Arc<dyn ObjectDatabase>) {
+    *DATABASE.write().unwrap() = Some(database);
+}
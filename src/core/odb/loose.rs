@@ -0,0 +1,427 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::UNIX_EPOCH;
+
+use crate::core::errors::NyxError;
+use crate::core::object_format::{object_format, ObjectFormat};
+use crate::core::object_type::NyxObjectType;
+use crate::core::shared::split_raw_object;
+
+use super::chunker::Chunker;
+use super::database::ObjectDatabase;
+use super::reader::ObjectReader;
+use super::stream;
+use super::writer::ObjectWriter;
+
+/// Numbers the temporary files of concurrent writes.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts the object file of a blob stored as chunks. The rest of the
+/// header is the one of the blob, the content lists `<chunk hash> <size>`
+/// lines in order.
+const CHUNKED_PREFIX: &[u8] = b"chunked ";
+
+/// Directory of the chunk files, its name is not a hash prefix so
+/// chunks are never listed as objects.
+const CHUNKS_DIR: &str = "chunks";
+
+/// Objects as single files, `<dir>/<first two hex digits>/<rest>`.
+/// Blobs larger than the chunk threshold are split into content-defined
+/// chunks shared between blobs, which keeps their hash unchanged.
+pub struct LooseObjectDatabase {
+    dir: PathBuf,
+    format: ObjectFormat,
+    chunk_threshold: Option<u64>,
+}
+
+impl LooseObjectDatabase {
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_format(dir, object_format(), None)
+    }
+
+    /// Stores blobs larger than `threshold` bytes as chunks.
+    pub fn with_chunking(dir: PathBuf, threshold: u64) -> Self {
+        Self::with_format(dir, object_format(), Some(threshold))
+    }
+
+    /// Names objects and chunks by hashes of `format` instead of the
+    /// object format of the repository, e.g. while converting it.
+    pub fn with_format(dir: PathBuf, format: ObjectFormat, chunk_threshold: Option<u64>) -> Self {
+        Self {
+            dir,
+            format,
+            chunk_threshold,
+        }
+    }
+
+    fn object_path(&self, hash: &str) -> Option<PathBuf> {
+        let valid = hash.len() > 2 && hash.is_char_boundary(2) && !hash.contains('/');
+        valid.then(|| self.dir.join(&hash[..2]).join(&hash[2..]))
+    }
+
+    fn hashes_in(&self, prefix: &str) -> Vec<String> {
+        fs::read_dir(self.dir.join(prefix))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|object| format!("{}{}", prefix, object.file_name().to_string_lossy()))
+            .collect()
+    }
+
+    fn temp_path(&self) -> PathBuf {
+        self.dir.join(format!(
+            "tmp_obj_{}_{}",
+            process::id(),
+            TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    /// Moves a completely written temporary file to `path`, unless a
+    /// file with the same content is already there.
+    fn persist(&self, temp_path: &Path, path: &Path) -> Result<(), NyxError> {
+        if path.is_file() {
+            fs::remove_file(temp_path)?;
+        } else {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::rename(temp_path, path)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a file through a temporary file, so no partially written
+    /// file is ever found under `path`.
+    fn write_file(&self, path: &Path, content: &[u8]) -> Result<(), NyxError> {
+        if path.is_file() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        let temp_path = self.temp_path();
+        if let Err(err) = fs::write(&temp_path, content) {
+            _ = fs::remove_file(&temp_path);
+            return Err(err.into());
+        }
+        self.persist(&temp_path, path)
+    }
+
+    fn chunk_path(&self, hash: &str) -> Option<PathBuf> {
+        self.object_path(hash)
+            .map(|_| self.dir.join(CHUNKS_DIR).join(&hash[..2]).join(&hash[2..]))
+    }
+
+    fn is_chunked(&self, size: u64) -> bool {
+        self.chunk_threshold
+            .is_some_and(|threshold| size > threshold)
+    }
+
+    /// Splits the blob content into chunks, stores the new ones and
+    /// writes the list of chunks as object file of the blob.
+    fn write_chunked(&self, size: u64, reader: &mut dyn Read) -> Result<String, NyxError> {
+        let mut hasher =
+            ObjectWriter::with_format(io::sink(), NyxObjectType::Blob, size, self.format)?;
+        let mut chunks = String::new();
+        for chunk in Chunker::new(reader) {
+            let chunk = chunk?;
+            hasher.write_all(&chunk)?;
+            let hash = self.format.digest(&chunk);
+            let path = self
+                .chunk_path(&hash)
+                .ok_or_else(|| NyxError::InvalidObject(hash.clone()))?;
+            self.write_file(&path, &chunk)?;
+            chunks.push_str(&format!("{} {}\n", hash, chunk.len()));
+        }
+        let hash = hasher.finish()?.1;
+
+        let path = self
+            .object_path(&hash)
+            .ok_or_else(|| NyxError::InvalidObject(hash.clone()))?;
+        let mut manifest = CHUNKED_PREFIX.to_vec();
+        manifest.extend_from_slice(format!("blob {}\0{}", size, chunks).as_bytes());
+        self.write_file(&path, &manifest)?;
+        Ok(hash)
+    }
+
+    /// Object files of the hashes and the chunk files only they use.
+    fn removable_files(&self, hashes: &HashSet<String>) -> Result<Vec<PathBuf>, NyxError> {
+        let mut files: Vec<PathBuf> = hashes
+            .iter()
+            .filter_map(|hash| self.object_path(hash))
+            .filter(|path| path.is_file())
+            .collect();
+        files.extend(self.unused_chunks(hashes)?);
+        Ok(files)
+    }
+
+    /// Chunk files that no object file lists anymore, ignoring the
+    /// object files of the hashes in `removed`.
+    fn unused_chunks(&self, removed: &HashSet<String>) -> Result<Vec<PathBuf>, NyxError> {
+        let mut used = HashSet::new();
+        for hash in self.iter().filter(|hash| !removed.contains(hash)) {
+            let Some(path) = self.object_path(&hash) else {
+                continue;
+            };
+            let mut file = BufReader::new(File::open(path)?);
+            let mut header = Vec::new();
+            file.read_until(0, &mut header)?;
+            if header.starts_with(CHUNKED_PREFIX) {
+                used.extend(read_chunk_list(file)?.into_iter().map(|(hash, _)| hash));
+            }
+        }
+
+        let mut unused = Vec::new();
+        for dir in fs::read_dir(self.dir.join(CHUNKS_DIR))
+            .into_iter()
+            .flatten()
+        {
+            let dir = dir?;
+            let prefix = dir.file_name().to_string_lossy().to_string();
+            for chunk in fs::read_dir(dir.path())? {
+                let chunk = chunk?;
+                let hash = format!("{}{}", prefix, chunk.file_name().to_string_lossy());
+                if !used.contains(&hash) {
+                    unused.push(chunk.path());
+                }
+            }
+        }
+        unused.sort();
+        Ok(unused)
+    }
+}
+
+/// Deletes a file and its directory once that is empty.
+fn remove_file(path: &Path) -> Result<(), NyxError> {
+    fs::remove_file(path)?;
+    if let Some(dir) = path.parent() {
+        if fs::read_dir(dir)?.next().is_none() {
+            fs::remove_dir(dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// The chunk hashes and lengths listed after the header of a chunked blob.
+fn read_chunk_list(file: impl BufRead) -> Result<Vec<(String, u64)>, NyxError> {
+    let mut chunks = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if let Some((hash, length)) = line.split_once(' ') {
+            let length = length
+                .parse()
+                .map_err(|_| NyxError::InvalidObject(format!("chunk {}", hash)))?;
+            chunks.push((hash.to_string(), length));
+        }
+    }
+    Ok(chunks)
+}
+
+/// Reads the chunk files of a chunked blob one after the other and
+/// fails for chunks whose length differs from the listed one.
+struct ChunkReader {
+    chunks: VecDeque<(PathBuf, u64)>,
+    /// The chunk being read and the number of bytes left in it.
+    current: Option<(PathBuf, File, u64)>,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.current.is_none() {
+                let Some((path, length)) = self.chunks.pop_front() else {
+                    return Ok(0);
+                };
+                let file = File::open(&path).map_err(|_| {
+                    let message = format!("missing chunk {}", path.display());
+                    io::Error::new(io::ErrorKind::NotFound, message)
+                })?;
+                self.current = Some((path, file, length));
+            }
+            let (path, file, left) = self.current.as_mut().unwrap();
+            let count = file.read(buf)?;
+            if count as u64 > *left || (count == 0 && *left > 0 && !buf.is_empty()) {
+                let message = format!("chunk {} has the wrong length", path.display());
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            *left -= count as u64;
+            if count > 0 || buf.is_empty() {
+                return Ok(count);
+            }
+            self.current = None;
+        }
+    }
+}
+
+impl ObjectDatabase for LooseObjectDatabase {
+    fn read(&self, hash: &str) -> Result<Vec<u8>, NyxError> {
+        let path = self
+            .object_path(hash)
+            .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+        let mut raw = fs::read(path)?;
+        if !raw.starts_with(CHUNKED_PREFIX) {
+            return Ok(raw);
+        }
+        let header_end = raw.iter().position(|b| *b == 0).unwrap_or(raw.len() - 1) + 1;
+        raw.truncate(header_end);
+        raw.drain(..CHUNKED_PREFIX.len());
+        self.reader(hash)?.read_to_end(&mut raw)?;
+        Ok(raw)
+    }
+
+    /// Chunked blobs are read as if they were stored as a whole.
+    fn reader(&self, hash: &str) -> Result<ObjectReader, NyxError> {
+        let path = self
+            .object_path(hash)
+            .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+        let mut file = BufReader::with_capacity(stream::buffer_size(), File::open(path)?);
+        let mut header = Vec::new();
+        file.read_until(0, &mut header)?;
+        let Some(header) = header.strip_prefix(CHUNKED_PREFIX) else {
+            return ObjectReader::new(hash, Box::new(Cursor::new(header).chain(file)));
+        };
+
+        let chunks: VecDeque<(PathBuf, u64)> = read_chunk_list(file)?
+            .into_iter()
+            .map(|(chunk, length)| Some((self.chunk_path(&chunk)?, length)))
+            .collect::<Option<_>>()
+            .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+        let listed: u64 = chunks.iter().map(|(_, length)| length).sum();
+        let chunks = ChunkReader {
+            chunks,
+            current: None,
+        };
+        let content = BufReader::with_capacity(stream::buffer_size(), chunks);
+        let reader =
+            ObjectReader::new(hash, Box::new(Cursor::new(header.to_vec()).chain(content)))?;
+        match reader.size == listed {
+            true => Ok(reader),
+            false => Err(NyxError::InvalidObject(format!(
+                "{} lists chunks of {} bytes for {} bytes",
+                hash, listed, reader.size
+            ))),
+        }
+    }
+
+    fn write(&self, hash: &str, raw: &[u8]) -> Result<(), NyxError> {
+        let path = self
+            .object_path(hash)
+            .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+        if path.is_file() {
+            return Ok(());
+        }
+        if let Some((NyxObjectType::Blob, content)) = split_raw_object(raw) {
+            if self.is_chunked(content.len() as u64) {
+                let written = self.write_chunked(content.len() as u64, &mut &content[..])?;
+                return match written == hash {
+                    true => Ok(()),
+                    false => Err(NyxError::InvalidObject(hash.to_string())),
+                };
+            }
+        }
+        self.write_file(&path, raw)
+    }
+
+    /// Streams into a temporary file that is renamed once the hash is known.
+    fn write_stream(
+        &self,
+        object_type: NyxObjectType,
+        size: u64,
+        reader: &mut dyn Read,
+    ) -> Result<String, NyxError> {
+        if object_type == NyxObjectType::Blob && self.is_chunked(size) {
+            return self.write_chunked(size, reader);
+        }
+        fs::create_dir_all(&self.dir)?;
+        let temp_path = self.temp_path();
+        let written = (|| {
+            let file = BufWriter::with_capacity(stream::buffer_size(), File::create(&temp_path)?);
+            let mut writer = ObjectWriter::with_format(file, object_type, size, self.format)?;
+            stream::copy(reader, &mut writer)?;
+            writer.finish().map(|(_, hash)| hash)
+        })();
+        let hash = match written {
+            Ok(hash) => hash,
+            Err(err) => {
+                _ = fs::remove_file(&temp_path);
+                return Err(err);
+            }
+        };
+
+        let path = self
+            .object_path(&hash)
+            .ok_or_else(|| NyxError::InvalidObject(hash.clone()))?;
+        self.persist(&temp_path, &path)?;
+        Ok(hash)
+    }
+
+    fn exists(&self, hash: &str) -> bool {
+        self.object_path(hash).is_some_and(|path| path.is_file())
+    }
+
+    /// The modification time of the object file.
+    fn written_at(&self, hash: &str) -> Result<Option<i64>, NyxError> {
+        let path = self
+            .object_path(hash)
+            .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+        let written = fs::metadata(path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Ok(Some(written))
+    }
+
+    fn removable_size(&self, hashes: &HashSet<String>) -> Result<u64, NyxError> {
+        let mut size = 0;
+        for path in self.removable_files(hashes)? {
+            size += fs::metadata(path)?.len();
+        }
+        Ok(size)
+    }
+
+    fn remove(&self, hashes: &HashSet<String>) -> Result<u64, NyxError> {
+        let mut size = 0;
+        for path in self.removable_files(hashes)? {
+            size += fs::metadata(&path)?.len();
+            remove_file(&path)?;
+        }
+        Ok(size)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        let mut prefixes: Vec<String> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|dir| dir.path().is_dir())
+            .map(|dir| dir.file_name().to_string_lossy().to_string())
+            .filter(|prefix| prefix.len() == 2)
+            .collect();
+        prefixes.sort();
+        Box::new(prefixes.into_iter().flat_map(|prefix| {
+            let mut hashes = self.hashes_in(&prefix);
+            hashes.sort();
+            hashes
+        }))
+    }
+
+    /// Only reads the directory of the first two hex digits.
+    fn with_prefix(&self, prefix: &str) -> Vec<String> {
+        if prefix.len() < 2 || !prefix.is_char_boundary(2) {
+            return self
+                .iter()
+                .filter(|hash| hash.starts_with(prefix))
+                .collect();
+        }
+        let mut hashes: Vec<String> = self
+            .hashes_in(&prefix[..2])
+            .into_iter()
+            .filter(|hash| hash.starts_with(prefix))
+            .collect();
+        hashes.sort();
+        hashes
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::RwLock;
+
+use crate::core::errors::NyxError;
+
+use super::database::ObjectDatabase;
+
+/// Objects kept in memory only, for tests and tools that must not
+/// touch the disk.
+#[derive(Default)]
+pub struct MemoryObjectDatabase {
+    objects: RwLock<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryObjectDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ObjectDatabase for MemoryObjectDatabase {
+    fn read(&self, hash: &str) -> Result<Vec<u8>, NyxError> {
+        self.objects
+            .read()
+            .unwrap()
+            .get(hash)
+            .cloned()
+            .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))
+    }
+
+    fn write(&self, hash: &str, raw: &[u8]) -> Result<(), NyxError> {
+        self.objects
+            .write()
+            .unwrap()
+            .entry(hash.to_string())
+            .or_insert_with(|| raw.to_vec());
+        Ok(())
+    }
+
+    fn exists(&self, hash: &str) -> bool {
+        self.objects.read().unwrap().contains_key(hash)
+    }
+
+    fn removable_size(&self, hashes: &HashSet<String>) -> Result<u64, NyxError> {
+        let objects = self.objects.read().unwrap();
+        Ok(hashes
+            .iter()
+            .filter_map(|hash| objects.get(hash))
+            .map(|raw| raw.len() as u64)
+            .sum())
+    }
+
+    fn remove(&self, hashes: &HashSet<String>) -> Result<u64, NyxError> {
+        let mut objects = self.objects.write().unwrap();
+        Ok(hashes
+            .iter()
+            .filter_map(|hash| objects.remove(hash))
+            .map(|raw| raw.len() as u64)
+            .sum())
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        let hashes: Vec<String> = self.objects.read().unwrap().keys().cloned().collect();
+        Box::new(hashes.into_iter())
+    }
+}
@@ -0,0 +1,8 @@
+pub mod chunker;
+pub mod convert;
+pub mod database;
+pub mod loose;
+pub mod memory;
+pub mod reader;
+pub mod stream;
+pub mod writer;
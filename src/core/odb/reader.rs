@@ -0,0 +1,46 @@
+use std::io::{self, BufRead, Read};
+
+use crate::core::errors::NyxError;
+use crate::core::object_type::NyxObjectType;
+
+/// Content of a stored object, read in chunks after its header.
+pub struct ObjectReader {
+    pub object_type: NyxObjectType,
+    pub size: u64,
+    inner: io::Take<Box<dyn BufRead>>,
+}
+
+impl ObjectReader {
+    /// Reads the `<type> <size>\0` header of a raw object stream.
+    pub fn new(hash: &str, mut raw: Box<dyn BufRead>) -> Result<Self, NyxError> {
+        let invalid = || NyxError::InvalidObject(hash.to_string());
+        let mut header = Vec::new();
+        raw.read_until(0, &mut header)?;
+        if header.pop() != Some(0) {
+            return Err(invalid());
+        }
+        let header = std::str::from_utf8(&header)?;
+        let (object_type, size) = header.split_once(' ').ok_or_else(invalid)?;
+        let object_type = NyxObjectType::parse(object_type).ok_or_else(invalid)?;
+        let size: u64 = size.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            object_type,
+            size,
+            inner: raw.take(size),
+        })
+    }
+}
+
+impl ObjectReader {
+    /// Whether the stream goes on after the content the header declares.
+    pub fn has_trailing_data(self) -> io::Result<bool> {
+        let mut raw = self.inner.into_inner();
+        Ok(!raw.fill_buf()?.is_empty())
+    }
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
@@ -0,0 +1,37 @@
+use std::io::{self, Read, Write};
+use std::sync::OnceLock;
+
+use crate::core::config::Config;
+
+/// Chunk size of streamed object content unless `core.streamBufferSize`
+/// says otherwise.
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Size of the chunks files and objects are streamed in, so memory use
+/// does not grow with the size of a file.
+pub fn buffer_size() -> usize {
+    static BUFFER_SIZE: OnceLock<usize> = OnceLock::new();
+    *BUFFER_SIZE.get_or_init(|| {
+        Config::new()
+            .get("core.streamBufferSize")
+            .and_then(|size| size.parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_BUFFER_SIZE)
+    })
+}
+
+/// Copies everything from `reader` to `writer` in chunks of `buffer_size`.
+pub fn copy(reader: &mut dyn Read, writer: &mut dyn Write) -> io::Result<u64> {
+    let mut buffer = vec![0; buffer_size()];
+    let mut copied = 0;
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(copied),
+            Ok(count) => count,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        writer.write_all(&buffer[..count])?;
+        copied += count as u64;
+    }
+}
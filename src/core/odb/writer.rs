@@ -0,0 +1,70 @@
+use std::io::{self, Write};
+
+use crate::core::errors::NyxError;
+use crate::core::object_format::{object_format, Hasher, ObjectFormat};
+use crate::core::object_type::NyxObjectType;
+
+/// Writes an object with its header to `inner` and computes its hash
+/// on the way, so content of any size passes through in chunks.
+pub struct ObjectWriter<W: Write> {
+    inner: W,
+    hasher: Hasher,
+    remaining: u64,
+}
+
+impl<W: Write> ObjectWriter<W> {
+    /// Starts an object of `size` content bytes by writing its header.
+    pub fn new(inner: W, object_type: NyxObjectType, size: u64) -> io::Result<Self> {
+        Self::with_format(inner, object_type, size, object_format())
+    }
+
+    /// Like `new`, but hashes for the given object format instead of the
+    /// one of the repository.
+    pub fn with_format(
+        mut inner: W,
+        object_type: NyxObjectType,
+        size: u64,
+        format: ObjectFormat,
+    ) -> io::Result<Self> {
+        let header = format!("{} {}\0", object_type, size);
+        inner.write_all(header.as_bytes())?;
+        let mut hasher = format.hasher();
+        hasher.update(header.as_bytes());
+        Ok(Self {
+            inner,
+            hasher,
+            remaining: size,
+        })
+    }
+
+    /// Returns the writer and the hash once all content was written.
+    pub fn finish(mut self) -> Result<(W, String), NyxError> {
+        if self.remaining != 0 {
+            return Err(NyxError::InvalidObject(format!(
+                "{} bytes short of the declared size",
+                self.remaining
+            )));
+        }
+        self.inner.flush()?;
+        Ok((self.inner, self.hasher.finish()))
+    }
+}
+
+impl<W: Write> Write for ObjectWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() as u64 > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "content is longer than the declared size",
+            ));
+        }
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.remaining -= written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,185 @@
+use std::env;
+
+use glob::{MatchOptions, Pattern};
+
+use crate::FILE_SYSTEM;
+
+use super::errors::NyxError;
+
+const GLOB_CHARS: [char; 3] = ['*', '?', '['];
+
+/// A single pattern of a pathspec, relative to the repository root.
+struct Item {
+    spec: String,
+    path: String,
+    glob: Option<Pattern>,
+    exclude: bool,
+}
+
+impl Item {
+    /// Parses the magic of `:(top,exclude)path`, `:/path` and `:!path`.
+    fn parse(spec: &str, prefix: &str) -> Result<Self, NyxError> {
+        let (mut top, mut exclude) = (false, false);
+        let mut rest = spec;
+        if let Some(magic) = spec.strip_prefix(":(") {
+            let (words, path) = magic
+                .split_once(')')
+                .ok_or_else(|| NyxError::Usage(format!("missing ')' in pathspec '{}'", spec)))?;
+            for word in words.split(',').filter(|word| !word.is_empty()) {
+                match word {
+                    "top" => top = true,
+                    "exclude" => exclude = true,
+                    _ => {
+                        return Err(NyxError::Usage(format!(
+                            "unsupported pathspec magic '{}' in '{}'",
+                            word, spec
+                        )))
+                    }
+                }
+            }
+            rest = path;
+        } else if let Some(path) = spec.strip_prefix(':') {
+            rest = path.trim_start_matches(|c| match c {
+                '/' => {
+                    top = true;
+                    true
+                }
+                '!' | '^' => {
+                    exclude = true;
+                    true
+                }
+                _ => false,
+            });
+        }
+
+        let path = normalize(if top { "" } else { prefix }, rest)
+            .ok_or_else(|| NyxError::Usage(format!("'{}' is outside repository", spec)))?;
+        let glob =
+            match path.contains(GLOB_CHARS) {
+                true => Some(Pattern::new(&path).map_err(|err| {
+                    NyxError::Usage(format!("invalid pathspec '{}': {}", spec, err))
+                })?),
+                false => None,
+            };
+        Ok(Self {
+            spec: spec.to_string(),
+            path,
+            glob,
+            exclude,
+        })
+    }
+
+    /// Paths match if they are the path of the item, inside it or
+    /// match its glob. Wildcards match slashes as well.
+    fn matches(&self, path: &str) -> bool {
+        if self.path.is_empty()
+            || path == self.path
+            || path
+                .strip_prefix(&self.path)
+                .is_some_and(|rest| rest.starts_with('/'))
+        {
+            return true;
+        }
+        let Some(glob) = &self.glob else {
+            return false;
+        };
+        // A directory matching the glob matches everything inside it
+        let mut candidate = path;
+        loop {
+            if glob.matches_with(candidate, MatchOptions::new()) {
+                return true;
+            }
+            match candidate.rsplit_once('/') {
+                Some((parent, _)) => candidate = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+/// Joins `path` to the directory `prefix`, resolving `.` and `..`.
+/// Returns `None` for paths leaving the repository.
+fn normalize(prefix: &str, path: &str) -> Option<String> {
+    let mut components: Vec<&str> = Vec::new();
+    for component in prefix.split('/').chain(path.split('/')) {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop()?;
+            }
+            _ => components.push(component),
+        }
+    }
+    Some(components.join("/"))
+}
+
+/// Paths relative to the repository root without their `./` start.
+fn relative(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Selects paths of the repository for commands like `add`, `reset`
+/// and `restore`. Patterns are relative to the current directory unless
+/// they start with `:(top)` or `:/`, those starting with `:(exclude)`,
+/// `:!` or `:^` leave out what they match. An empty pathspec matches
+/// every path.
+pub struct Pathspec {
+    items: Vec<Item>,
+}
+
+impl Pathspec {
+    pub fn parse(specs: &[String]) -> Result<Self, NyxError> {
+        let root = FILE_SYSTEM.get_root_dir();
+        let cwd = env::current_dir()?;
+        let prefix = cwd
+            .strip_prefix(root)
+            .ok()
+            .and_then(|prefix| prefix.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let items = specs
+            .iter()
+            .map(|spec| Item::parse(spec, &prefix))
+            .collect::<Result<Vec<Item>, NyxError>>()?;
+        Ok(Self { items })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Whether the pathspec selects the path relative to the repository
+    /// root, with or without a leading `./`.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = relative(path);
+        let mut included = self.items.iter().filter(|item| !item.exclude).peekable();
+        let is_included = included.peek().is_none() || included.any(|item| item.matches(path));
+        is_included
+            && !self
+                .items
+                .iter()
+                .any(|item| item.exclude && item.matches(path))
+    }
+
+    /// Whether a pattern names exactly this path rather than selecting
+    /// it by a directory or a glob, e.g. to complain about ignored files.
+    pub fn names(&self, path: &str) -> bool {
+        let path = relative(path);
+        self.items
+            .iter()
+            .any(|item| !item.exclude && item.glob.is_none() && item.path == path)
+    }
+
+    /// The patterns that select none of the paths.
+    pub fn unmatched<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut unmatched: Vec<&Item> = self.items.iter().filter(|item| !item.exclude).collect();
+        for path in paths {
+            let path = relative(path);
+            unmatched.retain(|item| !item.matches(path));
+            if unmatched.is_empty() {
+                break;
+            }
+        }
+        unmatched.iter().map(|item| item.spec.clone()).collect()
+    }
+}
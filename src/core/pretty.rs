@@ -0,0 +1,196 @@
+use colored::Colorize;
+
+use super::commit::Commit;
+use super::date::{format_date, format_relative_date, format_short_date};
+use super::sequencer::short_hash;
+use super::signature::Signature;
+
+/// How a commit is printed by `log` and `show`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitFormat {
+    Oneline,
+    Short,
+    Medium,
+    Full,
+    Template(String),
+}
+
+impl CommitFormat {
+    /// Accepts the names of the builtin formats or a template with
+    /// placeholders, optionally prefixed with `format:` or `tformat:`.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "oneline" => CommitFormat::Oneline,
+            "short" => CommitFormat::Short,
+            "medium" => CommitFormat::Medium,
+            "full" => CommitFormat::Full,
+            _ => {
+                let template = value
+                    .strip_prefix("format:")
+                    .or_else(|| value.strip_prefix("tformat:"))
+                    .unwrap_or(value);
+                CommitFormat::Template(template.to_string())
+            }
+        }
+    }
+
+    pub fn format(&self, commit: &Commit) -> String {
+        match self {
+            CommitFormat::Oneline => format!(
+                "{} {}",
+                short_hash(commit.get_hash()).yellow(),
+                commit.summary()
+            ),
+            CommitFormat::Short => header(commit, false, false) + &indented_message(commit, true),
+            CommitFormat::Medium => medium(commit),
+            CommitFormat::Full => header(commit, true, false) + &indented_message(commit, false),
+            CommitFormat::Template(template) => expand_template(template, commit),
+        }
+    }
+
+    /// Multi line formats are separated by an empty line in a log.
+    pub fn is_multiline(&self) -> bool {
+        matches!(
+            self,
+            CommitFormat::Short | CommitFormat::Medium | CommitFormat::Full
+        )
+    }
+}
+
+fn header(commit: &Commit, with_committer: bool, with_date: bool) -> String {
+    let mut header = format!("{} {}\n", "commit".yellow(), commit.get_hash().yellow());
+    if commit.parent_hashes().len() > 1 {
+        let parents: Vec<&str> = commit
+            .parent_hashes()
+            .iter()
+            .map(|p| short_hash(p))
+            .collect();
+        header.push_str(&format!("Merge: {}\n", parents.join(" ")));
+    }
+    if let Some(author) = commit.author() {
+        header.push_str(&format!("Author: {} <{}>\n", author.name, author.email));
+        if with_date {
+            header.push_str(&format!(
+                "Date:   {}\n",
+                format_date(author.time, &author.offset)
+            ));
+        }
+    }
+    if with_committer {
+        if let Some(committer) = commit.committer() {
+            header.push_str(&format!(
+                "Commit: {} <{}>\n",
+                committer.name, committer.email
+            ));
+        }
+    }
+    header
+}
+
+/// The default format: hash, merge parents, author, date and message.
+pub fn medium(commit: &Commit) -> String {
+    header(commit, false, true) + &indented_message(commit, false)
+}
+
+fn indented_message(commit: &Commit, summary_only: bool) -> String {
+    let message = if summary_only {
+        commit.summary()
+    } else {
+        commit.message()
+    };
+    let lines: Vec<String> = message
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {}", line)
+            }
+        })
+        .collect();
+    format!("\n{}", lines.join("\n"))
+}
+
+fn body(commit: &Commit) -> String {
+    match commit.message().split_once("\n\n") {
+        Some((_, body)) => body.to_string(),
+        None => String::new(),
+    }
+}
+
+fn signature_placeholder(signature: Option<&Signature>, key: char) -> String {
+    let signature = match signature {
+        Some(signature) => signature,
+        None => return String::new(),
+    };
+    match key {
+        'n' => signature.name.clone(),
+        'e' => signature.email.clone(),
+        'd' => format_date(signature.time, &signature.offset),
+        'D' => format_short_date(signature.time, &signature.offset),
+        'r' => format_relative_date(signature.time),
+        't' => signature.time.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Expands placeholders like `%h %an %s`:
+///
+/// `%H`/`%h` (abbreviated) commit hash, `%T`/`%t` tree hash,
+/// `%P`/`%p` parent hashes, `%an %ae %ad %aD %ar %at` author name,
+/// email, date, short date, relative date and timestamp, `%c..`
+/// the same for the committer, `%s` subject, `%b` body, `%B` raw
+/// message, `%n` newline and `%%` a literal percent sign.
+pub fn expand_template(template: &str, commit: &Commit) -> String {
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        let key = match chars.next() {
+            Some(key) => key,
+            None => {
+                output.push('%');
+                break;
+            }
+        };
+        match key {
+            'H' => output.push_str(commit.get_hash()),
+            'h' => output.push_str(short_hash(commit.get_hash())),
+            'T' => output.push_str(commit.tree_hash()),
+            't' => output.push_str(short_hash(commit.tree_hash())),
+            'P' => output.push_str(&commit.parent_hashes().join(" ")),
+            'p' => {
+                let parents: Vec<&str> = commit
+                    .parent_hashes()
+                    .iter()
+                    .map(|p| short_hash(p))
+                    .collect();
+                output.push_str(&parents.join(" "));
+            }
+            'a' | 'c' => {
+                let signature = if key == 'a' {
+                    commit.author()
+                } else {
+                    commit.committer()
+                };
+                if let Some(field) = chars.next() {
+                    output.push_str(&signature_placeholder(signature, field));
+                }
+            }
+            's' => output.push_str(commit.summary()),
+            'b' => output.push_str(&body(commit)),
+            'B' => output.push_str(commit.message()),
+            'n' => output.push('\n'),
+            '%' => output.push('%'),
+            other => {
+                output.push('%');
+                output.push(other);
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::fs;
+
+use super::errors::NyxError;
+use super::index::index::Index;
+use super::object_format::object_format;
+use super::object_type::NyxObjectType;
+use super::refs::reflog::Reflog;
+use super::refs::refs;
+use super::shared::{read_object_data, read_object_type};
+use crate::FILE_SYSTEM;
+
+/// Files of operations in progress that name commits to return to.
+pub const STATE_FILES: [&str; 7] = [
+    "FETCH_HEAD",
+    "CHERRY_PICK_HEAD",
+    "REVERT_HEAD",
+    "sequencer/head",
+    "rebase-merge/onto",
+    "rebase-merge/orig-head",
+    "rebase-merge/stopped-sha",
+];
+
+/// Hashes of the objects an object points to: the tree and parents of
+/// a commit, the entries of a tree and the target of a tag.
+pub fn object_references(hash: &str) -> Result<Vec<String>, NyxError> {
+    // Blobs reference nothing, large ones are not worth reading
+    let object_type = read_object_type(hash)?;
+    if object_type == NyxObjectType::Blob {
+        return Ok(Vec::new());
+    }
+    Ok(content_references(object_type, &read_object_data(hash)?))
+}
+
+/// Like `object_references` for an object that is not stored (yet).
+pub fn content_references(object_type: NyxObjectType, content: &str) -> Vec<String> {
+    let headers = content.lines().take_while(|line| !line.is_empty());
+    let header_values = |keys: &[&str]| -> Vec<String> {
+        headers
+            .clone()
+            .filter_map(|line| line.split_once(' '))
+            .filter(|(key, value)| keys.contains(key) && is_object_hash(value))
+            .map(|(_, value)| value.to_string())
+            .collect()
+    };
+    match object_type {
+        NyxObjectType::Commit => header_values(&["tree", "parent"]),
+        NyxObjectType::Tag => header_values(&["object"]),
+        NyxObjectType::Tree => content
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .map(String::from)
+            .collect(),
+        NyxObjectType::Blob => Vec::new(),
+    }
+}
+
+/// Hashes pointed to by refs, HEAD, the index and operations in
+/// progress. With `with_reflogs` all values recorded in reflogs count
+/// as roots as well.
+pub fn root_hashes(with_reflogs: bool) -> Vec<String> {
+    let mut roots = vec![refs::head_hash()];
+    roots.extend(refs::list_refs("").into_iter().map(|(_, hash)| hash));
+    roots.extend(Index::new().entries().iter().map(|e| e.hash.clone()));
+
+    for name in STATE_FILES {
+        if let Ok(content) = fs::read_to_string(FILE_SYSTEM.get_repo_dir().join(name)) {
+            roots.extend(
+                content
+                    .lines()
+                    .filter_map(|line| line.split_whitespace().next())
+                    .map(String::from),
+            );
+        }
+    }
+    if with_reflogs {
+        for name in Reflog::names() {
+            for entry in Reflog::new(&name).entries() {
+                roots.push(entry.old_hash.clone());
+                roots.push(entry.new_hash.clone());
+            }
+        }
+    }
+
+    roots.retain(|hash| is_object_hash(hash) && !refs::is_zero_hash(hash));
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+pub fn is_object_hash(hash: &str) -> bool {
+    object_format().is_hash(hash)
+}
+
+/// All objects reachable from the roots. Missing or broken objects are
+/// included but not followed.
+pub fn reachable_objects(roots: &[String]) -> HashSet<String> {
+    let mut reachable = HashSet::new();
+    let mut stack: Vec<String> = roots.to_vec();
+    while let Some(hash) = stack.pop() {
+        if !reachable.insert(hash.clone()) {
+            continue;
+        }
+        if let Ok(references) = object_references(&hash) {
+            stack.extend(references.into_iter().filter(|h| !reachable.contains(h)));
+        }
+    }
+    reachable
+}
@@ -0,0 +1,2 @@
+pub mod rebase;
+pub mod todo;
@@ -0,0 +1,462 @@
+use std::fs;
+use std::process::Command;
+
+use crate::core::{
+    commit::Commit,
+    editor::{edit_file, strip_comments},
+    errors::NyxError,
+    index::index::Index,
+    refs::refs,
+    rev_walk::{commits_between, merge_base},
+    revision::resolve_commit,
+    sequencer::{amend_head, apply_change, commit_index, short_hash, Action, PickOutcome},
+    signature::Signature,
+    tree::tree::Tree,
+    worktree::{reset_hard, staged_changes, unstaged_changes},
+};
+use crate::FILE_SYSTEM;
+
+use super::todo::{parse_todo, validate_todo, TodoCommand, TodoItem, TODO_HELP};
+
+const DETACHED: &str = "detached HEAD";
+
+/// State of a rebase in progress, persisted in `.nyx/rebase-merge/`.
+pub struct Rebase {
+    /// Branch that is rebased or `None` for a detached HEAD
+    head_name: Option<String>,
+    onto: String,
+    orig_head: String,
+    todo: Vec<TodoItem>,
+    done: Vec<TodoItem>,
+}
+
+impl Rebase {
+    pub fn in_progress() -> bool {
+        FILE_SYSTEM.get_rebase_dir_path().exists()
+    }
+
+    /// Replays the commits of `upstream..HEAD` on top of `onto`
+    /// (defaults to `upstream`).
+    pub fn start(upstream: &str, onto: Option<&str>, interactive: bool) -> Result<(), NyxError> {
+        if Rebase::in_progress() {
+            return Err(NyxError::Usage(String::from(
+                "a rebase is already in progress, try --continue, --skip or --abort",
+            )));
+        }
+        let head = Commit::read(&refs::head_hash())?;
+        let index = Index::new();
+        let head_paths = Tree::read_paths(Some(head.tree_hash()));
+        if !staged_changes(&index, &head_paths).is_empty() || !unstaged_changes(&index).is_empty() {
+            return Err(NyxError::Usage(String::from(
+                "cannot rebase: You have unstaged or uncommitted changes.",
+            )));
+        }
+
+        let upstream_commit = resolve_commit(upstream)?;
+        let onto_commit = match onto {
+            Some(onto) => resolve_commit(onto)?,
+            None => upstream_commit.clone(),
+        };
+
+        let commits: Vec<Commit> =
+            commits_between(&[upstream_commit.get_hash().to_string()], head.get_hash())
+                .into_iter()
+                .filter(|commit| commit.parent_hashes().len() <= 1)
+                .collect();
+
+        let base = merge_base(head.get_hash(), onto_commit.get_hash());
+        if !interactive && onto.is_none() && base.as_deref() == Some(onto_commit.get_hash()) {
+            println!("Current branch is up to date.");
+            return Ok(());
+        }
+
+        let mut todo: Vec<TodoItem> = commits.iter().map(TodoItem::pick).collect();
+        if interactive {
+            todo = Rebase::edit_todo(
+                &todo,
+                upstream_commit.get_hash(),
+                head.get_hash(),
+                onto_commit.get_hash(),
+            )?;
+            if todo.is_empty() {
+                println!("Nothing to do");
+                return Ok(());
+            }
+        }
+
+        let mut rebase = Rebase {
+            head_name: refs::current_branch(),
+            onto: onto_commit.get_hash().to_string(),
+            orig_head: head.get_hash().to_string(),
+            todo,
+            done: Vec::new(),
+        };
+
+        // Work on a detached HEAD, the branch is only moved at the end
+        reset_hard(
+            &mut Index::new(),
+            &Tree::read_paths(Some(onto_commit.tree_hash())),
+        )?;
+        refs::detach_head(
+            &rebase.onto,
+            &format!("rebase (start): checkout {}", onto.unwrap_or(upstream)),
+        )?;
+        rebase.save()?;
+        rebase.run()
+    }
+
+    /// Lets the user edit the todo list and returns the parsed result.
+    fn edit_todo(
+        todo: &[TodoItem],
+        upstream: &str,
+        head: &str,
+        onto: &str,
+    ) -> Result<Vec<TodoItem>, NyxError> {
+        let path = FILE_SYSTEM.get_repo_dir().join("rebase-todo");
+        let lines: Vec<String> = todo.iter().map(|item| item.to_short_line()).collect();
+        let header = format!(
+            "\n# Rebase {}..{} onto {} ({} commands)",
+            short_hash(upstream),
+            short_hash(head),
+            short_hash(onto),
+            todo.len()
+        );
+        fs::write(
+            &path,
+            format!("{}\n{}{}", lines.join("\n"), header, TODO_HELP),
+        )?;
+        let edited = edit_file(&path).and_then(|_| Ok(fs::read_to_string(&path)?));
+        fs::remove_file(&path)?;
+        let todo = parse_todo(&edited?)?;
+        validate_todo(&todo)?;
+        Ok(todo)
+    }
+
+    fn run(&mut self) -> Result<(), NyxError> {
+        while !self.todo.is_empty() {
+            let item = self.todo.remove(0);
+            self.done.push(item.clone());
+            self.save()?;
+
+            match item.command {
+                TodoCommand::Drop => (),
+                TodoCommand::Exec => {
+                    println!("Executing: {}", item.argument);
+                    let status = Command::new("sh")
+                        .arg("-c")
+                        .arg(&item.argument)
+                        .current_dir(FILE_SYSTEM.get_root_dir())
+                        .status()?;
+                    if !status.success() {
+                        println!(
+                            "You can fix the problem, and then run\n\n    nyx rebase --continue\n"
+                        );
+                        return Err(NyxError::Usage(format!(
+                            "Execution failed: {}",
+                            item.argument
+                        )));
+                    }
+                }
+                _ => {
+                    if self.apply(&item)? {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        self.finish()
+    }
+
+    /// Applies a commit of the todo list. Returns true if the rebase stops.
+    fn apply(&mut self, item: &TodoItem) -> Result<bool, NyxError> {
+        let commit = Commit::read(&item.argument)?;
+        let melds = matches!(item.command, TodoCommand::Squash | TodoCommand::Fixup);
+        let message = if melds {
+            let head = Commit::read(&refs::head_hash())?;
+            match item.command {
+                TodoCommand::Squash => format!("{}\n\n{}", head.message(), commit.message()),
+                _ => head.message().to_string(),
+            }
+        } else {
+            commit.message().to_string()
+        };
+
+        match apply_change(&commit, Action::Pick, None)? {
+            PickOutcome::Conflict(paths) => {
+                self.write_stop_files(&commit, &message, melds)?;
+                for path in &paths {
+                    println!("CONFLICT (content): Merge conflict in {}", path);
+                }
+                println!(
+                    "error: could not apply {}... {}",
+                    short_hash(commit.get_hash()),
+                    commit.summary()
+                );
+                println!("hint: Resolve all conflicts manually, mark them as resolved with");
+                println!("hint: 'nyx add <paths>', then run 'nyx rebase --continue'.");
+                println!("hint: To skip this commit run 'nyx rebase --skip', to abort run 'nyx rebase --abort'.");
+                Err(NyxError::MergeConflict(paths))
+            }
+            outcome => {
+                self.commit_step(
+                    item.command,
+                    &commit,
+                    &message,
+                    outcome == PickOutcome::Empty,
+                )?;
+                if item.command == TodoCommand::Edit {
+                    return self.stop_for_edit(&commit);
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    /// Records the result of an applied todo item as commit.
+    fn commit_step(
+        &self,
+        command: TodoCommand,
+        commit: &Commit,
+        message: &str,
+        empty: bool,
+    ) -> Result<(), NyxError> {
+        match command {
+            TodoCommand::Squash => {
+                let message = Rebase::edit_message(message)?;
+                amend_head(&message, "rebase (squash)")?;
+            }
+            TodoCommand::Fixup => {
+                amend_head(message, "rebase (fixup)")?;
+            }
+            _ if empty => {
+                println!(
+                    "dropping {} {} -- patch contents already upstream",
+                    short_hash(commit.get_hash()),
+                    commit.summary()
+                );
+            }
+            TodoCommand::Reword => {
+                let message = Rebase::edit_message(message)?;
+                commit_index(&message, commit.author().cloned(), "rebase (reword)")?;
+            }
+            _ => {
+                commit_index(message, commit.author().cloned(), "rebase (pick)")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn stop_for_edit(&self, commit: &Commit) -> Result<bool, NyxError> {
+        fs::write(self.state_path("amend"), refs::head_hash())?;
+        println!(
+            "Stopped at {}... {}\nYou can amend the commit now by staging changes and running\n\n    nyx rebase --continue\n",
+            short_hash(commit.get_hash()),
+            commit.summary()
+        );
+        Ok(true)
+    }
+
+    fn edit_message(message: &str) -> Result<String, NyxError> {
+        let path = FILE_SYSTEM.get_repo_dir().join("COMMIT_EDITMSG");
+        fs::write(
+            &path,
+            format!(
+                "{}\n\n# Please enter the commit message for your changes. Lines starting\n# with '#' will be ignored.\n",
+                message
+            ),
+        )?;
+        edit_file(&path)?;
+        let edited = strip_comments(&fs::read_to_string(&path)?);
+        if edited.is_empty() {
+            return Err(NyxError::Usage(String::from(
+                "Aborting commit due to empty commit message.",
+            )));
+        }
+        Ok(edited)
+    }
+
+    fn write_stop_files(
+        &self,
+        commit: &Commit,
+        message: &str,
+        melds: bool,
+    ) -> Result<(), NyxError> {
+        fs::write(self.state_path("stopped-sha"), commit.get_hash())?;
+        fs::write(self.state_path("message"), message)?;
+        if let Some(author) = commit.author() {
+            fs::write(self.state_path("author"), author.to_string())?;
+        }
+        if melds {
+            fs::write(self.state_path("amend"), refs::head_hash())?;
+        }
+        Ok(())
+    }
+
+    fn remove_stop_files(&self) -> Result<(), NyxError> {
+        for name in ["stopped-sha", "message", "author", "amend"] {
+            let path = self.state_path(name);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Commits the resolved conflict or the amended commit and carries on.
+    pub fn resume() -> Result<(), NyxError> {
+        let mut rebase = Rebase::load()?;
+        let index = Index::new();
+        let unmerged = index.unmerged_paths();
+        if !unmerged.is_empty() {
+            return Err(NyxError::Usage(format!(
+                "You must edit all merge conflicts and then mark them as resolved using nyx add: {}",
+                unmerged.join(", ")
+            )));
+        }
+
+        let amend = fs::read_to_string(rebase.state_path("amend")).ok();
+        let stopped = fs::read_to_string(rebase.state_path("stopped-sha")).ok();
+        let command = rebase.done.last().map(|item| item.command);
+
+        if let Some(stopped) = stopped {
+            let commit = Commit::read(stopped.trim())?;
+            let message = fs::read_to_string(rebase.state_path("message"))
+                .unwrap_or_else(|_| commit.message().to_string());
+            let author = fs::read_to_string(rebase.state_path("author"))
+                .ok()
+                .and_then(|author| Signature::parse(&author));
+
+            match command {
+                Some(TodoCommand::Squash) => {
+                    amend_head(&Rebase::edit_message(&message)?, "rebase (squash)")?;
+                }
+                Some(TodoCommand::Fixup) => {
+                    amend_head(&message, "rebase (fixup)")?;
+                }
+                Some(TodoCommand::Reword) => {
+                    commit_index(&Rebase::edit_message(&message)?, author, "rebase (reword)")?;
+                }
+                _ => {
+                    commit_index(&message, author, "rebase (continue)")?;
+                }
+            }
+            rebase.remove_stop_files()?;
+            if command == Some(TodoCommand::Edit) {
+                rebase.stop_for_edit(&commit)?;
+                return Ok(());
+            }
+        } else if amend.is_some() {
+            let head = Commit::read(&refs::head_hash())?;
+            if Index::new().to_tree().hash != head.tree_hash() {
+                amend_head(head.message(), "rebase (amend)")?;
+            }
+            rebase.remove_stop_files()?;
+        }
+        rebase.run()
+    }
+
+    /// Drops the commit that stopped the rebase and carries on.
+    pub fn skip() -> Result<(), NyxError> {
+        let mut rebase = Rebase::load()?;
+        let head = Commit::read(&refs::head_hash())?;
+        reset_hard(&mut Index::new(), &Tree::read_paths(Some(head.tree_hash())))?;
+        rebase.remove_stop_files()?;
+        rebase.run()
+    }
+
+    /// Restores the branch and working tree as they were before the rebase.
+    pub fn abort() -> Result<(), NyxError> {
+        let rebase = Rebase::load()?;
+        let original = Commit::read(&rebase.orig_head)?;
+        reset_hard(
+            &mut Index::new(),
+            &Tree::read_paths(Some(original.tree_hash())),
+        )?;
+
+        let message = format!("rebase (abort): returning to {}", rebase.head_description());
+        match &rebase.head_name {
+            Some(branch) => {
+                let current = refs::head_hash();
+                refs::set_head_to_branch(branch)?;
+                refs::append_head_log(&current, &rebase.orig_head, &message)?;
+            }
+            None => refs::detach_head(&rebase.orig_head, &message)?,
+        }
+        Rebase::clear()
+    }
+
+    fn finish(&self) -> Result<(), NyxError> {
+        let head = refs::head_hash();
+        match &self.head_name {
+            Some(branch) => {
+                refs::update_ref(
+                    branch,
+                    &head,
+                    &format!("rebase (finish): refs/{} onto {}", branch, self.onto),
+                )?;
+                refs::set_head_to_branch(branch)?;
+                refs::append_head_log(
+                    &head,
+                    &head,
+                    &format!("rebase (finish): returning to refs/{}", branch),
+                )?;
+                println!("Successfully rebased and updated refs/{}.", branch);
+            }
+            None => println!("Successfully rebased."),
+        }
+        Rebase::clear()
+    }
+
+    fn head_description(&self) -> String {
+        match &self.head_name {
+            Some(branch) => format!("refs/{}", branch),
+            None => self.orig_head.clone(),
+        }
+    }
+
+    fn state_path(&self, name: &str) -> std::path::PathBuf {
+        FILE_SYSTEM.get_rebase_dir_path().join(name)
+    }
+
+    fn save(&self) -> Result<(), NyxError> {
+        fs::create_dir_all(FILE_SYSTEM.get_rebase_dir_path())?;
+        let head_name = self.head_name.as_deref().unwrap_or(DETACHED);
+        fs::write(self.state_path("head-name"), head_name)?;
+        fs::write(self.state_path("onto"), &self.onto)?;
+        fs::write(self.state_path("orig-head"), &self.orig_head)?;
+        let lines = |items: &Vec<TodoItem>| -> String {
+            items.iter().map(|item| format!("{}\n", item)).collect()
+        };
+        fs::write(self.state_path("todo"), lines(&self.todo))?;
+        fs::write(self.state_path("done"), lines(&self.done))?;
+        Ok(())
+    }
+
+    fn load() -> Result<Self, NyxError> {
+        if !Rebase::in_progress() {
+            return Err(NyxError::Usage(String::from("No rebase in progress?")));
+        }
+        let dir = FILE_SYSTEM.get_rebase_dir_path();
+        let read = |name: &str| -> Result<String, NyxError> {
+            Ok(fs::read_to_string(dir.join(name))?.trim().to_string())
+        };
+        let head_name = read("head-name")?;
+
+        Ok(Self {
+            head_name: if head_name == DETACHED {
+                None
+            } else {
+                Some(head_name)
+            },
+            onto: read("onto")?,
+            orig_head: read("orig-head")?,
+            todo: parse_todo(&read("todo")?)?,
+            done: parse_todo(&read("done")?)?,
+        })
+    }
+
+    fn clear() -> Result<(), NyxError> {
+        fs::remove_dir_all(FILE_SYSTEM.get_rebase_dir_path())?;
+        Ok(())
+    }
+}
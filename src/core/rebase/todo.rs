@@ -0,0 +1,158 @@
+use std::fmt::Display;
+
+use crate::core::{commit::Commit, errors::NyxError, revision::resolve_hash_prefix};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TodoCommand {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+    Exec,
+}
+
+impl TodoCommand {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "pick" | "p" => Some(TodoCommand::Pick),
+            "reword" | "r" => Some(TodoCommand::Reword),
+            "edit" | "e" => Some(TodoCommand::Edit),
+            "squash" | "s" => Some(TodoCommand::Squash),
+            "fixup" | "f" => Some(TodoCommand::Fixup),
+            "drop" | "d" => Some(TodoCommand::Drop),
+            "exec" | "x" => Some(TodoCommand::Exec),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            TodoCommand::Pick => "pick",
+            TodoCommand::Reword => "reword",
+            TodoCommand::Edit => "edit",
+            TodoCommand::Squash => "squash",
+            TodoCommand::Fixup => "fixup",
+            TodoCommand::Drop => "drop",
+            TodoCommand::Exec => "exec",
+        }
+    }
+}
+
+/// A single line of the rebase todo list. For `exec` the argument is the
+/// shell command, for every other command the hash of a commit.
+#[derive(Debug, Clone)]
+pub struct TodoItem {
+    pub command: TodoCommand,
+    pub argument: String,
+    pub summary: String,
+}
+
+impl TodoItem {
+    pub fn pick(commit: &Commit) -> Self {
+        Self {
+            command: TodoCommand::Pick,
+            argument: commit.get_hash().to_string(),
+            summary: commit.summary().to_string(),
+        }
+    }
+
+    fn parse(line: &str) -> Result<Option<Self>, NyxError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let command = TodoCommand::parse(word)
+            .ok_or_else(|| NyxError::Usage(format!("invalid todo command '{}'", word)))?;
+        let rest = rest.trim();
+
+        if command == TodoCommand::Exec {
+            if rest.is_empty() {
+                return Err(NyxError::Usage(String::from("missing command after exec")));
+            }
+            return Ok(Some(Self {
+                command,
+                argument: rest.to_string(),
+                summary: String::new(),
+            }));
+        }
+
+        let (hash, summary) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let hash = resolve_hash_prefix(hash)
+            .ok_or_else(|| NyxError::Usage(format!("invalid commit '{}' in todo list", hash)))?;
+        Ok(Some(Self {
+            command,
+            argument: hash,
+            summary: summary.trim().to_string(),
+        }))
+    }
+
+    /// Line with an abbreviated hash as shown to the user for editing.
+    pub fn to_short_line(&self) -> String {
+        match self.command {
+            TodoCommand::Exec => self.to_string(),
+            _ => format!(
+                "{} {} {}",
+                self.command.name(),
+                &self.argument[..7.min(self.argument.len())],
+                self.summary
+            ),
+        }
+    }
+}
+
+impl Display for TodoItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.summary.is_empty() {
+            write!(f, "{} {}", self.command.name(), self.argument)
+        } else {
+            write!(
+                f,
+                "{} {} {}",
+                self.command.name(),
+                self.argument,
+                self.summary
+            )
+        }
+    }
+}
+
+pub fn parse_todo(content: &str) -> Result<Vec<TodoItem>, NyxError> {
+    let mut items = Vec::new();
+    for line in content.lines() {
+        if let Some(item) = TodoItem::parse(line)? {
+            items.push(item);
+        }
+    }
+    Ok(items)
+}
+
+/// Checks a freshly edited todo list before the rebase starts.
+pub fn validate_todo(items: &[TodoItem]) -> Result<(), NyxError> {
+    if let Some(first) = items.iter().find(|i| i.command != TodoCommand::Exec) {
+        if matches!(first.command, TodoCommand::Squash | TodoCommand::Fixup) {
+            return Err(NyxError::Usage(format!(
+                "cannot '{}' without a previous commit",
+                first.command.name()
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub const TODO_HELP: &str = "
+# Commands:
+# p, pick <commit> = use commit
+# r, reword <commit> = use commit, but edit the commit message
+# e, edit <commit> = use commit, but stop for amending
+# s, squash <commit> = use commit, but meld into previous commit
+# f, fixup <commit> = like \"squash\", but discard this commit's log message
+# x, exec <command> = run command (the rest of the line) using shell
+# d, drop <commit> = remove commit
+#
+# These lines can be re-ordered; they are executed from top to bottom.
+# If you remove a line here THAT COMMIT WILL BE LOST.
+# However, if you remove everything, the rebase will be aborted.
+";
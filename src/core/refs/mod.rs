@@ -0,0 +1,2 @@
+pub mod reflog;
+pub mod refs;
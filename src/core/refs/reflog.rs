@@ -0,0 +1,178 @@
+use format_bytes::format_bytes;
+use std::fs;
+use std::io::Write;
+
+use crate::core::{errors::NyxError, signature::Signature, traits::Byte};
+
+use super::refs::HEAD;
+use crate::FILE_SYSTEM;
+
+#[derive(Debug, Clone)]
+pub struct ReflogEntry {
+    pub old_hash: String,
+    pub new_hash: String,
+    pub committer: Signature,
+    pub message: String,
+}
+
+impl ReflogEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let (head, message) = match line.find('\t') {
+            Some(idx) => (&line[..idx], line[idx + 1..].to_string()),
+            None => (line, String::new()),
+        };
+        let mut parts = head.splitn(3, ' ');
+        let old_hash = parts.next()?.to_string();
+        let new_hash = parts.next()?.to_string();
+        let committer = Signature::parse(parts.next()?)?;
+
+        Some(Self {
+            old_hash,
+            new_hash,
+            committer,
+            message,
+        })
+    }
+}
+
+impl Byte for ReflogEntry {
+    fn as_bytes(&self) -> Vec<u8> {
+        format_bytes!(
+            b"{} {} {}\t{}",
+            self.old_hash.as_bytes(),
+            self.new_hash.as_bytes(),
+            self.committer.to_string().as_bytes(),
+            self.message.as_bytes()
+        )
+    }
+}
+
+/// History of the values of a single ref, stored oldest first in
+/// `.nyx/logs/<ref>` with one line per update.
+pub struct Reflog {
+    name: String,
+    entries: Vec<ReflogEntry>,
+}
+
+impl Reflog {
+    pub fn new(name: &str) -> Self {
+        let content = fs::read_to_string(FILE_SYSTEM.get_reflog_path(name)).unwrap_or_default();
+        let entries = content.lines().filter_map(ReflogEntry::parse).collect();
+
+        Self {
+            name: name.to_string(),
+            entries,
+        }
+    }
+
+    /// Names of all refs that have a reflog, e.g. `HEAD` or `stash`.
+    pub fn names() -> Vec<String> {
+        let mut names = Vec::new();
+        if FILE_SYSTEM.get_reflog_path(HEAD).is_file() {
+            names.push(HEAD.to_string());
+        }
+        let refs_dir = FILE_SYSTEM.get_reflog_path("");
+        let mut dirs = vec![refs_dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(name) = path.strip_prefix(&refs_dir) {
+                    names.push(name.to_string_lossy().to_string());
+                }
+            }
+        }
+        names.sort();
+        names
+    }
+
+    /// Appends a single entry without reading the whole log.
+    pub fn append(
+        name: &str,
+        old_hash: &str,
+        new_hash: &str,
+        message: &str,
+    ) -> Result<(), NyxError> {
+        let path = FILE_SYSTEM.get_reflog_path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let entry = ReflogEntry {
+            old_hash: old_hash.to_string(),
+            new_hash: new_hash.to_string(),
+            committer: Signature::now(),
+            message: message.replace('\n', " "),
+        };
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        file.write_all(&format_bytes!(b"{}\n", entry.as_bytes()))?;
+        Ok(())
+    }
+
+    /// Entries ordered from the newest to the oldest, the order in
+    /// which `<ref>@{n}` addresses them.
+    pub fn newest_first(&self) -> Vec<&ReflogEntry> {
+        self.entries.iter().rev().collect()
+    }
+
+    pub fn entries(&self) -> &Vec<ReflogEntry> {
+        &self.entries
+    }
+
+    pub fn get(&self, n: usize) -> Option<&ReflogEntry> {
+        self.entries.iter().rev().nth(n)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Replaces the values of all entries, e.g. after the objects got new
+    /// hashes. Entries with a value `map` does not know are dropped.
+    pub fn map_hashes(&mut self, map: impl Fn(&str) -> Option<String>) {
+        self.entries = self
+            .entries
+            .drain(..)
+            .filter_map(|entry| {
+                Some(ReflogEntry {
+                    old_hash: map(&entry.old_hash)?,
+                    new_hash: map(&entry.new_hash)?,
+                    ..entry
+                })
+            })
+            .collect();
+    }
+
+    /// Removes the n-th newest entry. The entry that now follows the removed
+    /// one takes over its old value so the chain of values stays intact.
+    pub fn remove(&mut self, n: usize) -> Option<ReflogEntry> {
+        if n >= self.entries.len() {
+            return None;
+        }
+        let idx = self.entries.len() - 1 - n;
+        let removed = self.entries.remove(idx);
+        if let Some(next) = self.entries.get_mut(idx) {
+            next.old_hash = removed.old_hash.clone();
+        }
+        Some(removed)
+    }
+
+    pub fn write(&self) -> Result<(), NyxError> {
+        let path = FILE_SYSTEM.get_reflog_path(&self.name);
+        if self.entries.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        FILE_SYSTEM.write_contents(&self.entries, path.to_str().unwrap());
+        Ok(())
+    }
+}
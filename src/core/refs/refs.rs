@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::Path;
+
+use crate::core::errors::NyxError;
+use crate::core::object_format::object_format;
+use crate::FILE_SYSTEM;
+
+use super::reflog::Reflog;
+
+pub const HEAD: &str = "HEAD";
+pub const DEFAULT_BRANCH: &str = "master";
+
+const SYMBOLIC_PREFIX: &str = "ref: refs/";
+
+/// Namespaces below `refs/` that do not contain branches.
+const NON_BRANCH_NAMESPACES: [&str; 4] = ["tags", "remotes", "bisect", "stash"];
+
+/// The hash recorded for a ref that did not exist, all zeros in the
+/// length of the object format.
+pub fn zero_hash() -> String {
+    object_format().zero_hash()
+}
+
+pub fn is_zero_hash(hash: &str) -> bool {
+    !hash.is_empty() && hash.bytes().all(|b| b == b'0')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Head {
+    /// HEAD points to a branch, e.g. `ref: refs/master`
+    Branch(String),
+    /// HEAD contains a commit hash directly
+    Detached(String),
+}
+
+pub fn read_head() -> Option<Head> {
+    let content = fs::read_to_string(FILE_SYSTEM.get_head_path()).ok()?;
+    parse_head(&content)
+}
+
+/// Returns the name of the checked out branch or `None` if HEAD is detached.
+pub fn current_branch() -> Option<String> {
+    match read_head() {
+        Some(Head::Branch(name)) => Some(name),
+        Some(Head::Detached(_)) => None,
+        None => Some(DEFAULT_BRANCH.to_string()),
+    }
+}
+
+/// Returns the hash of the commit HEAD points to or an empty string
+/// if there is no commit yet.
+pub fn head_hash() -> String {
+    match read_head() {
+        Some(Head::Branch(name)) => read_ref(&name).unwrap_or_default(),
+        Some(Head::Detached(hash)) => hash,
+        None => String::new(),
+    }
+}
+
+pub fn read_ref(name: &str) -> Option<String> {
+    let path = FILE_SYSTEM.get_ref_path(name);
+    if !path.is_file() {
+        return None;
+    }
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    if content.is_empty() {
+        None
+    } else {
+        Some(content.to_string())
+    }
+}
+
+/// Whether `name` stays below the refs directory: not empty, no
+/// leading slash, no `..` and no empty or `.` components.
+pub fn is_valid_ref_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('/')
+        && !name.contains("..")
+        && !name.contains(['\\', '\0'])
+        && name
+            .split('/')
+            .all(|component| !component.is_empty() && component != ".")
+}
+
+/// Fails for names `is_valid_ref_name` rejects, before any file is touched.
+pub fn check_ref_name(name: &str) -> Result<(), NyxError> {
+    match is_valid_ref_name(name) {
+        true => Ok(()),
+        false => Err(NyxError::InvalidRef(name.to_string())),
+    }
+}
+
+pub fn is_branch_name(name: &str) -> bool {
+    let namespace = name.split('/').next().unwrap_or_default();
+    !name.is_empty() && !NON_BRANCH_NAMESPACES.contains(&namespace)
+}
+
+pub fn ref_exists(name: &str) -> bool {
+    FILE_SYSTEM.get_ref_path(name).is_file()
+}
+
+/// Points the ref `name` to `hash` and records the update in its reflog.
+pub fn update_ref(name: &str, hash: &str, message: &str) -> Result<(), NyxError> {
+    check_ref_name(name)?;
+    let path = FILE_SYSTEM.get_ref_path(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let old_hash = read_ref(name).unwrap_or_else(zero_hash);
+    fs::write(path, hash)?;
+    Reflog::append(name, &old_hash, hash, message)
+}
+
+/// Overwrites a ref without recording the change in its reflog.
+pub fn write_ref(name: &str, hash: &str) -> Result<(), NyxError> {
+    check_ref_name(name)?;
+    let path = FILE_SYSTEM.get_ref_path(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, hash)?;
+    Ok(())
+}
+
+pub fn delete_ref(name: &str) -> Result<(), NyxError> {
+    check_ref_name(name)?;
+    let path = FILE_SYSTEM.get_ref_path(name);
+    if path.is_file() {
+        fs::remove_file(path)?;
+    }
+    let log_path = FILE_SYSTEM.get_reflog_path(name);
+    if log_path.is_file() {
+        fs::remove_file(log_path)?;
+    }
+    Ok(())
+}
+
+/// Moves whatever HEAD points to. On a branch the branch is advanced,
+/// in detached state HEAD itself receives the new hash.
+pub fn update_head(hash: &str, message: &str) -> Result<(), NyxError> {
+    let old_hash = head_hash();
+    match read_head() {
+        Some(Head::Branch(name)) => update_ref(&name, hash, message)?,
+        Some(Head::Detached(_)) => fs::write(FILE_SYSTEM.get_head_path(), hash)?,
+        None => {
+            update_ref(DEFAULT_BRANCH, hash, message)?;
+            set_head_to_branch(DEFAULT_BRANCH)?;
+        }
+    }
+    append_head_log(&old_hash, hash, message)
+}
+
+/// Lets HEAD point to the branch `name` without touching the working tree.
+pub fn set_head_to_branch(name: &str) -> Result<(), NyxError> {
+    fs::write(
+        FILE_SYSTEM.get_head_path(),
+        format!("{}{}", SYMBOLIC_PREFIX, name),
+    )?;
+    Ok(())
+}
+
+/// Writes `hash` directly to HEAD without touching the working tree.
+pub fn detach_head(hash: &str, message: &str) -> Result<(), NyxError> {
+    let old_hash = head_hash();
+    fs::write(FILE_SYSTEM.get_head_path(), hash)?;
+    append_head_log(&old_hash, hash, message)
+}
+
+pub fn append_head_log(old_hash: &str, new_hash: &str, message: &str) -> Result<(), NyxError> {
+    let old_hash = match old_hash.is_empty() {
+        true => zero_hash(),
+        false => old_hash.to_string(),
+    };
+    Reflog::append(HEAD, &old_hash, new_hash, message)
+}
+
+/// Lists all refs below `prefix` (e.g. `tags`) as `(name, hash)` pairs
+/// sorted by name. An empty prefix lists every ref.
+pub fn list_refs(prefix: &str) -> Vec<(String, String)> {
+    let mut refs = Vec::new();
+    let refs_dir = FILE_SYSTEM.get_refs_dir_path();
+    let start = if prefix.is_empty() {
+        refs_dir.clone()
+    } else {
+        refs_dir.join(prefix)
+    };
+    collect_refs(&refs_dir, &start, &mut refs);
+    refs.sort();
+    refs
+}
+
+/// Expands the name of a branch or tag to the name of its ref, `HEAD`
+/// standing for the current branch. `None` if there is no such ref.
+pub fn expand_ref_name(name: &str) -> Option<String> {
+    let name = match name {
+        HEAD => current_branch()?,
+        name => name.to_string(),
+    };
+    let tag = format!("tags/{}", name);
+    [name, tag].into_iter().find(|candidate| {
+        (is_branch_name(candidate) || candidate.starts_with("tags/")) && ref_exists(candidate)
+    })
+}
+
+/// Lists all local branches as `(name, hash)` pairs sorted by name.
+pub fn list_branches() -> Vec<(String, String)> {
+    list_refs("")
+        .into_iter()
+        .filter(|(name, _)| is_branch_name(name))
+        .collect()
+}
+
+/// Parses the content of HEAD: `Head::Branch` for `ref: refs/<name>`.
+pub fn parse_head(content: &str) -> Option<Head> {
+    let content = content.trim();
+    if let Some(name) = content.strip_prefix(SYMBOLIC_PREFIX) {
+        Some(Head::Branch(name.to_string()))
+    } else if content.is_empty() {
+        None
+    } else {
+        Some(Head::Detached(content.to_string()))
+    }
+}
+
+fn collect_refs(refs_dir: &Path, dir: &Path, refs: &mut Vec<(String, String)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_refs(refs_dir, &path, refs);
+        } else {
+            let name = path.strip_prefix(refs_dir).unwrap().to_str().unwrap();
+            if let Some(hash) = read_ref(name) {
+                refs.push((name.to_string(), hash));
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::core::errors::NyxError;
+use crate::core::rev_walk::{walk, WalkOrder};
+use crate::core::revision::peel;
+use crate::core::shared::{object_exists, read_raw_object};
+
+use super::http::{encode_advertisement, parse_advertisement};
+use super::pack;
+use super::transfer::collect_objects;
+use super::transport::{RefUpdate, Transport};
+
+const SIGNATURE: &str = "# nyx bundle v1";
+
+/// A file carrying refs together with a pack of the objects they need,
+/// to move history where no transport reaches.
+///
+/// After the signature line follow the prerequisites as `-<hash>`, the
+/// commits a receiver must already have, then the refs in the format of
+/// the HTTP ref advertisement, an empty line and the pack.
+pub struct Bundle {
+    pub prerequisites: Vec<String>,
+    pub refs: Vec<(String, String)>,
+    pub head: Option<String>,
+    pub pack: Vec<u8>,
+}
+
+impl Bundle {
+    /// True if `path` is a file starting with the bundle signature.
+    pub fn is_bundle(path: &Path) -> bool {
+        let mut start = [0; SIGNATURE.len()];
+        match fs::File::open(path) {
+            Ok(mut file) if path.is_file() => {
+                file.read_exact(&mut start).is_ok() && start == SIGNATURE.as_bytes()
+            }
+            _ => false,
+        }
+    }
+
+    /// Bundles the refs with everything reachable from them that is not
+    /// reachable from the `exclude` commits.
+    pub fn create(
+        refs: Vec<(String, String)>,
+        head: Option<String>,
+        exclude: &[String],
+    ) -> Result<Self, NyxError> {
+        let tips: Vec<String> = refs.iter().map(|(_, hash)| hash.clone()).collect();
+        let commit_tips: Vec<String> = tips.iter().map(|hash| peel(hash)).collect();
+        let commits = walk(&commit_tips, exclude, WalkOrder::Date);
+        if commits.is_empty() {
+            return Err(NyxError::Usage(String::from(
+                "Refusing to create empty bundle.",
+            )));
+        }
+
+        // The excluded parents of bundled commits are the boundary the
+        // receiver needs to have
+        let included: HashSet<&str> = commits.iter().map(|commit| commit.get_hash()).collect();
+        let mut prerequisites: Vec<String> = commits
+            .iter()
+            .flat_map(|commit| commit.parent_hashes())
+            .filter(|parent| !included.contains(parent.as_str()))
+            .cloned()
+            .collect();
+        prerequisites.sort();
+        prerequisites.dedup();
+
+        let objects = collect_objects(&tips, &prerequisites, read_raw_object)?;
+        let mut pack = Vec::new();
+        pack::write(&mut pack, &objects, read_raw_object)?;
+        Ok(Self {
+            prerequisites,
+            refs,
+            head,
+            pack,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, NyxError> {
+        let content = fs::read(path)?;
+        let invalid = || NyxError::Usage(format!("'{}' is not a bundle", path.display()));
+        let end = content
+            .windows(2)
+            .position(|window| window == b"\n\n")
+            .ok_or_else(invalid)?;
+        let header = std::str::from_utf8(&content[..end]).map_err(|_| invalid())?;
+        let mut lines = header.lines();
+        if lines.next() != Some(SIGNATURE) {
+            return Err(invalid());
+        }
+
+        let mut prerequisites = Vec::new();
+        let mut advertisement = String::new();
+        for line in lines {
+            match line.strip_prefix('-') {
+                Some(hash) => prerequisites.push(hash.to_string()),
+                None => advertisement.push_str(&format!("{}\n", line)),
+            }
+        }
+        let (refs, head) = parse_advertisement(&advertisement);
+        Ok(Self {
+            prerequisites,
+            refs,
+            head,
+            pack: content[end + 2..].to_vec(),
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), NyxError> {
+        let mut header = format!("{}\n", SIGNATURE);
+        for hash in &self.prerequisites {
+            header.push_str(&format!("-{}\n", hash));
+        }
+        header.push_str(&encode_advertisement(&self.refs, &self.head));
+        header.push('\n');
+
+        let mut content = header.into_bytes();
+        content.extend(&self.pack);
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Prerequisites missing in the current repository.
+    pub fn missing_prerequisites(&self) -> Vec<String> {
+        self.prerequisites
+            .iter()
+            .filter(|hash| !object_exists(hash))
+            .cloned()
+            .collect()
+    }
+
+    /// Fails unless all prerequisites are in the current repository.
+    pub fn check_prerequisites(&self) -> Result<(), NyxError> {
+        let missing = self.missing_prerequisites();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(NyxError::Usage(format!(
+            "Repository lacks these prerequisite commits:\n{}",
+            missing.join("\n")
+        )))
+    }
+}
+
+/// A bundle file used like a read-only remote.
+pub struct BundleTransport {
+    url: String,
+    bundle: Bundle,
+}
+
+impl BundleTransport {
+    pub fn new(url: &str) -> Result<Self, NyxError> {
+        Ok(Self {
+            url: url.to_string(),
+            bundle: Bundle::read(Path::new(url))?,
+        })
+    }
+}
+
+impl Transport for BundleTransport {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn list_refs(&self) -> Result<Vec<(String, String)>, NyxError> {
+        Ok(self.bundle.refs.clone())
+    }
+
+    fn head_branch(&self) -> Result<Option<String>, NyxError> {
+        Ok(self.bundle.head.clone())
+    }
+
+    /// A bundle can only hand out its whole pack.
+    fn fetch_pack(&self, _wants: &[String], _haves: &[String]) -> Result<Vec<u8>, NyxError> {
+        self.bundle.check_prerequisites()?;
+        Ok(self.bundle.pack.clone())
+    }
+
+    fn send_pack(&self, _pack: &[u8], _updates: &[RefUpdate]) -> Result<(), NyxError> {
+        Err(NyxError::Usage(format!(
+            "cannot push to the bundle '{}'",
+            self.url
+        )))
+    }
+}
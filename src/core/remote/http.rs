@@ -0,0 +1,208 @@
+use std::cell::OnceCell;
+use std::io::Read;
+
+use crate::core::errors::NyxError;
+use crate::core::refs::refs::{is_zero_hash, zero_hash};
+
+use super::transport::{RefUpdate, Transport};
+
+/// Endpoint advertising the refs and the branch of HEAD.
+pub const INFO_REFS: &str = "/info/refs";
+/// Endpoint answering wants and haves with a pack.
+pub const UPLOAD_PACK: &str = "/upload-pack";
+/// Endpoint receiving ref updates followed by a pack.
+pub const RECEIVE_PACK: &str = "/receive-pack";
+pub const PACK_CONTENT_TYPE: &str = "application/x-nyx-pack";
+/// Endpoint serving large files as `<LFS_OBJECTS><oid>`.
+pub const LFS_OBJECTS: &str = "/lfs/objects/";
+
+type Advertisement = (Vec<(String, String)>, Option<String>);
+
+pub fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// One `<hash> <name>` line per ref, the branch of HEAD as `HEAD <branch>`.
+pub fn encode_advertisement(refs: &[(String, String)], head: &Option<String>) -> String {
+    let mut content = String::new();
+    if let Some(branch) = head {
+        content.push_str(&format!("HEAD {}\n", branch));
+    }
+    for (name, hash) in refs {
+        content.push_str(&format!("{} {}\n", hash, name));
+    }
+    content
+}
+
+pub fn parse_advertisement(content: &str) -> Advertisement {
+    let mut refs = Vec::new();
+    let mut head = None;
+    for (key, value) in content.lines().filter_map(|line| line.split_once(' ')) {
+        match key {
+            "HEAD" => head = Some(value.to_string()),
+            hash => refs.push((value.to_string(), hash.to_string())),
+        }
+    }
+    (refs, head)
+}
+
+/// One `want <hash>` or `have <hash>` line per object.
+pub fn encode_negotiation(wants: &[String], haves: &[String]) -> String {
+    let wants = wants.iter().map(|hash| format!("want {}\n", hash));
+    let haves = haves.iter().map(|hash| format!("have {}\n", hash));
+    wants.chain(haves).collect()
+}
+
+pub fn parse_negotiation(content: &str) -> (Vec<String>, Vec<String>) {
+    let mut wants = Vec::new();
+    let mut haves = Vec::new();
+    for (key, hash) in content.lines().filter_map(|line| line.split_once(' ')) {
+        match key {
+            "want" => wants.push(hash.to_string()),
+            "have" => haves.push(hash.to_string()),
+            _ => (),
+        }
+    }
+    (wants, haves)
+}
+
+/// One `<old> <new> <name>` line per update, an empty line and the pack.
+/// A ref that must not exist yet has the zero hash as old value.
+pub fn encode_push(updates: &[RefUpdate], pack: &[u8]) -> Vec<u8> {
+    let mut content = String::new();
+    for update in updates {
+        let old = update.old.clone().unwrap_or_else(zero_hash);
+        content.push_str(&format!("{} {} {}\n", old, update.new, update.name));
+    }
+    content.push('\n');
+    let mut body = content.into_bytes();
+    body.extend(pack);
+    body
+}
+
+pub fn parse_push(body: &[u8]) -> Result<(Vec<RefUpdate>, &[u8]), NyxError> {
+    let invalid = || NyxError::Usage(String::from("malformed push request"));
+    let end = match body.starts_with(b"\n") {
+        true => 0,
+        false => body
+            .windows(2)
+            .position(|window| window == b"\n\n")
+            .map(|idx| idx + 1)
+            .ok_or_else(invalid)?,
+    };
+    let header = std::str::from_utf8(&body[..end]).map_err(|_| invalid())?;
+    let mut updates = Vec::new();
+    for line in header.lines() {
+        let mut parts = line.splitn(3, ' ');
+        let (old, new, name) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(old), Some(new), Some(name)) => (old, new, name),
+            _ => return Err(invalid()),
+        };
+        updates.push(RefUpdate {
+            name: name.to_string(),
+            old: (!is_zero_hash(old)).then(|| old.to_string()),
+            new: new.to_string(),
+        });
+    }
+    Ok((updates, &body[end + 1..]))
+}
+
+/// A repository served by `nyx serve`.
+pub struct HttpTransport {
+    url: String,
+    agent: ureq::Agent,
+    advertisement: OnceCell<Advertisement>,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            agent: ureq::Agent::new(),
+            advertisement: OnceCell::new(),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.url.trim_end_matches('/'), path)
+    }
+
+    /// Turns a failed request into an error, using the body of an error
+    /// response as message.
+    fn error(&self, err: ureq::Error) -> NyxError {
+        match err {
+            ureq::Error::Status(_, response) => NyxError::Usage(
+                response
+                    .into_string()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            ),
+            ureq::Error::Transport(err) => {
+                NyxError::Usage(format!("unable to access '{}': {}", self.url, err))
+            }
+        }
+    }
+
+    fn read_body(response: ureq::Response) -> Result<Vec<u8>, NyxError> {
+        let mut body = Vec::new();
+        response.into_reader().read_to_end(&mut body)?;
+        Ok(body)
+    }
+
+    fn advertisement(&self) -> Result<&Advertisement, NyxError> {
+        if let Some(advertisement) = self.advertisement.get() {
+            return Ok(advertisement);
+        }
+        let response = self
+            .agent
+            .get(&self.endpoint(INFO_REFS))
+            .call()
+            .map_err(|err| self.error(err))?;
+        let body = String::from_utf8(HttpTransport::read_body(response)?)?;
+        Ok(self
+            .advertisement
+            .get_or_init(|| parse_advertisement(&body)))
+    }
+}
+
+impl Transport for HttpTransport {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn list_refs(&self) -> Result<Vec<(String, String)>, NyxError> {
+        Ok(self.advertisement()?.0.clone())
+    }
+
+    fn head_branch(&self) -> Result<Option<String>, NyxError> {
+        Ok(self.advertisement()?.1.clone())
+    }
+
+    fn fetch_pack(&self, wants: &[String], haves: &[String]) -> Result<Vec<u8>, NyxError> {
+        let response = self
+            .agent
+            .post(&self.endpoint(UPLOAD_PACK))
+            .send_string(&encode_negotiation(wants, haves))
+            .map_err(|err| self.error(err))?;
+        HttpTransport::read_body(response)
+    }
+
+    fn send_pack(&self, pack: &[u8], updates: &[RefUpdate]) -> Result<(), NyxError> {
+        self.agent
+            .post(&self.endpoint(RECEIVE_PACK))
+            .set("Content-Type", PACK_CONTENT_TYPE)
+            .send_bytes(&encode_push(updates, pack))
+            .map_err(|err| self.error(err))?;
+        Ok(())
+    }
+
+    fn lfs_object(&self, oid: &str) -> Result<Box<dyn Read + Send>, NyxError> {
+        let response = self
+            .agent
+            .get(&self.endpoint(&format!("{}{}", LFS_OBJECTS, oid)))
+            .call()
+            .map_err(|err| self.error(err))?;
+        Ok(Box::new(response.into_reader()))
+    }
+}
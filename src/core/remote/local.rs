@@ -0,0 +1,195 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::core::config::Config;
+use crate::core::errors::NyxError;
+use crate::core::lfs::store;
+use crate::core::object_format::{ObjectFormat, OBJECT_FORMAT_KEY};
+use crate::core::odb::{database::ObjectDatabase, loose::LooseObjectDatabase};
+use crate::core::refs::refs::{self, parse_head, Head};
+
+use super::pack;
+use super::transfer::collect_objects;
+use super::transport::{RefUpdate, Transport};
+
+/// A repository on the local file system, addressed by its working
+/// tree or by its `.nyx` directory.
+pub struct LocalTransport {
+    url: String,
+    repo_dir: PathBuf,
+    format: ObjectFormat,
+    objects: LooseObjectDatabase,
+}
+
+impl LocalTransport {
+    pub fn new(url: &str) -> Result<Self, NyxError> {
+        let path = Path::new(url);
+        let repo_dir = if path.join(".nyx").is_dir() {
+            path.join(".nyx")
+        } else if path.join("objects").is_dir() && path.join("refs").is_dir() {
+            path.to_path_buf()
+        } else {
+            return Err(NyxError::Usage(format!(
+                "'{}' does not appear to be a nyx repository",
+                url
+            )));
+        };
+        let config = fs::read_to_string(repo_dir.join("config")).unwrap_or_default();
+        let format = Config::parse(&config)
+            .get(OBJECT_FORMAT_KEY)
+            .and_then(|name| ObjectFormat::parse(&name))
+            .unwrap_or(ObjectFormat::Sha1);
+        Ok(Self {
+            url: url.to_string(),
+            objects: LooseObjectDatabase::with_format(repo_dir.join("objects"), format, None),
+            format,
+            repo_dir,
+        })
+    }
+
+    fn ref_path(&self, name: &str) -> PathBuf {
+        self.repo_dir.join("refs").join(name)
+    }
+
+    fn read_ref(&self, name: &str) -> Option<String> {
+        let content = fs::read_to_string(self.ref_path(name)).ok()?;
+        let content = content.trim();
+        (!content.is_empty()).then(|| content.to_string())
+    }
+
+    fn read_object(&self, hash: &str) -> Result<Vec<u8>, NyxError> {
+        if !self.format.is_hash(hash) {
+            return Err(NyxError::InvalidObject(hash.to_string()));
+        }
+        self.objects
+            .read(hash)
+            .map_err(|_| NyxError::InvalidObject(format!("{} missing in '{}'", hash, self.url)))
+    }
+
+    fn write_object(&self, hash: &str, raw: &[u8]) -> Result<(), NyxError> {
+        self.objects.write(hash, raw)
+    }
+
+    /// Fails unless the ref may be moved and still points to `old`.
+    fn check_update(&self, name: &str, old: Option<&str>) -> Result<(), NyxError> {
+        refs::check_ref_name(name)?;
+        self.check_current_branch(name)?;
+        if self.read_ref(name).as_deref() != old {
+            return Err(NyxError::Usage(format!(
+                "ref '{}' of '{}' changed in the meantime",
+                name, self.url
+            )));
+        }
+        Ok(())
+    }
+
+    fn write_ref(&self, name: &str, new: &str) -> Result<(), NyxError> {
+        let path = self.ref_path(name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, new)?;
+        Ok(())
+    }
+
+    /// A branch checked out in the other working tree must not be moved
+    /// behind its back unless `receive.denyCurrentBranch` allows it.
+    fn check_current_branch(&self, name: &str) -> Result<(), NyxError> {
+        if self.head_branch()?.as_deref() != Some(name) {
+            return Ok(());
+        }
+        let config = fs::read_to_string(self.repo_dir.join("config")).unwrap_or_default();
+        let deny = Config::parse(&config).get("receive.denyCurrentBranch");
+        match deny.as_deref() {
+            Some("ignore") | Some("warn") | Some("false") => Ok(()),
+            _ => Err(NyxError::Usage(format!(
+                "refusing to update checked out branch '{}' of '{}'",
+                name, self.url
+            ))),
+        }
+    }
+}
+
+fn collect_refs(refs_dir: &Path, dir: &Path, refs: &mut Vec<(String, String)>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_refs(refs_dir, &path, refs);
+        } else if let (Ok(name), Ok(hash)) =
+            (path.strip_prefix(refs_dir), fs::read_to_string(&path))
+        {
+            refs.push((name.to_string_lossy().to_string(), hash.trim().to_string()));
+        }
+    }
+}
+
+impl Transport for LocalTransport {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn list_refs(&self) -> Result<Vec<(String, String)>, NyxError> {
+        let refs_dir = self.repo_dir.join("refs");
+        let mut refs = Vec::new();
+        collect_refs(&refs_dir, &refs_dir, &mut refs);
+        refs.retain(|(_, hash)| !hash.is_empty());
+        refs.sort();
+        Ok(refs)
+    }
+
+    fn head_branch(&self) -> Result<Option<String>, NyxError> {
+        let content = fs::read_to_string(self.repo_dir.join("HEAD")).unwrap_or_default();
+        Ok(match parse_head(&content) {
+            Some(Head::Branch(name)) => Some(name),
+            _ => None,
+        })
+    }
+
+    /// Read from the config, so empty repositories have a format too.
+    fn object_format(&self) -> Result<Option<ObjectFormat>, NyxError> {
+        Ok(Some(self.format))
+    }
+
+    fn fetch_pack(&self, wants: &[String], haves: &[String]) -> Result<Vec<u8>, NyxError> {
+        let read = |hash: &str| self.read_object(hash);
+        let objects = collect_objects(wants, haves, read)?;
+        let mut pack = Vec::new();
+        pack::write(&mut pack, &objects, read)?;
+        Ok(pack)
+    }
+
+    fn send_pack(&self, pack: &[u8], updates: &[RefUpdate]) -> Result<(), NyxError> {
+        for (hash, raw) in pack::decode(pack)? {
+            self.write_object(&hash, &raw)?;
+        }
+        for update in updates {
+            if !self.format.is_hash(&update.new) || !self.objects.exists(&update.new) {
+                return Err(NyxError::InvalidObject(format!(
+                    "{} missing in '{}'",
+                    update.new, self.url
+                )));
+            }
+        }
+        // No ref is moved unless all of them can be
+        for update in updates {
+            self.check_update(&update.name, update.old.as_deref())?;
+        }
+        for update in updates {
+            self.write_ref(&update.name, &update.new)?;
+        }
+        Ok(())
+    }
+
+    fn lfs_object(&self, oid: &str) -> Result<Box<dyn Read + Send>, NyxError> {
+        let objects_dir = store::objects_dir(&self.repo_dir);
+        let valid = oid.len() == 64 && oid.chars().all(|c| c.is_ascii_hexdigit());
+        match valid.then(|| File::open(store::object_path_in(&objects_dir, oid))) {
+            Some(Ok(file)) => Ok(Box::new(file)),
+            _ => Err(NyxError::Usage(format!(
+                "'{}' has no large file {}",
+                self.url, oid
+            ))),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod bundle;
+pub mod http;
+pub mod local;
+pub mod pack;
+pub mod remote;
+pub mod transfer;
+pub mod transport;
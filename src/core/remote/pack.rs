@@ -0,0 +1,58 @@
+use std::io::Write;
+
+use crate::core::errors::NyxError;
+use crate::core::shared::calculate_hash;
+
+const SIGNATURE: &str = "NYXPACK";
+
+/// Serializes objects including their headers. A pack starts with a
+/// `NYXPACK <count>` line, each object follows as `<hash> <length>` line
+/// and the raw object. Objects are read one at a time as they are written.
+pub fn write(
+    out: &mut impl Write,
+    hashes: &[String],
+    read: impl Fn(&str) -> Result<Vec<u8>, NyxError>,
+) -> Result<(), NyxError> {
+    writeln!(out, "{} {}", SIGNATURE, hashes.len())?;
+    for hash in hashes {
+        let raw = read(hash)?;
+        writeln!(out, "{} {}", hash, raw.len())?;
+        out.write_all(&raw)?;
+    }
+    Ok(())
+}
+
+/// Reads the objects of a pack and verifies their hashes.
+pub fn decode(pack: &[u8]) -> Result<Vec<(String, Vec<u8>)>, NyxError> {
+    let invalid = || NyxError::InvalidObject(String::from("malformed pack"));
+    let mut rest = pack;
+    let next_line = |rest: &mut &[u8]| -> Result<String, NyxError> {
+        let end = rest.iter().position(|b| *b == b'\n').ok_or_else(invalid)?;
+        let line = String::from_utf8(rest[..end].to_vec()).map_err(|_| invalid())?;
+        *rest = &rest[end + 1..];
+        Ok(line)
+    };
+
+    let header = next_line(&mut rest)?;
+    let count: usize = match header.split_once(' ') {
+        Some((SIGNATURE, count)) => count.parse().map_err(|_| invalid())?,
+        _ => return Err(invalid()),
+    };
+    // Every object takes at least a byte, the count is not to be trusted
+    let mut objects = Vec::with_capacity(count.min(rest.len()));
+    for _ in 0..count {
+        let line = next_line(&mut rest)?;
+        let (hash, length) = line.split_once(' ').ok_or_else(invalid)?;
+        let length: usize = length.parse().map_err(|_| invalid())?;
+        if rest.len() < length {
+            return Err(invalid());
+        }
+        let (raw, tail) = rest.split_at(length);
+        if calculate_hash(raw) != hash {
+            return Err(NyxError::InvalidObject(format!("{} is corrupt", hash)));
+        }
+        objects.push((hash.to_string(), raw.to_vec()));
+        rest = tail;
+    }
+    Ok(objects)
+}
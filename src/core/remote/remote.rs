@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use crate::core::config::Config;
+use crate::core::errors::NyxError;
+use crate::core::refs::refs;
+
+use super::bundle::{Bundle, BundleTransport};
+use super::http::{is_http_url, HttpTransport};
+use super::local::LocalTransport;
+use super::transport::Transport;
+
+pub const DEFAULT_REMOTE: &str = "origin";
+/// State file listing the branches of the last fetch, the first one
+/// can be used as revision `FETCH_HEAD`.
+pub const FETCH_HEAD: &str = "FETCH_HEAD";
+const REMOTES_PREFIX: &str = "remotes";
+
+/// A repository configured in `[remote "<name>"]` of `.nyx/config`.
+#[derive(Debug, Clone)]
+pub struct Remote {
+    pub name: String,
+    pub url: String,
+}
+
+impl Remote {
+    /// Looks up a configured remote. A name that is no remote but the
+    /// path or URL of a repository is used as an unnamed remote.
+    pub fn load(name: &str) -> Result<Self, NyxError> {
+        if let Some(url) = Config::new().get(&format!("remote.{}.url", name)) {
+            return Ok(Self {
+                name: name.to_string(),
+                url,
+            });
+        }
+        if Path::new(name).exists() || is_http_url(name) {
+            return Ok(Self {
+                name: String::new(),
+                url: name.to_string(),
+            });
+        }
+        Err(NyxError::Usage(format!(
+            "'{}' does not appear to be a nyx repository",
+            name
+        )))
+    }
+
+    /// The remote of the current branch, `origin` otherwise.
+    pub fn default_name() -> String {
+        refs::current_branch()
+            .and_then(|branch| Config::new().get(&format!("branch.{}.remote", branch)))
+            .unwrap_or_else(|| DEFAULT_REMOTE.to_string())
+    }
+
+    pub fn list() -> Vec<Self> {
+        let config = Config::new();
+        config
+            .subsections("remote")
+            .into_iter()
+            .filter_map(|name| {
+                let url = config.get(&format!("remote.{}.url", name))?;
+                Some(Self { name, url })
+            })
+            .collect()
+    }
+
+    pub fn add(name: &str, url: &str) -> Result<Self, NyxError> {
+        let mut config = Config::new();
+        if config.get(&format!("remote.{}.url", name)).is_some() {
+            return Err(NyxError::Usage(format!("remote {} already exists.", name)));
+        }
+        config.set(&format!("remote.{}.url", name), url)?;
+        config.write()?;
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+        })
+    }
+
+    /// Removes the remote, its remote-tracking refs and the upstream
+    /// configuration of branches following it.
+    pub fn remove(name: &str) -> Result<(), NyxError> {
+        let remote = Remote::load(name)?;
+        let mut config = Config::new();
+        config.remove_section("remote", Some(name));
+        for branch in config.subsections("branch") {
+            if config.get(&format!("branch.{}.remote", branch)).as_deref() == Some(name) {
+                config.remove_section("branch", Some(&branch));
+            }
+        }
+        config.write()?;
+        for (tracking, _) in refs::list_refs(&format!("{}/{}", REMOTES_PREFIX, remote.name)) {
+            refs::delete_ref(&tracking)?;
+        }
+        Ok(())
+    }
+
+    pub fn transport(&self) -> Result<Box<dyn Transport>, NyxError> {
+        if is_http_url(&self.url) {
+            return Ok(Box::new(HttpTransport::new(&self.url)));
+        }
+        if Bundle::is_bundle(Path::new(&self.url)) {
+            return Ok(Box::new(BundleTransport::new(&self.url)?));
+        }
+        Ok(Box::new(LocalTransport::new(&self.url)?))
+    }
+
+    /// Name of the ref tracking `branch` of this remote, e.g.
+    /// `remotes/origin/master`. Unnamed remotes are not tracked.
+    pub fn tracking_ref(&self, branch: &str) -> Option<String> {
+        if self.name.is_empty() {
+            return None;
+        }
+        Some(format!("{}/{}/{}", REMOTES_PREFIX, self.name, branch))
+    }
+
+    /// Short name shown to the user, e.g. `origin/master`.
+    pub fn tracking_name(&self, branch: &str) -> String {
+        match self.name.is_empty() {
+            true => branch.to_string(),
+            false => format!("{}/{}", self.name, branch),
+        }
+    }
+
+    /// The remote and its branch that `branch` follows, if configured.
+    pub fn upstream(branch: &str) -> Option<(Self, String)> {
+        let config = Config::new();
+        let name = config.get(&format!("branch.{}.remote", branch))?;
+        let remote_branch = config.get(&format!("branch.{}.merge", branch))?;
+        Some((Remote::load(&name).ok()?, remote_branch))
+    }
+
+    /// Records that `branch` follows `remote_branch` of this remote.
+    pub fn set_upstream(&self, branch: &str, remote_branch: &str) -> Result<(), NyxError> {
+        let mut config = Config::new();
+        config.set(&format!("branch.{}.remote", branch), &self.name)?;
+        config.set(&format!("branch.{}.merge", branch), remote_branch)?;
+        config.write()
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::core::errors::NyxError;
+use crate::core::object_format::object_format;
+use crate::core::object_type::NyxObjectType;
+use crate::core::reachability::content_references;
+use crate::core::refs::refs;
+use crate::core::shared::{object_exists, read_raw_object, split_raw_object, write_raw_object};
+use crate::core::signature::Signature;
+
+use super::pack;
+use super::transport::{RefUpdate, Transport};
+
+/// Objects can only be exchanged between repositories hashing them alike.
+pub fn check_object_format(transport: &dyn Transport) -> Result<(), NyxError> {
+    match transport.object_format()? {
+        Some(format) if format != object_format() => Err(NyxError::Usage(format!(
+            "'{}' uses the {} object format, this repository {}",
+            transport.url(),
+            format,
+            object_format()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Side a commit is reachable from while `collect_objects` walks them.
+const WANTED: u8 = 1;
+const COMMON: u8 = 2;
+
+/// What `collect_objects` needs to know about an object: its type, the
+/// objects it references (tree and parents for commits) and the commit time.
+struct ObjectInfo {
+    object_type: NyxObjectType,
+    references: Vec<String>,
+    time: i64,
+}
+
+fn object_info(hash: &str, raw: &[u8]) -> Result<ObjectInfo, NyxError> {
+    let (object_type, content) =
+        split_raw_object(raw).ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+    let content = String::from_utf8_lossy(content);
+    let time = content
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| line.strip_prefix("committer "))
+        .and_then(Signature::parse)
+        .map_or(0, |committer| committer.time);
+    Ok(ObjectInfo {
+        object_type,
+        references: content_references(object_type, &content),
+        time,
+    })
+}
+
+/// Adds the objects of a tree that are not `seen` yet to `objects`,
+/// subtrees and blobs before the tree referencing them. Blobs are not read.
+fn collect_tree(
+    hash: &str,
+    read: &impl Fn(&str) -> Result<Vec<u8>, NyxError>,
+    seen: &mut HashSet<String>,
+    objects: &mut Vec<String>,
+) -> Result<(), NyxError> {
+    if !seen.insert(hash.to_string()) {
+        return Ok(());
+    }
+    let raw = read(hash)?;
+    let (_, content) =
+        split_raw_object(&raw).ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+    for line in String::from_utf8_lossy(content).lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(kind), Some(child)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if kind == "tree" {
+            collect_tree(child, read, seen, objects)?;
+        } else if seen.insert(child.to_string()) {
+            objects.push(child.to_string());
+        }
+    }
+    objects.push(hash.to_string());
+    Ok(())
+}
+
+/// Collects the hashes of the objects reachable from `wants` but not from
+/// `haves`. Haves the sender does not know are ignored. Commits are
+/// walked from both sides newest first until only common ones are left,
+/// so the history behind the commits being sent is not read. Objects are
+/// ordered so that referenced objects mostly come first, which keeps the
+/// holes of an interrupted transfer small.
+pub fn collect_objects(
+    wants: &[String],
+    haves: &[String],
+    read: impl Fn(&str) -> Result<Vec<u8>, NyxError>,
+) -> Result<Vec<String>, NyxError> {
+    let mut commits: HashMap<String, ObjectInfo> = HashMap::new();
+    let mut flags: HashMap<String, u8> = HashMap::new();
+    let mut queue: BinaryHeap<(i64, String, u8)> = BinaryHeap::new();
+    // Objects seen from the common side, never sent
+    let mut seen: HashSet<String> = HashSet::new();
+    // Wanted tags, trees and blobs with their types
+    let mut roots: Vec<(String, NyxObjectType)> = Vec::new();
+
+    for (tips, flag) in [(wants, WANTED), (haves, COMMON)] {
+        for tip in tips {
+            let mut hash = tip.clone();
+            // Tags are followed to the object they point to
+            loop {
+                let object = match read(&hash).and_then(|raw| object_info(&hash, &raw)) {
+                    Ok(object) => object,
+                    Err(_) if flag == COMMON => break,
+                    Err(err) => return Err(err),
+                };
+                if object.object_type == NyxObjectType::Commit {
+                    if flags.get(&hash).is_none_or(|old| old & flag == 0) {
+                        *flags.entry(hash.clone()).or_default() |= flag;
+                        queue.push((object.time, hash.clone(), flag));
+                    }
+                    commits.insert(hash, object);
+                    break;
+                }
+                if flag == COMMON {
+                    seen.insert(hash.clone());
+                } else {
+                    roots.push((hash.clone(), object.object_type));
+                }
+                match (object.object_type, object.references.first()) {
+                    (NyxObjectType::Tag, Some(target)) => hash = target.clone(),
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    // Common flags spread to the parents of common commits, the walk
+    // stops once no commit that is only wanted is left in the queue
+    let mut wanted_queued = queue.iter().filter(|(_, _, flag)| *flag == WANTED).count();
+    while wanted_queued > 0 {
+        let Some((_, hash, queued_flag)) = queue.pop() else {
+            break;
+        };
+        if queued_flag == WANTED {
+            wanted_queued -= 1;
+        }
+        let flag = flags[&hash];
+        let parents: Vec<String> = commits[&hash].references.iter().skip(1).cloned().collect();
+        for parent in parents {
+            if !commits.contains_key(&parent) {
+                match read(&parent).and_then(|raw| object_info(&parent, &raw)) {
+                    Ok(object) => commits.insert(parent.clone(), object),
+                    // The history below common commits may be incomplete
+                    Err(_) if flag & COMMON != 0 => continue,
+                    Err(err) => return Err(err),
+                };
+            }
+            let old = flags.get(&parent).copied().unwrap_or_default();
+            if old | flag != old {
+                flags.insert(parent.clone(), old | flag);
+                if old | flag == WANTED {
+                    wanted_queued += 1;
+                }
+                queue.push((commits[&parent].time, parent, old | flag));
+            }
+        }
+    }
+
+    let mut sent: Vec<&String> = flags
+        .iter()
+        .filter(|(_, flag)| **flag == WANTED)
+        .map(|(hash, _)| hash)
+        .collect();
+    sent.sort_by_key(|hash| (commits[*hash].time, *hash));
+
+    // The trees of the common parents of sent commits hold what the
+    // receiver already has
+    let mut unused = Vec::new();
+    for hash in &sent {
+        for parent in commits[*hash].references.iter().skip(1) {
+            if flags.get(parent).is_some_and(|flag| flag & COMMON != 0) {
+                if let Some(tree) = commits[parent].references.first() {
+                    collect_tree(tree, &read, &mut seen, &mut unused)?;
+                }
+            }
+        }
+    }
+
+    let mut objects: Vec<String> = Vec::new();
+    for hash in sent {
+        if let Some(tree) = commits[hash].references.first() {
+            collect_tree(tree, &read, &mut seen, &mut objects)?;
+        }
+        objects.push(hash.clone());
+    }
+    for (hash, object_type) in roots.into_iter().rev() {
+        match object_type {
+            NyxObjectType::Tree => collect_tree(&hash, &read, &mut seen, &mut objects)?,
+            _ => {
+                if seen.insert(hash.clone()) {
+                    objects.push(hash);
+                }
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Downloads the objects reachable from the remote `tips`, announcing
+/// the local refs as already present. Returns the number of new objects.
+pub fn fetch_objects(transport: &dyn Transport, tips: &[String]) -> Result<usize, NyxError> {
+    let mut wants: Vec<String> = tips
+        .iter()
+        .filter(|hash| !object_exists(hash))
+        .cloned()
+        .collect();
+    wants.sort();
+    wants.dedup();
+    if wants.is_empty() {
+        return Ok(0);
+    }
+    check_object_format(transport)?;
+    let mut haves: Vec<String> = refs::list_refs("")
+        .into_iter()
+        .map(|(_, hash)| hash)
+        .chain(Some(refs::head_hash()))
+        .filter(|hash| !hash.is_empty() && object_exists(hash))
+        .collect();
+    haves.sort();
+    haves.dedup();
+
+    let objects = pack::decode(&transport.fetch_pack(&wants, &haves)?)?;
+    let mut count = 0;
+    for (hash, raw) in &objects {
+        if !object_exists(hash) {
+            write_raw_object(hash, raw)?;
+            count += 1;
+        }
+    }
+    for hash in &wants {
+        if !object_exists(hash) {
+            return Err(NyxError::InvalidObject(format!(
+                "{} missing in '{}'",
+                hash,
+                transport.url()
+            )));
+        }
+    }
+    Ok(count)
+}
+
+/// Uploads the local objects needed for the ref updates and applies them.
+/// `remote_tips` are the current values of the remote refs, those known
+/// here spare sending their history. Returns the number of sent objects.
+pub fn push_objects(
+    transport: &dyn Transport,
+    remote_tips: &[String],
+    updates: &[RefUpdate],
+) -> Result<usize, NyxError> {
+    check_object_format(transport)?;
+    let wants: Vec<String> = updates.iter().map(|update| update.new.clone()).collect();
+    let haves: Vec<String> = remote_tips
+        .iter()
+        .filter(|hash| object_exists(hash))
+        .cloned()
+        .collect();
+    let objects = collect_objects(&wants, &haves, read_raw_object)?;
+    let mut pack = Vec::new();
+    pack::write(&mut pack, &objects, read_raw_object)?;
+    transport.send_pack(&pack, updates)?;
+    Ok(objects.len())
+}
@@ -0,0 +1,51 @@
+use std::io::Read;
+
+use crate::core::errors::NyxError;
+use crate::core::object_format::ObjectFormat;
+
+/// Moves the ref `name` of a remote to `new` if it still has the value
+/// `old`, `None` meaning it must not exist yet.
+#[derive(Debug, Clone)]
+pub struct RefUpdate {
+    pub name: String,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+/// Access to the refs and objects of another repository. Objects are
+/// exchanged as packs: the side that receives them names what it wants
+/// and what it already has, the other side sends only the difference.
+pub trait Transport {
+    /// Location of the repository as shown to the user.
+    fn url(&self) -> &str;
+
+    /// All refs of the repository as `(name, hash)` pairs, without HEAD.
+    fn list_refs(&self) -> Result<Vec<(String, String)>, NyxError>;
+
+    /// The branch HEAD points to, `None` if it is detached or unborn.
+    fn head_branch(&self) -> Result<Option<String>, NyxError>;
+
+    /// The object format of the repository, judged by the hashes of its
+    /// refs. `None` if it has no refs.
+    fn object_format(&self) -> Result<Option<ObjectFormat>, NyxError> {
+        Ok(self
+            .list_refs()?
+            .iter()
+            .find_map(|(_, hash)| ObjectFormat::of_hash(hash)))
+    }
+
+    /// A pack of the objects reachable from `wants` but not from `haves`.
+    fn fetch_pack(&self, wants: &[String], haves: &[String]) -> Result<Vec<u8>, NyxError>;
+
+    /// Stores the objects of `pack` and then applies the ref updates.
+    fn send_pack(&self, pack: &[u8], updates: &[RefUpdate]) -> Result<(), NyxError>;
+
+    /// Content of a large file from the large file store.
+    fn lfs_object(&self, oid: &str) -> Result<Box<dyn Read + Send>, NyxError> {
+        Err(NyxError::Usage(format!(
+            "'{}' has no large file {}",
+            self.url(),
+            oid
+        )))
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use super::commit::Commit;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WalkOrder {
+    /// Newest commit date first, parents may show up before children
+    Date,
+    /// Newest commit date first, but never a parent before its children
+    DateTopo,
+    /// Children before parents, lines of history are not interleaved
+    Topo,
+}
+
+fn commit_time(commit: &Commit) -> i64 {
+    commit
+        .committer()
+        .or_else(|| commit.author())
+        .map(|s| s.time)
+        .unwrap_or(0)
+}
+
+/// Walks the commits reachable from `include` that are not reachable
+/// from `exclude` in the given order.
+pub fn walk(include: &[String], exclude: &[String], order: WalkOrder) -> Vec<Commit> {
+    let excluded = ancestors(exclude);
+    let mut commits: HashMap<String, Commit> = HashMap::new();
+    let mut queue: VecDeque<String> = include.iter().cloned().collect();
+    while let Some(hash) = queue.pop_front() {
+        if hash.is_empty() || excluded.contains(&hash) || commits.contains_key(&hash) {
+            continue;
+        }
+        if let Some(commit) = Commit::from_hash(&hash) {
+            queue.extend(commit.parent_hashes().iter().cloned());
+            commits.insert(hash, commit);
+        }
+    }
+
+    let newest_first = |a: &String, b: &String| {
+        let time = |h: &String| commits.get(h).map(commit_time).unwrap_or(0);
+        time(b).cmp(&time(a)).then_with(|| a.cmp(b))
+    };
+
+    let mut ordered: Vec<String> = Vec::with_capacity(commits.len());
+    if order == WalkOrder::Date {
+        // Newest first from the tips on, commits with the same date in
+        // the order they were reached
+        let mut queue: BinaryHeap<(i64, Reverse<usize>, String)> = BinaryHeap::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for hash in include {
+            if let Some(commit) = commits.get(hash) {
+                if seen.insert(hash.clone()) {
+                    queue.push((commit_time(commit), Reverse(seen.len()), hash.clone()));
+                }
+            }
+        }
+        while let Some((_, _, hash)) = queue.pop() {
+            for parent in commits[&hash].parent_hashes() {
+                if let Some(commit) = commits.get(parent) {
+                    if seen.insert(parent.clone()) {
+                        queue.push((commit_time(commit), Reverse(seen.len()), parent.clone()));
+                    }
+                }
+            }
+            ordered.push(hash);
+        }
+    } else {
+        // Kahn's algorithm over the child -> parent edges
+        let mut children_left: HashMap<&String, usize> = commits.keys().map(|h| (h, 0)).collect();
+        for commit in commits.values() {
+            for parent in commit.parent_hashes() {
+                if let Some(count) = children_left.get_mut(parent) {
+                    *count += 1;
+                }
+            }
+        }
+        let mut ready: Vec<String> = children_left
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(hash, _)| (*hash).clone())
+            .collect();
+        ready.sort_by(|a, b| newest_first(b, a));
+
+        while let Some(hash) = ready.pop() {
+            let commit = &commits[&hash];
+            for parent in commit.parent_hashes().iter().rev() {
+                if let Some(count) = children_left.get_mut(parent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(parent.clone());
+                    }
+                }
+            }
+            if order == WalkOrder::DateTopo {
+                // Keep the oldest commits at the front, the next one is popped from the back
+                ready.sort_by(|a, b| newest_first(b, a));
+            }
+            ordered.push(hash);
+        }
+    }
+
+    ordered
+        .into_iter()
+        .filter_map(|hash| commits.remove(&hash))
+        .collect()
+}
+
+/// All commits reachable from the given commits, including themselves.
+pub fn ancestors(hashes: &[String]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<String> = hashes.iter().filter(|h| !h.is_empty()).cloned().collect();
+
+    while let Some(hash) = queue.pop_front() {
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+        if let Some(commit) = Commit::from_hash(&hash) {
+            queue.extend(commit.parent_hashes().iter().cloned());
+        }
+    }
+    seen
+}
+
+pub fn is_ancestor(ancestor: &str, descendant: &str) -> bool {
+    ancestors(&[descendant.to_string()]).contains(ancestor)
+}
+
+/// Commits reachable from `include` but not from any of `exclude`
+/// (`exclude..include`), parents ordered before their children.
+pub fn commits_between(exclude: &[String], include: &str) -> Vec<Commit> {
+    let excluded = ancestors(exclude);
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut ordered = Vec::new();
+
+    // Iterative depth first search emitting a commit after its parents
+    let mut stack: Vec<(String, bool)> = vec![(include.to_string(), false)];
+    while let Some((hash, parents_done)) = stack.pop() {
+        if excluded.contains(&hash) || hash.is_empty() {
+            continue;
+        }
+        let commit = match Commit::from_hash(&hash) {
+            Some(commit) => commit,
+            None => continue,
+        };
+        if parents_done {
+            ordered.push(commit);
+            continue;
+        }
+        if !visited.insert(hash.clone()) {
+            continue;
+        }
+        stack.push((hash, true));
+        for parent in commit.parent_hashes().iter().rev() {
+            if !visited.contains(parent) {
+                stack.push((parent.clone(), false));
+            }
+        }
+    }
+    ordered
+}
+
+/// The best common ancestor of two commits: a common ancestor that is
+/// not an ancestor of another common ancestor.
+pub fn merge_base(a: &str, b: &str) -> Option<String> {
+    let of_a = ancestors(&[a.to_string()]);
+    let of_b = ancestors(&[b.to_string()]);
+    let common: Vec<&String> = of_a.intersection(&of_b).collect();
+
+    let parents: Vec<String> = common
+        .iter()
+        .filter_map(|hash| Commit::from_hash(hash))
+        .flat_map(|commit| commit.parent_hashes().clone())
+        .collect();
+    let below = ancestors(&parents);
+
+    common
+        .into_iter()
+        .find(|hash| !below.contains(*hash))
+        .cloned()
+}
+
+/// Counts the commits only reachable from `a` and those only reachable
+/// from `b`. Both sides are walked newest first until only commits
+/// reachable from both are left, so the history below the merge base
+/// is not read.
+pub fn ahead_behind(a: &str, b: &str) -> (usize, usize) {
+    const A: u8 = 1;
+    const B: u8 = 2;
+    let mut flags: HashMap<String, u8> = HashMap::new();
+    let mut commits: HashMap<String, Commit> = HashMap::new();
+    let mut queue: BinaryHeap<(i64, String, u8)> = BinaryHeap::new();
+    for (hash, flag) in [(a, A), (b, B)] {
+        if let Some(commit) = Commit::from_hash(hash) {
+            *flags.entry(hash.to_string()).or_default() |= flag;
+            queue.push((commit_time(&commit), hash.to_string(), flag));
+            commits.insert(hash.to_string(), commit);
+        }
+    }
+
+    // Entries queued before they were reached from both sides
+    let mut one_sided = queue.len();
+    while one_sided > 0 {
+        let Some((_, hash, queued_flag)) = queue.pop() else {
+            break;
+        };
+        if queued_flag != A | B {
+            one_sided -= 1;
+        }
+        let flag = flags[&hash];
+        for parent in commits[&hash].parent_hashes().clone() {
+            let old = flags.get(&parent).copied().unwrap_or_default();
+            if old | flag == old {
+                continue;
+            }
+            let Some(commit) = commits
+                .remove(&parent)
+                .or_else(|| Commit::from_hash(&parent))
+            else {
+                continue;
+            };
+            flags.insert(parent.clone(), old | flag);
+            if old | flag != A | B {
+                one_sided += 1;
+            }
+            queue.push((commit_time(&commit), parent.clone(), old | flag));
+            commits.insert(parent, commit);
+        }
+    }
+
+    let count = |side: u8| flags.values().filter(|flag| **flag == side).count();
+    (count(A), count(B))
+}
@@ -0,0 +1,135 @@
+use std::fs;
+
+use crate::FILE_SYSTEM;
+
+use super::commit::Commit;
+use super::errors::NyxError;
+use super::object_format::object_format;
+use super::object_type::NyxObjectType;
+use super::odb::database::database;
+use super::refs::reflog::Reflog;
+use super::refs::refs;
+use super::remote::remote::FETCH_HEAD;
+use super::shared::read_object_type;
+use super::tag::Tag;
+use super::tree::tree::Tree;
+
+/// Resolves a revision to a full object hash. Supported are
+/// `HEAD`/`@`, ref names, full and abbreviated hashes, reflog
+/// selectors like `stash@{2}`, the suffixes `~<n>` and `^<n>` and
+/// `<rev>:<path>` naming a blob or tree of a commit.
+pub fn resolve(rev: &str) -> Result<String, NyxError> {
+    let invalid = || NyxError::InvalidRevision(rev.to_string());
+
+    if let Some((rev, path)) = rev.split_once(':') {
+        let commit = resolve_commit(if rev.is_empty() { refs::HEAD } else { rev })?;
+        return Tree::lookup(commit.tree_hash(), path)
+            .map(|(_, hash)| hash)
+            .ok_or_else(invalid);
+    }
+
+    let split = rev.find(['~', '^']).unwrap_or(rev.len());
+    let (base, mut suffix) = rev.split_at(split);
+    let mut hash = resolve_base(base).ok_or_else(invalid)?;
+
+    while !suffix.is_empty() {
+        hash = peel(&hash);
+        let operator = suffix.as_bytes()[0];
+        suffix = &suffix[1..];
+        let digits = suffix
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(suffix.len());
+        let count: Option<usize> = suffix[..digits].parse().ok();
+        suffix = &suffix[digits..];
+
+        if operator == b'~' {
+            for _ in 0..count.unwrap_or(1) {
+                let commit = Commit::from_hash(&hash).ok_or_else(invalid)?;
+                hash = commit.get_parent_hash().to_string();
+                if hash.is_empty() {
+                    return Err(invalid());
+                }
+            }
+        } else {
+            let n = count.unwrap_or(1);
+            if n == 0 {
+                continue;
+            }
+            let commit = Commit::from_hash(&hash).ok_or_else(invalid)?;
+            hash = commit
+                .parent_hashes()
+                .get(n - 1)
+                .cloned()
+                .ok_or_else(invalid)?;
+        }
+    }
+    Ok(hash)
+}
+
+fn resolve_base(base: &str) -> Option<String> {
+    if let Some(idx) = base.find("@{") {
+        let name = match &base[..idx] {
+            "" | "@" => refs::HEAD,
+            name => name,
+        };
+        let n: usize = base[idx + 2..].strip_suffix('}')?.parse().ok()?;
+        return Reflog::new(name).get(n).map(|e| e.new_hash.clone());
+    }
+
+    if base == FETCH_HEAD {
+        let content = fs::read_to_string(FILE_SYSTEM.get_state_file_path(FETCH_HEAD)).ok()?;
+        return content.split_whitespace().next().map(String::from);
+    }
+
+    if base == refs::HEAD || base == "@" {
+        let hash = refs::head_hash();
+        return if hash.is_empty() { None } else { Some(hash) };
+    }
+
+    for candidate in [
+        base.to_string(),
+        format!("tags/{}", base),
+        format!("remotes/{}", base),
+    ] {
+        if let Some(hash) = refs::read_ref(&candidate) {
+            return Some(hash);
+        }
+    }
+
+    resolve_hash_prefix(base)
+}
+
+/// Expands an (abbreviated) object hash by searching the object database.
+/// Ambiguous prefixes are not resolved.
+pub fn resolve_hash_prefix(prefix: &str) -> Option<String> {
+    let too_long = prefix.len() > object_format().hex_len();
+    if prefix.len() < 4 || too_long || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let prefix = prefix.to_lowercase();
+    let mut matches = database().with_prefix(&prefix).into_iter();
+
+    let hash = matches.next()?;
+    if matches.next().is_some() {
+        return None;
+    }
+    Some(hash)
+}
+
+/// Follows annotated tags until an object that is not a tag is reached.
+pub fn peel(hash: &str) -> String {
+    let mut hash = hash.to_string();
+    while let Ok(NyxObjectType::Tag) = read_object_type(&hash) {
+        match Tag::from_hash(&hash) {
+            Some(tag) => hash = tag.object().to_string(),
+            None => break,
+        }
+    }
+    hash
+}
+
+/// Resolves a revision and makes sure it names a commit.
+pub fn resolve_commit(rev: &str) -> Result<Commit, NyxError> {
+    let hash = peel(&resolve(rev)?);
+    Commit::from_hash(&hash).ok_or_else(|| NyxError::InvalidRevision(rev.to_string()))
+}
@@ -0,0 +1,478 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::FILE_SYSTEM;
+
+use super::commit::Commit;
+use super::errors::NyxError;
+use super::index::index::Index;
+use super::merge::tree_merge::merge_trees;
+use super::refs::refs;
+use super::revision;
+use super::signature::Signature;
+use super::tree::tree::Tree;
+use super::worktree::{apply_tree_merge, reset_hard, staged_changes, unstaged_changes};
+
+const MERGE_MSG: &str = "MERGE_MSG";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Pick,
+    Revert,
+}
+
+impl Action {
+    fn name(&self) -> &str {
+        match self {
+            Action::Pick => "pick",
+            Action::Revert => "revert",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "pick" => Some(Action::Pick),
+            "revert" => Some(Action::Revert),
+            _ => None,
+        }
+    }
+
+    /// Name of the user facing command
+    pub fn command(&self) -> &str {
+        match self {
+            Action::Pick => "cherry-pick",
+            Action::Revert => "revert",
+        }
+    }
+
+    /// State file naming the commit that is currently applied
+    fn head_file(&self) -> &str {
+        match self {
+            Action::Pick => "CHERRY_PICK_HEAD",
+            Action::Revert => "REVERT_HEAD",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SequencerOptions {
+    /// Parent number to diff against when picking a merge commit
+    pub mainline: Option<usize>,
+    /// Append "(cherry picked from commit ...)" to the message
+    pub record_origin: bool,
+    /// Only update index and working tree
+    pub no_commit: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PickOutcome {
+    Applied,
+    Empty,
+    Conflict(Vec<String>),
+}
+
+pub fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
+
+/// Tree of the parent the change of `commit` is computed against.
+fn parent_tree(commit: &Commit, mainline: Option<usize>) -> Result<Option<String>, NyxError> {
+    let parents = commit.parent_hashes();
+    let parent = match (parents.len(), mainline) {
+        (0, _) => return Ok(None),
+        (1, _) => &parents[0],
+        (_, Some(n)) if n >= 1 && n <= parents.len() => &parents[n - 1],
+        (_, Some(n)) => {
+            return Err(NyxError::Usage(format!(
+                "commit {} does not have parent {}",
+                commit.get_hash(),
+                n
+            )))
+        }
+        (_, None) => {
+            return Err(NyxError::Usage(format!(
+                "commit {} is a merge but no -m option was given",
+                commit.get_hash()
+            )))
+        }
+    };
+    Ok(Some(Commit::read(parent)?.tree_hash().to_string()))
+}
+
+/// Applies the change `commit` introduced relative to its parent (or the
+/// inverse change for a revert) to index and working tree via a three-way
+/// merge with the current index.
+pub fn apply_change(
+    commit: &Commit,
+    action: Action,
+    mainline: Option<usize>,
+) -> Result<PickOutcome, NyxError> {
+    let parent_paths = Tree::read_paths(parent_tree(commit, mainline)?.as_deref());
+    let commit_paths = Tree::read_paths(Some(commit.tree_hash()));
+    let (base, theirs) = match action {
+        Action::Pick => (parent_paths, commit_paths),
+        Action::Revert => (commit_paths, parent_paths),
+    };
+    let head_paths = match Commit::from_head() {
+        Some(head) => Tree::read_paths(Some(head.tree_hash())),
+        None => BTreeMap::new(),
+    };
+
+    let mut index = Index::new();
+    let ours = index.to_map();
+    let theirs_label = format!("{}... {}", short_hash(commit.get_hash()), commit.summary());
+    let merge = merge_trees(&base, &ours, &theirs, "HEAD", &theirs_label)?;
+    let merged = merge.to_map();
+
+    let touched: Vec<String> = unstaged_changes(&index)
+        .into_iter()
+        .filter(|path| merged.get(path) != ours.get(path) || merge.conflicts().contains(path))
+        .collect();
+    if !touched.is_empty() {
+        return Err(NyxError::LocalChanges(touched));
+    }
+
+    apply_tree_merge(&merge, &ours, &head_paths, &mut index)?;
+
+    let conflicts = merge.conflicts();
+    if !conflicts.is_empty() {
+        Ok(PickOutcome::Conflict(conflicts))
+    } else if merged == ours {
+        Ok(PickOutcome::Empty)
+    } else {
+        Ok(PickOutcome::Applied)
+    }
+}
+
+/// Message of the commit that replays or reverts `commit`.
+pub fn pick_message(commit: &Commit, action: Action, record_origin: bool) -> String {
+    match action {
+        Action::Pick if record_origin => format!(
+            "{}\n\n(cherry picked from commit {})",
+            commit.message(),
+            commit.get_hash()
+        ),
+        Action::Pick => commit.message().to_string(),
+        Action::Revert => format!(
+            "Revert \"{}\"\n\nThis reverts commit {}.",
+            commit.summary(),
+            commit.get_hash()
+        ),
+    }
+}
+
+/// Commits the index on top of HEAD. Returns `None` if the index
+/// does not differ from HEAD.
+pub fn commit_index(
+    message: &str,
+    author: Option<Signature>,
+    reflog_action: &str,
+) -> Result<Option<Commit>, NyxError> {
+    let head = Commit::from_head();
+    let tree = Index::new().write_tree();
+    if let Some(head) = &head {
+        if head.tree_hash() == tree.hash {
+            return Ok(None);
+        }
+    }
+    let mut commit = Commit::new(&tree.hash, message);
+    if author.is_some() {
+        commit.set_author(author);
+    }
+    commit.write_as(reflog_action);
+
+    let branch = refs::current_branch().unwrap_or_else(|| String::from("detached HEAD"));
+    println!(
+        "[{} {}] {}",
+        branch,
+        short_hash(commit.get_hash()),
+        commit.summary()
+    );
+    Ok(Some(commit))
+}
+
+/// Replaces HEAD with a commit of the current index that has the same
+/// parents and author, used to meld changes into the previous commit.
+pub fn amend_head(message: &str, reflog_action: &str) -> Result<Commit, NyxError> {
+    let head = Commit::read(&refs::head_hash())?;
+    let tree = Index::new().write_tree();
+    let mut commit = Commit::with_parents(&tree.hash, head.parent_hashes().clone(), message);
+    commit.set_author(head.author().cloned());
+    commit.write_as(reflog_action);
+
+    let branch = refs::current_branch().unwrap_or_else(|| String::from("detached HEAD"));
+    println!(
+        "[{} {}] {}",
+        branch,
+        short_hash(commit.get_hash()),
+        commit.summary()
+    );
+    Ok(commit)
+}
+
+/// Applies a list of commits one after another and keeps track of the
+/// remaining ones in `.nyx/sequencer/` when a conflict interrupts it.
+pub struct Sequencer {
+    action: Action,
+    todo: Vec<String>,
+    head: String,
+    options: SequencerOptions,
+}
+
+impl Sequencer {
+    pub fn in_progress() -> bool {
+        FILE_SYSTEM.get_sequencer_dir_path().exists()
+    }
+
+    pub fn start(
+        action: Action,
+        revs: &[String],
+        options: SequencerOptions,
+    ) -> Result<(), NyxError> {
+        if Sequencer::in_progress() {
+            return Err(NyxError::Usage(String::from(
+                "a cherry-pick or revert is already in progress, try --continue or --abort",
+            )));
+        }
+        let head = refs::head_hash();
+        if head.is_empty() {
+            return Err(NyxError::Usage(String::from(
+                "You do not have the initial commit yet",
+            )));
+        }
+        let index = Index::new();
+        let head_paths = Tree::read_paths(Some(Commit::read(&head)?.tree_hash()));
+        if !options.no_commit && !staged_changes(&index, &head_paths).is_empty() {
+            return Err(NyxError::LocalChanges(staged_changes(&index, &head_paths)));
+        }
+
+        let todo = revs
+            .iter()
+            .map(|rev| revision::resolve_commit(rev).map(|c| c.get_hash().to_string()))
+            .collect::<Result<Vec<String>, NyxError>>()?;
+
+        let mut sequencer = Sequencer {
+            action,
+            todo,
+            head,
+            options,
+        };
+        sequencer.run()
+    }
+
+    fn run(&mut self) -> Result<(), NyxError> {
+        let remaining = self.todo.len();
+        while let Some(hash) = self.todo.first().cloned() {
+            match self.pick(&hash) {
+                Ok(()) => {
+                    self.todo.remove(0);
+                }
+                Err(err @ NyxError::MergeConflict(_)) => return Err(err),
+                Err(err) => {
+                    // Commits picked before the failure stay, keep the rest
+                    // to be continued or aborted
+                    if self.todo.len() < remaining || Sequencer::in_progress() {
+                        self.save()?;
+                        println!(
+                            "hint: run 'nyx {0} --continue' or 'nyx {0} --abort'",
+                            self.action.command()
+                        );
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Sequencer::clear()
+    }
+
+    fn pick(&self, hash: &str) -> Result<(), NyxError> {
+        let commit = Commit::read(hash)?;
+        let message = pick_message(&commit, self.action, self.options.record_origin);
+
+        match apply_change(&commit, self.action, self.options.mainline)? {
+            PickOutcome::Conflict(paths) => {
+                self.save()?;
+                fs::write(FILE_SYSTEM.get_state_file_path(MERGE_MSG), &message)?;
+                fs::write(
+                    FILE_SYSTEM.get_state_file_path(self.action.head_file()),
+                    hash,
+                )?;
+                for path in &paths {
+                    println!("CONFLICT (content): Merge conflict in {}", path);
+                }
+                println!(
+                    "error: could not {} {}... {}",
+                    self.action.name(),
+                    short_hash(hash),
+                    commit.summary()
+                );
+                println!("hint: after resolving the conflicts, mark the corrected paths");
+                println!(
+                    "hint: with 'nyx add <paths>' and run 'nyx {} --continue'",
+                    self.action.command()
+                );
+                return Err(NyxError::MergeConflict(paths));
+            }
+            PickOutcome::Empty => {
+                println!(
+                    "Skipping {}... {}: the changes are already present",
+                    short_hash(hash),
+                    commit.summary()
+                );
+            }
+            PickOutcome::Applied => {
+                if !self.options.no_commit {
+                    commit_index(&message, self.author_of(&commit), self.action.command())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn author_of(&self, commit: &Commit) -> Option<Signature> {
+        match self.action {
+            Action::Pick => commit.author().cloned(),
+            Action::Revert => None,
+        }
+    }
+
+    /// Commits the resolved conflict and applies the remaining commits.
+    pub fn resume() -> Result<(), NyxError> {
+        let mut sequencer = Sequencer::load()?;
+        let index = Index::new();
+        let unmerged = index.unmerged_paths();
+        if !unmerged.is_empty() {
+            return Err(NyxError::Usage(format!(
+                "Committing is not possible because you have unmerged files: {}",
+                unmerged.join(", ")
+            )));
+        }
+
+        let head_file = FILE_SYSTEM.get_state_file_path(sequencer.action.head_file());
+        if head_file.exists() {
+            let hash = fs::read_to_string(&head_file)?;
+            let commit = Commit::read(hash.trim())?;
+            let message = fs::read_to_string(FILE_SYSTEM.get_state_file_path(MERGE_MSG))
+                .unwrap_or_else(|_| commit.message().to_string());
+            if !sequencer.options.no_commit {
+                commit_index(
+                    &message,
+                    sequencer.author_of(&commit),
+                    sequencer.action.command(),
+                )?;
+            }
+            sequencer.todo.retain(|h| *h != commit.get_hash());
+            Sequencer::remove_state_files(sequencer.action)?;
+        }
+        sequencer.run()
+    }
+
+    /// Drops the commit that caused the conflict and carries on.
+    pub fn skip() -> Result<(), NyxError> {
+        let mut sequencer = Sequencer::load()?;
+        let head = Commit::read(&refs::head_hash())?;
+        reset_hard(&mut Index::new(), &Tree::read_paths(Some(head.tree_hash())))?;
+
+        if !sequencer.todo.is_empty() {
+            sequencer.todo.remove(0);
+        }
+        Sequencer::remove_state_files(sequencer.action)?;
+        sequencer.run()
+    }
+
+    /// Returns to the commit that was checked out before the sequence started.
+    pub fn abort() -> Result<(), NyxError> {
+        let sequencer = Sequencer::load()?;
+        let original = Commit::read(&sequencer.head)?;
+        reset_hard(
+            &mut Index::new(),
+            &Tree::read_paths(Some(original.tree_hash())),
+        )?;
+        if refs::head_hash() != sequencer.head {
+            refs::update_head(
+                &sequencer.head,
+                &format!("{}: abort", sequencer.action.command()),
+            )?;
+        }
+        Sequencer::remove_state_files(sequencer.action)?;
+        Sequencer::clear()
+    }
+
+    fn save(&self) -> Result<(), NyxError> {
+        let dir = FILE_SYSTEM.get_sequencer_dir_path();
+        fs::create_dir_all(&dir)?;
+        let todo: Vec<String> = self
+            .todo
+            .iter()
+            .map(|hash| format!("{} {}\n", self.action.name(), hash))
+            .collect();
+        fs::write(dir.join("todo"), todo.concat())?;
+        fs::write(dir.join("head"), &self.head)?;
+        let mut options = format!(
+            "record-origin={}\nno-commit={}\n",
+            self.options.record_origin, self.options.no_commit
+        );
+        if let Some(mainline) = self.options.mainline {
+            options.push_str(&format!("mainline={}\n", mainline));
+        }
+        fs::write(dir.join("opts"), options)?;
+        Ok(())
+    }
+
+    fn load() -> Result<Self, NyxError> {
+        let dir = FILE_SYSTEM.get_sequencer_dir_path();
+        if !dir.exists() {
+            return Err(NyxError::Usage(String::from(
+                "no cherry-pick or revert in progress",
+            )));
+        }
+
+        let mut action = Action::Pick;
+        let mut todo = Vec::new();
+        for line in fs::read_to_string(dir.join("todo"))?.lines() {
+            if let Some((name, hash)) = line.split_once(' ') {
+                action = Action::parse(name).unwrap_or(action);
+                todo.push(hash.to_string());
+            }
+        }
+        let head = fs::read_to_string(dir.join("head"))?.trim().to_string();
+
+        let mut options = SequencerOptions::default();
+        for line in fs::read_to_string(dir.join("opts"))
+            .unwrap_or_default()
+            .lines()
+        {
+            match line.split_once('=') {
+                Some(("record-origin", value)) => options.record_origin = value == "true",
+                Some(("no-commit", value)) => options.no_commit = value == "true",
+                Some(("mainline", value)) => options.mainline = value.parse().ok(),
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            action,
+            todo,
+            head,
+            options,
+        })
+    }
+
+    fn remove_state_files(action: Action) -> Result<(), NyxError> {
+        for name in [action.head_file(), MERGE_MSG] {
+            let path = FILE_SYSTEM.get_state_file_path(name);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear() -> Result<(), NyxError> {
+        let dir = FILE_SYSTEM.get_sequencer_dir_path();
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
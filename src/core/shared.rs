@@ -1,30 +1,47 @@
 use format_bytes::format_bytes;
-use sha1::{Digest, Sha1};
-use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::Path;
 
-use crate::FILE_SYSTEM;
+use super::{
+    errors::NyxError,
+    object_format::object_format,
+    object_type::NyxObjectType,
+    odb::{database::database, reader::ObjectReader, stream, writer::ObjectWriter},
+};
 
-use super::{errors::NyxError, object_type::NyxObjectType};
+/// Computes the hash of an object without storing it.
+pub fn hash_object(content: &[u8], object_type: NyxObjectType) -> String {
+    calculate_hash(&append_object_header(content, object_type))
+}
 
-// TODO: split in creation and writing
+/// Stores an object and returns its hash.
 pub fn generate_object(content: &[u8], object_type: NyxObjectType) -> String {
     let content = append_object_header(content, object_type);
-    let hash = calculate_sha1(&content);
-
-    let object_dir_path = FILE_SYSTEM.get_object_dir_path(&hash[..2]);
+    let hash = calculate_hash(&content);
+    database().write(&hash, &content).unwrap();
+    hash
+}
 
-    if !object_dir_path.exists() {
-        fs::create_dir(&object_dir_path).unwrap();
-    }
+/// Computes the object hash of a file, reading it in chunks.
+pub fn hash_file(path: &Path, object_type: NyxObjectType) -> Result<String, NyxError> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut writer = ObjectWriter::new(io::sink(), object_type, size)?;
+    stream::copy(&mut file, &mut writer)?;
+    Ok(writer.finish()?.1)
+}
 
-    fs::write(object_dir_path.join(&hash[2..]), &content).unwrap();
-    hash
+/// Stores a file as object, streaming it into the object database.
+pub fn write_file_object(path: &Path, object_type: NyxObjectType) -> Result<String, NyxError> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    database().write_stream(object_type, size, &mut file)
 }
 
-pub fn calculate_sha1(content: &[u8]) -> String {
-    let mut hasher = Sha1::new();
-    hasher.update(content);
-    hex::encode(hasher.finalize())
+/// Hashes with the function of the object format of the repository.
+pub fn calculate_hash(content: &[u8]) -> String {
+    object_format().digest(content)
 }
 
 pub fn append_object_header(content: &[u8], object_type: NyxObjectType) -> Vec<u8> {
@@ -33,20 +50,55 @@ pub fn append_object_header(content: &[u8], object_type: NyxObjectType) -> Vec<u
     format_bytes!(b"{} {}\0{}", object_type_bytes, content_len_bytes, content)
 }
 
+/// Opens an object to read its content in chunks, e.g. to restore a
+/// large file without holding it in memory.
+pub fn open_object(hash: &str) -> Result<ObjectReader, NyxError> {
+    database().reader(hash)
+}
+
 pub fn read_object_data(hash: &str) -> Result<String, NyxError> {
-    let path = FILE_SYSTEM.get_object_path(&hash[..2], &hash[2..]);
-    let content = fs::read(path)?;
-    let index = &content.iter().position(|x| *x == 0).unwrap();
-    let content = &content[*index + 1..];
+    Ok(String::from_utf8(read_object_content(hash)?)?)
+}
 
-    let content = std::str::from_utf8(&content)?;
+/// Reads the content of an object as bytes, e.g. of binary blobs.
+pub fn read_object_content(hash: &str) -> Result<Vec<u8>, NyxError> {
+    let mut content = database().read(hash)?;
+    let index = content
+        .iter()
+        .position(|x| *x == 0)
+        .ok_or_else(|| NyxError::InvalidObject(hash.to_string()))?;
+    content.drain(..=index);
+    Ok(content)
+}
+
+pub fn object_exists(hash: &str) -> bool {
+    database().exists(hash)
+}
+
+/// Reads an object including its header, e.g. to send it to a remote.
+pub fn read_raw_object(hash: &str) -> Result<Vec<u8>, NyxError> {
+    database().read(hash)
+}
+
+/// Stores an object including its header as received from a remote.
+pub fn write_raw_object(hash: &str, raw: &[u8]) -> Result<(), NyxError> {
+    database().write(hash, raw)
+}
 
-    Ok(content.to_string())
+/// Splits an object into its type and content.
+pub fn split_raw_object(raw: &[u8]) -> Option<(NyxObjectType, &[u8])> {
+    let nul = raw.iter().position(|b| *b == 0)?;
+    let header = std::str::from_utf8(&raw[..nul]).ok()?;
+    let object_type = NyxObjectType::parse(header.split(' ').next()?)?;
+    Some((object_type, &raw[nul + 1..]))
 }
 
-pub fn get_object_hash(path: &str) -> String {
-    let content = fs::read(path).unwrap();
-    let object_hash = generate_object(&content, NyxObjectType::Blob);
+/// Hashes of all objects in the object database, sorted.
+pub fn list_objects() -> Vec<String> {
+    database().iter().collect()
+}
 
-    return object_hash;
+/// Reads the type from the header of an object.
+pub fn read_object_type(hash: &str) -> Result<NyxObjectType, NyxError> {
+    Ok(open_object(hash)?.object_type)
 }
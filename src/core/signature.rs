@@ -0,0 +1,71 @@
+use std::env;
+use std::fmt::Display;
+
+use super::config::Config;
+use super::date;
+
+/// Identity and point in time of an author or committer,
+/// serialized as `Name <email> 1658000000 +0000`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub time: i64,
+    pub offset: String,
+}
+
+impl Signature {
+    /// Creates a signature for the current user at the current time.
+    /// The identity is taken from `NYX_AUTHOR_NAME`/`NYX_AUTHOR_EMAIL`,
+    /// falling back to `user.name`/`user.email` of the config.
+    pub fn now() -> Self {
+        let config = Config::new();
+        let name = env::var("NYX_AUTHOR_NAME")
+            .ok()
+            .or_else(|| config.get("user.name"))
+            .or_else(|| env::var("USER").ok())
+            .unwrap_or_else(|| String::from("unknown"));
+        let email = env::var("NYX_AUTHOR_EMAIL")
+            .ok()
+            .or_else(|| config.get("user.email"))
+            .unwrap_or_default();
+        let time = match env::var("NYX_AUTHOR_DATE") {
+            Ok(value) => date::parse_date(&value).unwrap_or_else(date::now),
+            Err(_) => date::now(),
+        };
+
+        Self {
+            name,
+            email,
+            time,
+            offset: String::from("+0000"),
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let open = value.find('<')?;
+        let close = value.rfind('>')?;
+        let name = value[..open].trim().to_string();
+        let email = value[open + 1..close].to_string();
+        let mut rest = value[close + 1..].split_whitespace();
+        let time = rest.next()?.parse().ok()?;
+        let offset = rest.next().unwrap_or("+0000").to_string();
+
+        Some(Self {
+            name,
+            email,
+            time,
+            offset,
+        })
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} <{}> {} {}",
+            self.name, self.email, self.time, self.offset
+        )
+    }
+}
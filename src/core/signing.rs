@@ -0,0 +1,136 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use ssh_key::{HashAlg, LineEnding, PrivateKey, PublicKey, SshSig};
+
+use super::config::Config;
+use super::errors::NyxError;
+
+/// Signatures are made for this namespace only, so they cannot be
+/// passed off as signatures of files or other tools.
+const NAMESPACE: &str = "nyx";
+
+/// Config key with the path of the private key commits and tags are
+/// signed with, an OpenSSH key file such as `~/.ssh/id_ed25519`.
+pub const SIGNING_KEY: &str = "user.signingKey";
+/// Config key with the path of the file listing trusted keys, one
+/// `<principals> [options] <key type> <base64 key>` per line.
+pub const ALLOWED_SIGNERS: &str = "gpg.ssh.allowedSignersFile";
+
+/// First line of an armored signature.
+pub const SIGNATURE_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
+
+/// Paths starting with `~/` are relative to the home directory.
+fn config_path(key: &str) -> Result<PathBuf, NyxError> {
+    let path = Config::new()
+        .get(key)
+        .ok_or_else(|| NyxError::Usage(format!("{} is not set", key)))?;
+    Ok(match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    })
+}
+
+/// Signs `payload` with the key of `user.signingKey` and returns the
+/// armored signature.
+pub fn sign(payload: &[u8]) -> Result<String, NyxError> {
+    let path = config_path(SIGNING_KEY)?;
+    let key = PrivateKey::read_openssh_file(&path).map_err(|err| {
+        NyxError::Usage(format!(
+            "cannot read signing key '{}': {}",
+            path.display(),
+            err
+        ))
+    })?;
+    if key.is_encrypted() {
+        return Err(NyxError::Usage(format!(
+            "signing key '{}' is encrypted, which is not supported",
+            path.display()
+        )));
+    }
+    key.sign(NAMESPACE, HashAlg::Sha512, payload)
+        .and_then(|signature| signature.to_pem(LineEnding::LF))
+        .map(|signature| signature.trim_end().to_string())
+        .map_err(|err| NyxError::Usage(format!("signing failed: {}", err)))
+}
+
+/// Principals and keys of the allowed signers file, an unset or
+/// missing file trusts nobody.
+fn allowed_signers() -> Vec<(String, PublicKey)> {
+    let content = config_path(ALLOWED_SIGNERS)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (principals, rest) = line.split_once(char::is_whitespace)?;
+            // Options may come before the key, which starts with its type
+            let key_start = rest
+                .split_whitespace()
+                .position(|word| word.starts_with("ssh-") || word.starts_with("ecdsa-"))?;
+            let key = rest
+                .split_whitespace()
+                .skip(key_start)
+                .collect::<Vec<&str>>()
+                .join(" ");
+            Some((principals.to_string(), PublicKey::from_openssh(&key).ok()?))
+        })
+        .collect()
+}
+
+/// Outcome of checking a signature.
+pub struct Verification {
+    /// Whether the signature was made over the payload by its key.
+    pub valid: bool,
+    /// Principals of the allowed signers file trusting the key.
+    pub principal: Option<String>,
+    pub key_type: String,
+    pub fingerprint: String,
+}
+
+impl Verification {
+    /// Valid and made by a trusted key.
+    pub fn is_good(&self) -> bool {
+        self.valid && self.principal.is_some()
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quality = match self.valid {
+            true => "Good",
+            false => "Bad",
+        };
+        write!(f, "{} \"{}\" signature", quality, NAMESPACE)?;
+        if let Some(principal) = &self.principal {
+            write!(f, " for {}", principal)?;
+        }
+        write!(f, " with {} key {}", self.key_type, self.fingerprint)?;
+        if self.valid && self.principal.is_none() {
+            write!(f, "\nNo principal matched.")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks an armored signature of `payload` against the allowed signers.
+pub fn verify(payload: &[u8], signature: &str) -> Result<Verification, NyxError> {
+    let signature = SshSig::from_pem(signature)
+        .map_err(|err| NyxError::Usage(format!("malformed signature: {}", err)))?;
+    let key = PublicKey::from(signature.public_key().clone());
+    let principal = allowed_signers()
+        .into_iter()
+        .find(|(_, allowed)| allowed.key_data() == key.key_data())
+        .map(|(principals, _)| principals);
+    Ok(Verification {
+        valid: key.verify(NAMESPACE, payload, &signature).is_ok(),
+        principal,
+        key_type: key.algorithm().to_string(),
+        fingerprint: key.fingerprint(HashAlg::Sha256).to_string(),
+    })
+}
@@ -0,0 +1,167 @@
+use super::errors::NyxError;
+use super::object_type::NyxObjectType;
+use super::shared::{generate_object, read_object_data};
+use super::signature::Signature;
+use super::signing::{self, Verification, SIGNATURE_BEGIN};
+
+/// An annotated tag object pointing to another object, usually a commit.
+#[derive(Debug, Clone)]
+pub struct Tag {
+    object: String,
+    object_type: NyxObjectType,
+    name: String,
+    tagger: Option<Signature>,
+    /// Armored signature, stored after the message like in git.
+    signature: Option<String>,
+    hash: String,
+    message: String,
+    /// Content of the object the tag was read from.
+    raw: Option<String>,
+}
+
+impl Tag {
+    pub fn new(object: &str, object_type: NyxObjectType, name: &str, message: &str) -> Self {
+        Self {
+            object: object.to_string(),
+            object_type,
+            name: name.to_string(),
+            tagger: Some(Signature::now()),
+            signature: None,
+            hash: String::new(),
+            message: message.to_string(),
+            raw: None,
+        }
+    }
+
+    pub fn from_hash(hash: &str) -> Option<Self> {
+        if hash.is_empty() {
+            return None;
+        }
+        let content = read_object_data(hash).ok()?;
+        Tag::parse(hash, &content)
+    }
+
+    pub fn read(hash: &str) -> Result<Self, NyxError> {
+        Tag::from_hash(hash).ok_or_else(|| NyxError::InvalidObject(hash.to_string()))
+    }
+
+    fn parse(hash: &str, content: &str) -> Option<Self> {
+        let (headers, message) = content.split_once("\n\n").unwrap_or((content, ""));
+        let (message, signature) = match message.find(SIGNATURE_BEGIN) {
+            Some(idx) => (&message[..idx], Some(message[idx..].trim_end().to_string())),
+            None => (message, None),
+        };
+        let mut object = None;
+        let mut object_type = None;
+        let mut name = String::new();
+        let mut tagger = None;
+
+        for line in headers.lines() {
+            match line.split_once(' ')? {
+                ("object", value) => object = Some(value.to_string()),
+                ("type", value) => object_type = NyxObjectType::parse(value),
+                ("tag", value) => name = value.to_string(),
+                ("tagger", value) => tagger = Signature::parse(value),
+                _ => (),
+            }
+        }
+
+        Some(Self {
+            object: object?,
+            object_type: object_type?,
+            name,
+            tagger,
+            signature,
+            hash: hash.to_string(),
+            message: message.trim_end().to_string(),
+            raw: Some(content.to_string()),
+        })
+    }
+
+    pub fn get_content(&self) -> String {
+        let mut content = format!(
+            "object {}\ntype {}\ntag {}\n",
+            self.object, self.object_type, self.name
+        );
+        if let Some(tagger) = &self.tagger {
+            content = format!("{}tagger {}\n", content, tagger);
+        }
+        content = format!("{}\n{}\n", content, self.message);
+        if let Some(signature) = &self.signature {
+            content = format!("{}{}\n", content, signature);
+        }
+        content
+    }
+
+    /// The content without the signature, which is what gets signed.
+    /// Tags that were read keep their content byte for byte up to the
+    /// signature.
+    pub fn payload(&self) -> String {
+        let raw = match &self.raw {
+            Some(raw) => raw,
+            None => {
+                return Tag {
+                    signature: None,
+                    ..self.clone()
+                }
+                .get_content()
+            }
+        };
+        let body = raw.find("\n\n").map_or(raw.len(), |idx| idx + 2);
+        match raw[body..].find(SIGNATURE_BEGIN) {
+            Some(idx) => raw[..body + idx].to_string(),
+            None => raw.to_string(),
+        }
+    }
+
+    /// Signs the tag with the key of `user.signingKey`, must happen
+    /// before it is written.
+    pub fn sign(&mut self) -> Result<(), NyxError> {
+        // Reading the object trims the message, the payload must match
+        self.message = self.message.trim_end().to_string();
+        self.raw = None;
+        self.signature = Some(signing::sign(self.payload().as_bytes())?);
+        Ok(())
+    }
+
+    /// Checks the signature, `None` if the tag is not signed.
+    pub fn verify(&self) -> Result<Option<Verification>, NyxError> {
+        match &self.signature {
+            Some(signature) => signing::verify(self.payload().as_bytes(), signature).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn write_object(&mut self) -> &str {
+        self.hash = generate_object(self.get_content().as_bytes(), NyxObjectType::Tag);
+        &self.hash
+    }
+
+    pub fn get_hash(&self) -> &str {
+        &self.hash
+    }
+
+    pub fn object(&self) -> &str {
+        &self.object
+    }
+
+    pub fn object_type(&self) -> NyxObjectType {
+        self.object_type
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn tagger(&self) -> Option<&Signature> {
+        self.tagger.as_ref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn set_tagger(&mut self, tagger: Option<Signature>) {
+        self.tagger = tagger;
+    }
+}
@@ -25,6 +25,6 @@ impl Byte for Vec<TreeEntry> {
             .iter()
             .map(|e| format_bytes!(b"{}\n", e.as_bytes()))
             .collect();
-        (&bytes_vec.concat()[..]).to_vec()
+        bytes_vec.concat()
     }
 }
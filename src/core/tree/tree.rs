@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use super::entry::TreeEntry;
 use crate::{core::shared::read_object_data, NyxObjectType};
 
@@ -9,6 +11,12 @@ pub struct Tree {
     pub path: String,
 }
 
+impl Default for Tree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Tree {
     pub fn new() -> Self {
         Self {
@@ -48,7 +56,7 @@ impl Tree {
 
     fn add_entry(&mut self, hash: &str, name: &str, entry_type: NyxObjectType) {
         self.entries.push(TreeEntry {
-            entry_type: entry_type,
+            entry_type,
             hash: hash.to_string(),
             path: name.to_string(),
         });
@@ -74,4 +82,59 @@ impl Tree {
     pub fn get_tree_by_hash(&self, hash: &str) -> Option<&Tree> {
         self.trees.iter().find(|t| t.hash == hash)
     }
+
+    /// Maps the path of every blob in this tree and its subtrees
+    /// to the blob hash, e.g. `src/main.rs -> 3b18e5...`.
+    pub fn flatten(&self) -> BTreeMap<String, String> {
+        let mut paths = BTreeMap::new();
+        self.flatten_recursive("", &mut paths);
+        paths
+    }
+
+    fn flatten_recursive(&self, prefix: &str, paths: &mut BTreeMap<String, String>) {
+        for entry in &self.entries {
+            let path = format!("{}{}", prefix, entry.path);
+            match entry.entry_type {
+                NyxObjectType::Blob => {
+                    paths.insert(path, entry.hash.clone());
+                }
+                NyxObjectType::Tree => {
+                    if let Some(tree) = self.get_tree_by_hash(&entry.hash) {
+                        tree.flatten_recursive(&format!("{}/", path), paths);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Flattens the tree with the given hash, `None` yields no paths.
+    pub fn read_paths(hash: Option<&str>) -> BTreeMap<String, String> {
+        match hash {
+            Some(hash) if !hash.is_empty() => Tree::from_hash(hash).flatten(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Finds the entry at `path` (e.g. `src/core`) below the tree with
+    /// the given hash and returns its type and hash.
+    pub fn lookup(hash: &str, path: &str) -> Option<(NyxObjectType, String)> {
+        let mut current = (NyxObjectType::Tree, hash.to_string());
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            if current.0 != NyxObjectType::Tree {
+                return None;
+            }
+            let content = read_object_data(&current.1).ok()?;
+            current = content.lines().find_map(|line| {
+                let line: Vec<&str> = line.split_whitespace().collect();
+                match line.as_slice() {
+                    [entry_type, hash, entry_name] if *entry_name == name => {
+                        Some((NyxObjectType::parse(entry_type)?, hash.to_string()))
+                    }
+                    _ => None,
+                }
+            })?;
+        }
+        Some(current)
+    }
 }
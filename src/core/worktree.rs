@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::FILE_SYSTEM;
+
+use super::errors::NyxError;
+use super::index::file_state::NyxFileState;
+use super::index::index::Index;
+use super::lfs::filter;
+use super::merge::tree_merge::{MergedEntry, TreeMerge};
+
+/// Absolute path of a path relative to the repository root.
+pub fn working_path(path: &str) -> PathBuf {
+    FILE_SYSTEM.get_root_dir().join(path)
+}
+
+/// Computes the blob hash of a working tree file without writing
+/// an object. Returns `None` if the file does not exist.
+pub fn hash_working_file(path: &str) -> Option<String> {
+    filter::hash_working(&working_path(path), path).ok()
+}
+
+/// Stores a working tree file as blob and returns its hash.
+pub fn write_working_file_object(path: &str) -> Option<String> {
+    filter::write_working(&working_path(path), path).ok()
+}
+
+pub fn write_working_file(path: &str, content: &[u8]) -> Result<(), NyxError> {
+    let path = working_path(path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Restores a blob to the working tree, streaming its content.
+pub fn write_working_blob(path: &str, hash: &str) -> Result<(), NyxError> {
+    let path = working_path(path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    filter::restore(hash, &path)
+}
+
+/// Removes a working tree file and all directories it leaves empty.
+pub fn remove_working_file(path: &str) -> Result<(), NyxError> {
+    let path = working_path(path);
+    if path.is_file() {
+        fs::remove_file(&path)?;
+    }
+    let root = FILE_SYSTEM.get_root_dir();
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if d == root.as_path() || fs::remove_dir(d).is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+    Ok(())
+}
+
+/// Brings the working tree from the `old` paths to the `new` paths.
+/// Files that are equal in both are left untouched.
+pub fn update_working_tree(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Result<(), NyxError> {
+    for path in old.keys() {
+        if !new.contains_key(path) {
+            remove_working_file(path)?;
+        }
+    }
+    for (path, hash) in new {
+        if old.get(path) != Some(hash) || !working_path(path).exists() {
+            write_working_blob(path, hash)?;
+        }
+    }
+    Ok(())
+}
+
+/// Makes working tree and index match the given commit paths. Unlike
+/// `update_working_tree` the actual file contents are compared, so
+/// conflict markers and other local edits are discarded.
+pub fn reset_hard(index: &mut Index, target: &BTreeMap<String, String>) -> Result<(), NyxError> {
+    for entry in index.entries() {
+        if !target.contains_key(&entry.path) {
+            remove_working_file(&entry.path)?;
+        }
+    }
+    for (path, hash) in target {
+        if hash_working_file(path).as_ref() != Some(hash) {
+            write_working_blob(path, hash)?;
+        }
+    }
+    index.reset_to(target, NyxFileState::Committed);
+    Ok(())
+}
+
+/// Tracked paths whose working tree content differs from the index,
+/// including deleted files.
+pub fn unstaged_changes(index: &Index) -> Vec<String> {
+    index
+        .entries()
+        .iter()
+        .filter(|e| hash_working_file(&e.path).as_deref() != Some(e.hash.as_str()))
+        .map(|e| e.path.clone())
+        .collect()
+}
+
+/// Paths whose indexed version differs from the given commit paths.
+pub fn staged_changes(index: &Index, head: &BTreeMap<String, String>) -> Vec<String> {
+    let indexed = index.to_map();
+    let mut paths: Vec<String> = indexed
+        .iter()
+        .filter(|(path, hash)| head.get(*path) != Some(*hash))
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in head.keys() {
+        if !indexed.contains_key(path) {
+            paths.push(path.clone());
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// Snapshot of the tracked files as they are in the working tree.
+/// Their content is written as blobs, deleted files are left out.
+pub fn snapshot_tracked_files(index: &Index) -> BTreeMap<String, String> {
+    index
+        .entries()
+        .iter()
+        .filter_map(|e| write_working_file_object(&e.path).map(|hash| (e.path.clone(), hash)))
+        .collect()
+}
+
+/// Writes the result of a merge on top of `ours` to the working tree and
+/// stages it. Conflicted files get their markers written and are marked
+/// as unmerged in the index.
+pub fn apply_tree_merge(
+    merge: &TreeMerge,
+    ours: &BTreeMap<String, String>,
+    head: &BTreeMap<String, String>,
+    index: &mut Index,
+) -> Result<(), NyxError> {
+    for path in ours.keys() {
+        if !merge.entries.contains_key(path) {
+            remove_working_file(path)?;
+            index.remove_entry(path);
+        }
+    }
+    for (path, entry) in &merge.entries {
+        match entry {
+            MergedEntry::Clean(hash) => {
+                if ours.get(path) != Some(hash) || !working_path(path).exists() {
+                    write_working_blob(path, hash)?;
+                }
+                let state = if head.get(path) == Some(hash) {
+                    NyxFileState::Committed
+                } else {
+                    NyxFileState::Staged
+                };
+                index.set_entry(path, hash, state);
+            }
+            MergedEntry::Conflict {
+                content,
+                ours,
+                theirs,
+            } => {
+                write_working_file(path, content)?;
+                let hash = ours.as_ref().or(theirs.as_ref()).unwrap();
+                index.set_entry(path, hash, NyxFileState::Unmerged);
+            }
+        }
+    }
+    index.write();
+    Ok(())
+}
@@ -1,62 +1,251 @@
-#![feature(drain_filter, fs_try_exists)]
-use lazy_static::{__Deref, lazy_static};
+// Commands and data types live in modules named like their directory
+#![allow(clippy::module_inception)]
+use lazy_static::lazy_static;
 use std::env;
 
 pub mod core;
 
 use crate::core::cl_args::NyxCli;
 use crate::core::cl_args::NyxCommand;
-use crate::core::commands::add::add;
+use crate::core::commands::add::{add, AddOptions};
+use crate::core::commands::bisect::bisect;
+use crate::core::commands::blame::blame;
+use crate::core::commands::bundle::bundle;
 use crate::core::commands::cat_file::cat_file;
 use crate::core::commands::checkout::checkout;
+use crate::core::commands::cherry_pick::cherry_pick;
+use crate::core::commands::clone::clone;
 use crate::core::commands::commit::commit;
+use crate::core::commands::convert_objects::convert_objects;
+use crate::core::commands::fast_export::fast_export;
+use crate::core::commands::fast_import::fast_import;
+use crate::core::commands::fetch::fetch;
+use crate::core::commands::fsck::fsck;
+use crate::core::commands::gc::gc;
 use crate::core::commands::hash_object::hash_object;
-use crate::core::commands::init::init;
-use crate::core::commands::log::log;
+use crate::core::commands::import_git::import_git;
+use crate::core::commands::init::{init, parse_object_format};
+use crate::core::commands::lfs::lfs;
+use crate::core::commands::log::{log, LogOptions};
 use crate::core::commands::ls_file::ls_file;
+use crate::core::commands::prune::prune;
+use crate::core::commands::push::push;
+use crate::core::commands::rebase::rebase;
+use crate::core::commands::remote::remote;
+use crate::core::commands::reset::reset;
+use crate::core::commands::restore::restore;
+use crate::core::commands::revert::revert;
+use crate::core::commands::serve::serve;
+use crate::core::commands::show::show;
+use crate::core::commands::stash::stash;
 use crate::core::commands::status::status;
+use crate::core::commands::tag::tag;
+use crate::core::commands::verify_commit::verify_commit;
+use crate::core::commands::verify_tag::verify_tag;
 use crate::core::errors::NyxError;
 use crate::core::file_system::NyxFileSystem;
 use crate::core::object_type::NyxObjectType;
+use crate::core::sequencer::SequencerOptions;
 
 lazy_static! {
     static ref FILE_SYSTEM: NyxFileSystem = NyxFileSystem::new();
 }
 
 pub fn run(cli: NyxCli) -> Result<(), NyxError> {
+    // Cloning switches into the new repository before FILE_SYSTEM is set up
+    if let Some(NyxCommand::Clone { source, directory }) = &cli.command {
+        return clone(source, directory);
+    }
+
     if !FILE_SYSTEM.is_repository() {
-        match &cli.command {
-            Some(command) => match command {
-                NyxCommand::Init => {
-                    if let Ok(_) = init() {
-                        let nyx_dir = env::current_dir().unwrap().join(".nyx");
-                        println!("Initialized empty nyx repository in {:?}.", nyx_dir);
-                        return Ok(());
-                    }
+        if let Some(command) = &cli.command {
+            match command {
+                NyxCommand::Init { object_format } => {
+                    init(parse_object_format(object_format)?)?;
+                    let nyx_dir = env::current_dir().unwrap().join(".nyx");
+                    println!("Initialized empty nyx repository in {:?}.", nyx_dir);
+                    return Ok(());
                 }
                 _ => {
                     eprintln!("Not a nyx repository (or any of the parent directories)");
                     std::process::exit(1);
                 }
-            },
-            _ => (),
+            }
         }
     }
 
     match &cli.command {
         Some(command) => match command {
-            NyxCommand::HashObject { path } => _ = hash_object(path)?,
-            NyxCommand::CatFile { hash } => _ = cat_file(hash)?,
-            NyxCommand::Add { paths } => add(paths.deref().to_vec())?,
+            NyxCommand::HashObject {
+                paths,
+                object_type,
+                write,
+                stdin,
+                stdin_paths,
+            } => hash_object(paths, object_type, *write, *stdin, *stdin_paths)?,
+            NyxCommand::CatFile { hash } => cat_file(hash)?,
+            NyxCommand::Add {
+                paths,
+                all,
+                update,
+                dry_run,
+                force,
+                patch,
+            } => add(
+                paths,
+                AddOptions {
+                    all: *all,
+                    update: *update,
+                    dry_run: *dry_run,
+                    force: *force,
+                    patch: *patch,
+                },
+            )?,
             NyxCommand::LsFile => ls_file(),
-            NyxCommand::Commit { message } => commit(message),
-            NyxCommand::Status => status(),
-            NyxCommand::Log => log(),
+            NyxCommand::Prune {
+                expire,
+                dry_run,
+                verbose,
+            } => prune(expire, *dry_run, *verbose)?,
+            NyxCommand::Fsck {
+                unreachable,
+                no_dangling,
+                no_reflogs,
+            } => fsck(*unreachable, *no_dangling, *no_reflogs)?,
+            NyxCommand::VerifyCommit { revs } => verify_commit(revs)?,
+            NyxCommand::VerifyTag { names } => verify_tag(names)?,
+            NyxCommand::Commit {
+                message,
+                no_verify,
+                sign,
+            } => commit(message, *no_verify, *sign)?,
+            NyxCommand::Status {
+                paths,
+                short,
+                branch,
+                porcelain,
+                null,
+            } => status(paths, *short, *branch, porcelain, *null)?,
+            NyxCommand::Log {
+                revisions,
+                paths,
+                max_count,
+                since,
+                until,
+                author,
+                grep,
+                format,
+                oneline,
+                graph,
+                topo_order,
+                date_order,
+                show_signature,
+            } => log(
+                revisions,
+                paths,
+                LogOptions {
+                    max_count: *max_count,
+                    since: since.clone(),
+                    until: until.clone(),
+                    author: author.clone(),
+                    grep: grep.clone(),
+                    format: format.clone(),
+                    oneline: *oneline,
+                    graph: *graph,
+                    topo_order: *topo_order,
+                    date_order: *date_order,
+                    show_signature: *show_signature,
+                },
+            )?,
+            NyxCommand::Remote { verbose, command } => remote(command, *verbose)?,
+            NyxCommand::Fetch { remote } => fetch(remote)?,
+            NyxCommand::Push {
+                remote,
+                refspec,
+                force,
+                set_upstream,
+            } => push(remote, refspec, *force, *set_upstream)?,
+            NyxCommand::Serve { bind, port } => serve(bind, *port)?,
+            NyxCommand::Gc { prune, dry_run } => gc(prune, *dry_run)?,
+            NyxCommand::ImportGit {
+                path,
+                map_file,
+                force,
+            } => import_git(path, map_file, *force)?,
+            NyxCommand::ConvertObjects {
+                object_format,
+                map_file,
+            } => convert_objects(object_format, map_file)?,
+            NyxCommand::FastExport { revisions } => fast_export(revisions)?,
+            NyxCommand::FastImport => fast_import()?,
+            NyxCommand::Bundle { command } => bundle(command)?,
+            NyxCommand::Lfs { command } => lfs(command)?,
+            NyxCommand::Bisect { command } => bisect(command)?,
+            NyxCommand::Blame { path, rev, range } => blame(path, rev, range)?,
+            NyxCommand::Show { revs } => show(revs)?,
+            NyxCommand::Tag {
+                name,
+                rev,
+                annotate,
+                message,
+                delete,
+                force,
+                sign,
+            } => tag(name, rev, *annotate, message, *delete, *force, *sign)?,
             NyxCommand::Checkout { hash } => checkout(hash),
-            NyxCommand::Init => {
+            NyxCommand::Reset { paths, patch } => reset(paths, *patch)?,
+            NyxCommand::Restore { paths, patch } => restore(paths, *patch)?,
+            NyxCommand::CherryPick {
+                revs,
+                proceed,
+                abort,
+                skip,
+                record_origin,
+                mainline,
+                no_commit,
+            } => cherry_pick(
+                revs,
+                *proceed,
+                *abort,
+                *skip,
+                SequencerOptions {
+                    mainline: *mainline,
+                    record_origin: *record_origin,
+                    no_commit: *no_commit,
+                },
+            )?,
+            NyxCommand::Revert {
+                revs,
+                proceed,
+                abort,
+                skip,
+                mainline,
+                no_commit,
+            } => revert(
+                revs,
+                *proceed,
+                *abort,
+                *skip,
+                SequencerOptions {
+                    mainline: *mainline,
+                    record_origin: false,
+                    no_commit: *no_commit,
+                },
+            )?,
+            NyxCommand::Rebase {
+                upstream,
+                onto,
+                interactive,
+                proceed,
+                abort,
+                skip,
+            } => rebase(upstream, onto, *interactive, *proceed, *abort, *skip)?,
+            NyxCommand::Stash { command } => stash(command)?,
+            NyxCommand::Init { .. } => {
                 eprintln!("Repository already initialized");
                 std::process::exit(1);
             }
+            NyxCommand::Clone { .. } => unreachable!("clone is handled before"),
         },
         None => println!("Command not known! Type nyx --help for help"),
     };
@@ -4,5 +4,8 @@ use nyx::core::cl_args::NyxCli;
 fn main() {
     let cli = NyxCli::parse();
 
-    nyx::run(cli).unwrap();
+    if let Err(err) = nyx::run(cli) {
+        eprintln!("fatal: {}", err);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEMP_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory below the temporary directory of the system, removed
+/// with everything in it once dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!(
+            "nyx-test-{}-{}-{}",
+            name,
+            process::id(),
+            TEMP_DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Runs the nyx binary in `dir`.
+pub fn nyx_output(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nyx"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+/// Runs the nyx binary in `dir` and returns its output, failing the
+/// test unless it succeeds.
+pub fn nyx(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let output = nyx_output(dir, args);
+    assert!(
+        output.status.success(),
+        "nyx {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+/// Like `nyx`, with the output as trimmed text.
+pub fn nyx_text(dir: &Path, args: &[&str]) -> String {
+    String::from_utf8(nyx(dir, args))
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+/// Creates a repository in a new directory `name` below `parent`.
+pub fn init_repo(parent: &Path, name: &str) -> PathBuf {
+    let dir = parent.join(name);
+    fs::create_dir_all(&dir).unwrap();
+    nyx(&dir, &["init"]);
+    dir
+}
+
+/// Stages and commits a file with the given content, returns the hash
+/// of the commit.
+pub fn commit_file(repo: &Path, path: &str, content: &[u8], message: &str) -> String {
+    fs::write(repo.join(path), content).unwrap();
+    nyx(repo, &["add", path]);
+    nyx(repo, &["commit", "-m", message]);
+    nyx_text(repo, &["log", "-n", "1", "--format=%H"])
+}
+
+/// Binary content that is not valid UTF-8, the same for the same seed.
+pub fn sample_bytes(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 24) as u8
+        })
+        .collect()
+}
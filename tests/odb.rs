@@ -0,0 +1,194 @@
+mod common;
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use common::{sample_bytes, TempDir};
+use nyx::core::object_format::ObjectFormat;
+use nyx::core::object_type::NyxObjectType;
+use nyx::core::odb::{
+    database::ObjectDatabase, loose::LooseObjectDatabase, memory::MemoryObjectDatabase,
+    writer::ObjectWriter,
+};
+
+/// Blobs above this size are chunked by the chunking loose backend.
+const CHUNK_THRESHOLD: u64 = 1024;
+
+fn backends(dir: &Path) -> Vec<(&'static str, Box<dyn ObjectDatabase>)> {
+    vec![
+        ("memory", Box::new(MemoryObjectDatabase::new())),
+        (
+            "loose",
+            Box::new(LooseObjectDatabase::with_format(
+                dir.join("loose"),
+                ObjectFormat::Sha1,
+                None,
+            )),
+        ),
+        (
+            "chunked",
+            Box::new(LooseObjectDatabase::with_format(
+                dir.join("chunked"),
+                ObjectFormat::Sha1,
+                Some(CHUNK_THRESHOLD),
+            )),
+        ),
+    ]
+}
+
+/// The hash and the raw object of a blob with the given content.
+fn blob(content: &[u8]) -> (String, Vec<u8>) {
+    let mut writer = ObjectWriter::with_format(
+        Vec::new(),
+        NyxObjectType::Blob,
+        content.len() as u64,
+        ObjectFormat::Sha1,
+    )
+    .unwrap();
+    writer.write_all(content).unwrap();
+    let (raw, hash) = writer.finish().unwrap();
+    (hash, raw)
+}
+
+#[test]
+fn writer_adds_the_object_header() {
+    let (hash, raw) = blob(b"hello\n");
+    assert_eq!(raw, b"blob 6\0hello\n");
+    assert_eq!(hash, ObjectFormat::Sha1.digest(&raw));
+}
+
+#[test]
+fn objects_read_back_as_written() {
+    let dir = TempDir::new("odb-write");
+    let small = blob(b"small\n");
+    let large = blob(&sample_bytes(3 * CHUNK_THRESHOLD as usize + 7, 4));
+
+    for (name, database) in backends(dir.path()) {
+        for (hash, raw) in [&small, &large] {
+            assert!(!database.exists(hash), "{}", name);
+            database.write(hash, raw).unwrap();
+            database.write(hash, raw).unwrap();
+            assert!(database.exists(hash), "{}", name);
+            assert_eq!(&database.read(hash).unwrap(), raw, "{}", name);
+        }
+
+        let mut expected = vec![small.0.clone(), large.0.clone()];
+        expected.sort();
+        assert_eq!(database.iter().collect::<Vec<_>>(), expected, "{}", name);
+        assert_eq!(
+            database.with_prefix(&small.0[..4]),
+            vec![small.0.clone()],
+            "{}",
+            name
+        );
+
+        let missing = ObjectFormat::Sha1.digest(b"missing");
+        assert!(!database.exists(&missing), "{}", name);
+        assert!(database.read(&missing).is_err(), "{}", name);
+    }
+}
+
+#[test]
+fn readers_stream_the_content() {
+    let dir = TempDir::new("odb-reader");
+    let content = sample_bytes(5 * CHUNK_THRESHOLD as usize + 3, 5);
+    let (hash, raw) = blob(&content);
+
+    for (name, database) in backends(dir.path()) {
+        database.write(&hash, &raw).unwrap();
+        let mut reader = database.reader(&hash).unwrap();
+        assert_eq!(reader.object_type, NyxObjectType::Blob, "{}", name);
+        assert_eq!(reader.size, content.len() as u64, "{}", name);
+        assert_eq!(
+            database.size(&hash).unwrap(),
+            content.len() as u64,
+            "{}",
+            name
+        );
+
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert!(read == content, "{}", name);
+    }
+}
+
+#[test]
+fn streamed_writes_match_whole_writes() {
+    let dir = TempDir::new("odb-stream");
+    for content in [
+        b"streamed\n".to_vec(),
+        sample_bytes(4 * CHUNK_THRESHOLD as usize, 6),
+    ] {
+        let (hash, raw) = blob(&content);
+        for (name, database) in backends(dir.path()) {
+            let written = database
+                .write_stream(NyxObjectType::Blob, content.len() as u64, &mut &content[..])
+                .unwrap();
+            assert_eq!(written, hash, "{}", name);
+            assert!(database.read(&hash).unwrap() == raw, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn removed_objects_are_gone() {
+    let dir = TempDir::new("odb-remove");
+    let kept = blob(b"kept\n");
+    let removed = blob(&sample_bytes(2 * CHUNK_THRESHOLD as usize, 7));
+
+    for (name, database) in backends(dir.path()) {
+        for (hash, raw) in [&kept, &removed] {
+            database.write(hash, raw).unwrap();
+        }
+        let hashes = HashSet::from([removed.0.clone()]);
+        let size = database.removable_size(&hashes).unwrap();
+        assert!(size > 0, "{}", name);
+        assert!(database.exists(&removed.0), "{}", name);
+
+        assert_eq!(database.remove(&hashes).unwrap(), size, "{}", name);
+        assert!(!database.exists(&removed.0), "{}", name);
+        assert_eq!(database.read(&kept.0).unwrap(), kept.1, "{}", name);
+        assert_eq!(
+            database.iter().collect::<Vec<_>>(),
+            vec![kept.0.clone()],
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn truncated_chunks_fail_to_read() {
+    let dir = TempDir::new("odb-truncated");
+    let database = LooseObjectDatabase::with_format(
+        dir.path().to_path_buf(),
+        ObjectFormat::Sha1,
+        Some(CHUNK_THRESHOLD),
+    );
+    let (hash, raw) = blob(&sample_bytes(3 * CHUNK_THRESHOLD as usize, 8));
+    database.write(&hash, &raw).unwrap();
+
+    let chunk_dir = fs::read_dir(dir.path().join("chunks"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let chunk = fs::read_dir(chunk_dir.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let content = fs::read(&chunk).unwrap();
+    fs::write(&chunk, &content[..content.len() - 1]).unwrap();
+
+    assert!(database.read(&hash).is_err());
+    let mut read = Vec::new();
+    assert!(database
+        .reader(&hash)
+        .unwrap()
+        .read_to_end(&mut read)
+        .is_err());
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::{commit_file, init_repo, nyx, nyx_output, nyx_text, TempDir};
+
+/// `nyx serve` for a repository, stopped once dropped.
+struct Server {
+    process: Child,
+    port: u16,
+}
+
+impl Server {
+    fn start(repo: &Path) -> Self {
+        // Let the system pick a free port, then hand it to the server
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let process = Command::new(env!("CARGO_BIN_EXE_nyx"))
+            .args(["serve", "--port", &port.to_string()])
+            .current_dir(repo)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut server = Self { process, port };
+
+        let started = Instant::now();
+        while TcpStream::connect(("127.0.0.1", port)).is_err() {
+            assert!(
+                server.process.try_wait().unwrap().is_none(),
+                "nyx serve exited"
+            );
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "nyx serve did not start"
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+        server
+    }
+
+    fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Sends a request and returns the status code of the response.
+    fn request(&self, method: &str, path: &str, body: &[u8]) -> u16 {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        let head = format!(
+            "{} {} HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            method,
+            path,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+            .split(' ')
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .unwrap_or_else(|| panic!("invalid response '{}'", response))
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        _ = self.process.kill();
+        _ = self.process.wait();
+    }
+}
+
+fn read_ref(repo: &Path, name: &str) -> String {
+    fs::read_to_string(repo.join(".nyx").join("refs").join(name))
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn clone_fetch_and_push_over_http() {
+    let dir = TempDir::new("serve");
+    let origin = init_repo(dir.path(), "origin");
+    let first = commit_file(&origin, "file.txt", b"first\n", "first");
+    let server = Server::start(&origin);
+
+    nyx(dir.path(), &["clone", &server.url(), "copy"]);
+    let copy = dir.path().join("copy");
+    assert_eq!(fs::read(copy.join("file.txt")).unwrap(), b"first\n");
+    assert_eq!(read_ref(&copy, "master"), first);
+
+    let second = commit_file(&origin, "file.txt", b"second\n", "second");
+    nyx(&copy, &["fetch"]);
+    assert_eq!(read_ref(&copy, "remotes/origin/master"), second);
+
+    let pushed = commit_file(&copy, "other.txt", b"pushed\n", "pushed");
+    nyx(&copy, &["push", "origin", "master:feature"]);
+    assert_eq!(read_ref(&origin, "feature"), pushed);
+    let blob = nyx_text(&copy, &["hash-object", "other.txt"]);
+    assert_eq!(nyx_text(&origin, &["cat-file", &blob]), "pushed");
+
+    // The checked out branch of the origin is not moved
+    let rejected = nyx_output(&copy, &["push", "--force", "origin", "master"]);
+    assert!(!rejected.status.success());
+    assert_eq!(read_ref(&origin, "master"), second);
+}
+
+#[test]
+fn malformed_pack_does_not_stop_the_server() {
+    let dir = TempDir::new("serve-malformed");
+    let origin = init_repo(dir.path(), "origin");
+    commit_file(&origin, "file.txt", b"content\n", "first");
+    let server = Server::start(&origin);
+
+    let status = server.request("POST", "/receive-pack", b"\nNYXPACK 999999999999999999\n");
+    assert_eq!(status, 400);
+    let status = server.request("POST", "/receive-pack", b"\nNYXPACK 1\nnot a pack");
+    assert_eq!(status, 400);
+
+    assert_eq!(server.request("GET", "/info/refs", b""), 200);
+    nyx(dir.path(), &["clone", &server.url(), "copy"]);
+    assert_eq!(
+        fs::read(dir.path().join("copy").join("file.txt")).unwrap(),
+        b"content\n"
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{commit_file, init_repo, nyx, nyx_text, sample_bytes, TempDir};
+use nyx::core::object_format::ObjectFormat;
+
+/// Small enough that the files below take many buffers.
+const BUFFER_SIZE: usize = 1024;
+
+const FILE_SIZE: usize = 5 * BUFFER_SIZE + 123;
+
+fn blob_hash(content: &[u8]) -> String {
+    let mut raw = format!("blob {}\0", content.len()).into_bytes();
+    raw.extend_from_slice(content);
+    ObjectFormat::Sha1.digest(&raw)
+}
+
+fn streaming_repo(dir: &Path) -> PathBuf {
+    let repo = init_repo(dir, "repo");
+    fs::write(
+        repo.join(".nyx").join("config"),
+        format!("[core]\n\tstreamBufferSize = {}\n", BUFFER_SIZE),
+    )
+    .unwrap();
+    repo
+}
+
+#[test]
+fn hash_object_streams_files_larger_than_the_buffer() {
+    let dir = TempDir::new("stream-hash");
+    let repo = streaming_repo(dir.path());
+    let content = sample_bytes(FILE_SIZE, 1);
+    fs::write(repo.join("large.bin"), &content).unwrap();
+
+    let expected = blob_hash(&content);
+    assert_eq!(nyx_text(&repo, &["hash-object", "large.bin"]), expected);
+    assert_eq!(
+        nyx_text(&repo, &["hash-object", "-w", "large.bin"]),
+        expected
+    );
+
+    let mut printed = nyx(&repo, &["cat-file", &expected]);
+    assert_eq!(printed.pop(), Some(b'\n'));
+    assert_eq!(printed, content);
+}
+
+#[test]
+fn committed_files_larger_than_the_buffer_round_trip() {
+    let dir = TempDir::new("stream-commit");
+    let repo = streaming_repo(dir.path());
+    let first = sample_bytes(FILE_SIZE, 2);
+    let second = sample_bytes(FILE_SIZE + BUFFER_SIZE, 3);
+
+    let first_commit = commit_file(&repo, "large.bin", &first, "first");
+    commit_file(&repo, "large.bin", &second, "second");
+
+    for content in [&first, &second] {
+        let mut printed = nyx(&repo, &["cat-file", &blob_hash(content)]);
+        assert_eq!(printed.pop(), Some(b'\n'));
+        assert_eq!(&printed, content);
+    }
+
+    nyx(&repo, &["checkout", &first_commit]);
+    assert_eq!(fs::read(repo.join("large.bin")).unwrap(), first);
+    assert_eq!(
+        nyx_text(&repo, &["hash-object", "large.bin"]),
+        blob_hash(&first)
+    );
+
+    nyx(&repo, &["checkout", "master"]);
+    assert_eq!(fs::read(repo.join("large.bin")).unwrap(), second);
+}